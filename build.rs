@@ -15,6 +15,8 @@ Using a higher order model will cause runtime crashes.\n
 cargo:warning=Set `KENLM_MAX_ORDER=5` in your env to change it."
     );
     let max_order_flag = format!("-DKENLM_MAX_ORDER={max_order}");
+    #[cfg(feature = "napi")]
+    napi_build::setup();
 
     let mut b = autocxx_build::Builder::new("src/cxx/bridge.rs", &[&"src/cxx/"])
         .extra_clang_args(&[&max_order_flag])
@@ -0,0 +1,51 @@
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use clap::Parser;
+use kenlm_rs::{CoverageStats, ScoreOptions};
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(long, default_value = "test_data/carol.bin")]
+    model_path: PathBuf,
+    /// Corpus with one whitespace tokenized sentence per line
+    corpus: PathBuf,
+    #[clap(long, default_value = "10")]
+    top_missing: usize,
+}
+
+fn main() -> anyhow::Result<(), anyhow::Error> {
+    let Args {
+        model_path,
+        corpus,
+        top_missing,
+    } = Args::parse();
+
+    let model = kenlm_rs::Model::new(
+        model_path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Path could not be converted into &str"))?,
+        false,
+    )?;
+
+    let mut coverage = CoverageStats::new(&model);
+    let reader = std::io::BufReader::new(std::fs::File::open(corpus)?);
+    for line in reader.lines() {
+        let line = line?;
+        let sentence = line.split_ascii_whitespace().collect::<Vec<&str>>();
+        coverage.add_sentence(&model, &sentence, ScoreOptions::new(true, true));
+    }
+
+    for order in 1..=model.get_order() as usize {
+        eprintln!(
+            "{order}-grams: {} found, {} backed off",
+            coverage.found(order),
+            coverage.backed_off(order)
+        );
+    }
+    eprintln!("Most frequent missing n-grams:");
+    for (ngram, count) in coverage.most_frequent_missing(top_missing) {
+        eprintln!("{count}\t{ngram}");
+    }
+    Ok(())
+}
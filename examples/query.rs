@@ -0,0 +1,37 @@
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use clap::Parser;
+use kenlm_rs::QueryPrinter;
+
+/// Scores whitespace tokenized sentences from stdin, printing them like KenLM's `query`
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(long, default_value = "test_data/carol.bin")]
+    model_path: PathBuf,
+    /// Do not wrap the input in sentence context
+    #[clap(action, short = 'n', default_value = "false")]
+    no_sentence_context: bool,
+}
+
+fn main() -> anyhow::Result<(), anyhow::Error> {
+    let Args {
+        model_path,
+        no_sentence_context,
+    } = Args::parse();
+
+    let model = kenlm_rs::Model::new(
+        model_path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Path could not be converted into &str"))?,
+        false,
+    )?;
+
+    let stdout = std::io::stdout();
+    let mut printer = QueryPrinter::new(stdout.lock()).sentence_context(!no_sentence_context);
+    for line in std::io::stdin().lock().lines() {
+        printer.query_line(&model, &line?)?;
+    }
+    printer.summary()?;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use clap::Parser;
+use kenlm_rs::reader::arpa::read_arpa;
+use kenlm_rs::{probe, FileKind};
+
+/// Prints counts, quantiles and histograms of log-probabilities and backoffs per order.
+///
+/// Only ARPA files are supported, the bridge to KenLM can't enumerate the n-grams of a binary.
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(long, default_value = "test_data/arpa/lm_small.arpa")]
+    model_path: PathBuf,
+    #[clap(long, default_value = "10")]
+    bins: usize,
+}
+
+fn main() -> anyhow::Result<(), anyhow::Error> {
+    let Args { model_path, bins } = Args::parse();
+
+    let path = model_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Path could not be converted into &str"))?;
+    match probe(path)? {
+        FileKind::Arpa => {}
+        FileKind::Binary(model_type) => anyhow::bail!(
+            "{path} is a {model_type:?} binary, statistics can only be computed from the ARPA file"
+        ),
+        FileKind::Unknown => anyhow::bail!("{path} is neither an ARPA file nor a KenLM binary"),
+    }
+
+    let sections = read_arpa(BufReader::new(std::fs::File::open(&model_path)?))?;
+    for (order, section) in sections.backoffs.iter().enumerate() {
+        println!("{}-grams: {}", order + 1, section.len());
+        print_stats(
+            "log10 probabilities",
+            section.iter().map(|e| e.prob_backoff.log_prob).collect(),
+            bins,
+        );
+        print_stats(
+            "backoffs",
+            section.iter().map(|e| e.prob_backoff.backoff).collect(),
+            bins,
+        );
+    }
+    println!(
+        "{}-grams: {}",
+        sections.backoffs.len() + 1,
+        sections.no_backoff.len()
+    );
+    print_stats(
+        "log10 probabilities",
+        sections.no_backoff.iter().map(|e| e.prob).collect(),
+        bins,
+    );
+    Ok(())
+}
+
+fn print_stats(name: &str, mut values: Vec<f32>, bins: usize) {
+    if values.is_empty() || bins == 0 {
+        return;
+    }
+    values.sort_by(f32::total_cmp);
+    let quantile = |q: f32| values[((values.len() - 1) as f32 * q).round() as usize];
+    println!(
+        "  {name}: min {} | 25% {} | median {} | 75% {} | max {}",
+        quantile(0.),
+        quantile(0.25),
+        quantile(0.5),
+        quantile(0.75),
+        quantile(1.)
+    );
+
+    let (min, max) = (values[0], values[values.len() - 1]);
+    let width = (max - min) / bins as f32;
+    let mut histogram = vec![0usize; bins];
+    for value in &values {
+        let bin = if width > 0. {
+            (((value - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        histogram[bin] += 1;
+    }
+    let largest = histogram.iter().copied().max().unwrap_or_default().max(1);
+    for (bin, count) in histogram.iter().enumerate() {
+        let lower = min + bin as f32 * width;
+        let bar = "#".repeat(count * 40 / largest);
+        println!(
+            "    [{lower:>10.4}, {:>10.4}) {count:>8} {bar}",
+            lower + width
+        );
+    }
+}
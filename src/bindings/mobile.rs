@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use crate::{Error, Model};
+
+/// A KenLM model for Kotlin and Swift, exported through UniFFI
+///
+/// The model is loaded with its vocab and completion index. Methods may be called from any
+/// thread, the model is shared as `Arc` by the generated bindings.
+#[derive(uniffi::Object)]
+pub struct LanguageModel {
+    model: Model,
+}
+
+#[uniffi::export]
+impl LanguageModel {
+    /// Loads the model at `path`, an ARPA file or a KenLM binary
+    #[uniffi::constructor]
+    pub fn new(path: String) -> Result<Arc<Self>, Error> {
+        let model = super::load(&path)?;
+        Ok(Arc::new(LanguageModel { model }))
+    }
+
+    /// Log10 probability of `sentence`, see [Model::score_sentence]
+    pub fn score(&self, sentence: Vec<String>, bos: bool, eos: bool) -> f32 {
+        self.model.score_sentence(&sentence, bos, eos)
+    }
+
+    /// The `limit` most probable words starting with `prefix` after `context`
+    ///
+    /// `context` is in reading order, start it with `<s>` to complete the first word of a
+    /// sentence.
+    pub fn complete(&self, context: Vec<String>, prefix: String, limit: u32) -> Vec<String> {
+        super::complete(&self.model, &context, &prefix, limit as usize)
+    }
+
+    /// Order of the model
+    pub fn order(&self) -> u8 {
+        self.model.get_order()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LanguageModel;
+    use crate::{Error, Model};
+
+    #[test]
+    fn scores_like_model() {
+        let expected = Model::new("test_data/test.bin", false).unwrap();
+        let model = LanguageModel::new("test_data/test.bin".into()).unwrap();
+        let sentence = vec!["i".to_string(), "have".to_string()];
+        assert_eq!(
+            model.score(sentence.clone(), true, true),
+            expected.score_sentence(&sentence, true, true)
+        );
+        assert_eq!(model.complete(vec![], "<".into(), 2).len(), 2);
+        assert!(matches!(
+            LanguageModel::new("test_data/missing.bin".into()),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(feature = "uniffi")]
+mod mobile;
+#[cfg(feature = "napi")]
+mod node;
+
+#[cfg(feature = "uniffi")]
+pub use self::mobile::LanguageModel;
+#[cfg(feature = "napi")]
+pub use self::node::NodeModel;
+
+use crate::{Error, Model};
+
+/// Loads the model at `path` with its vocab and completion index
+fn load(path: &str) -> Result<Model, Error> {
+    let mut model = Model::builder(path).store_vocab(true).build()?;
+    model.build_completion_index()?;
+    Ok(model)
+}
+
+/// The `limit` most probable words starting with `prefix` after `context`
+///
+/// `context` is in reading order, start it with `<s>` to complete the first word of a sentence.
+fn complete(model: &Model, context: &[String], prefix: &str, limit: usize) -> Vec<String> {
+    let state = model.state_from_context(context);
+    let mut completions = match model.complete(prefix) {
+        Some(indices) => indices
+            .map(|index| (model.score_final_index(&state, index), index))
+            .collect::<Vec<_>>(),
+        None => return vec![],
+    };
+    completions.sort_by(|(score, _), (other, _)| other.total_cmp(score));
+    completions
+        .into_iter()
+        .take(limit)
+        .filter_map(|(_, index)| model.get_word(index).map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{complete, load};
+
+    #[test]
+    fn completions_are_ranked_by_probability() {
+        let model = load("test_data/test.bin").unwrap();
+        let context = vec!["<s>".to_string()];
+        let completions = complete(&model, &context, "<", 10);
+        assert_eq!(completions.len(), 3);
+        let state = model.state_from_context(&context);
+        let scores = completions
+            .iter()
+            .map(|word| model.score_final_word(&state, word))
+            .collect::<Vec<_>>();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(complete(&model, &context, "<", 1), completions[..1]);
+        assert!(complete(&model, &context, "toast", 10).is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Task};
+use napi_derive::napi;
+
+use crate::{Error, Model};
+
+impl From<Error> for napi::Error {
+    fn from(err: Error) -> Self {
+        napi::Error::from_reason(err.to_string())
+    }
+}
+
+/// A KenLM model for Node.js, exported through napi-rs as `Model`
+///
+/// The model is loaded with its vocab and completion index. The async methods run on the libuv
+/// thread pool and share the model with it.
+#[napi(js_name = "Model")]
+pub struct NodeModel {
+    model: Arc<Model>,
+}
+
+#[napi]
+impl NodeModel {
+    /// Loads the model at `path`, an ARPA file or a KenLM binary, blocks until it is loaded
+    #[napi(factory)]
+    pub fn load(path: String) -> napi::Result<Self> {
+        let model = super::load(&path)?;
+        Ok(NodeModel {
+            model: Arc::new(model),
+        })
+    }
+
+    /// Loads the model at `path` on the libuv thread pool
+    #[napi(ts_return_type = "Promise<Model>")]
+    pub fn load_async(path: String) -> AsyncTask<LoadTask> {
+        AsyncTask::new(LoadTask { path })
+    }
+
+    /// Log10 probability of `sentence`, with `<s>` and `</s>` unless disabled
+    #[napi]
+    pub fn score(&self, sentence: Vec<String>, bos: Option<bool>, eos: Option<bool>) -> f64 {
+        let score = self
+            .model
+            .score_sentence(&sentence, bos.unwrap_or(true), eos.unwrap_or(true));
+        f64::from(score)
+    }
+
+    /// Log10 probabilities of `sentences`, scored on the libuv thread pool
+    #[napi(ts_return_type = "Promise<number[]>")]
+    pub fn score_async(
+        &self,
+        sentences: Vec<Vec<String>>,
+        bos: Option<bool>,
+        eos: Option<bool>,
+    ) -> AsyncTask<ScoreTask> {
+        AsyncTask::new(ScoreTask {
+            model: self.model.clone(),
+            sentences,
+            bos: bos.unwrap_or(true),
+            eos: eos.unwrap_or(true),
+        })
+    }
+
+    /// The `limit` most probable words starting with `prefix` after `context`
+    ///
+    /// `context` is in reading order, start it with `<s>` to complete the first word of a
+    /// sentence.
+    #[napi]
+    pub fn complete(&self, context: Vec<String>, prefix: String, limit: u32) -> Vec<String> {
+        super::complete(&self.model, &context, &prefix, limit as usize)
+    }
+
+    /// Like `complete`, run on the libuv thread pool
+    #[napi(ts_return_type = "Promise<string[]>")]
+    pub fn complete_async(
+        &self,
+        context: Vec<String>,
+        prefix: String,
+        limit: u32,
+    ) -> AsyncTask<CompleteTask> {
+        AsyncTask::new(CompleteTask {
+            model: self.model.clone(),
+            context,
+            prefix,
+            limit: limit as usize,
+        })
+    }
+
+    /// Order of the model
+    #[napi(getter)]
+    pub fn order(&self) -> u32 {
+        u32::from(self.model.get_order())
+    }
+}
+
+/// Loads a model for [NodeModel::load_async]
+pub struct LoadTask {
+    path: String,
+}
+
+impl Task for LoadTask {
+    type Output = Model;
+    type JsValue = NodeModel;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(super::load(&self.path)?)
+    }
+
+    fn resolve(&mut self, _env: Env, model: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(NodeModel {
+            model: Arc::new(model),
+        })
+    }
+}
+
+/// Scores sentences for [NodeModel::score_async]
+pub struct ScoreTask {
+    model: Arc<Model>,
+    sentences: Vec<Vec<String>>,
+    bos: bool,
+    eos: bool,
+}
+
+impl Task for ScoreTask {
+    type Output = Vec<f64>;
+    type JsValue = Vec<f64>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(self
+            .sentences
+            .iter()
+            .map(|sentence| f64::from(self.model.score_sentence(sentence, self.bos, self.eos)))
+            .collect())
+    }
+
+    fn resolve(&mut self, _env: Env, scores: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(scores)
+    }
+}
+
+/// Completes a prefix for [NodeModel::complete_async]
+pub struct CompleteTask {
+    model: Arc<Model>,
+    context: Vec<String>,
+    prefix: String,
+    limit: usize,
+}
+
+impl Task for CompleteTask {
+    type Output = Vec<String>;
+    type JsValue = Vec<String>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(super::complete(
+            &self.model,
+            &self.context,
+            &self.prefix,
+            self.limit,
+        ))
+    }
+
+    fn resolve(&mut self, _env: Env, words: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(words)
+    }
+}
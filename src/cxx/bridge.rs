@@ -1,8 +1,10 @@
 // autocxx generates some stuff that makes clippy angry
 #![allow(clippy::all)]
 
+use ::std::any::Any;
 use ::std::cell::RefCell;
 use ::std::mem::size_of;
+use ::std::panic::{catch_unwind, AssertUnwindSafe};
 use ::std::rc::Rc;
 
 use autocxx::subclass::CppSubclassDefault;
@@ -18,22 +20,50 @@ include_cpp! {
     #include "lm/max_order.hh"
     #include "lm/binary_format.hh"
     #include "lm/facade.hh"
+    #include "lm/sizes.hh"
 
     safety!(unsafe)
     generate!("lm::ngram::SizeOfSanity")
     generate!("lm::ngram::ModelMaxOrder")
     generate_pod!("lm::ngram::FixedWidthParameters")
     generate_pod!("lm::ngram::State")
+    generate_pod!("lm::ngram::Left")
+    generate_pod!("lm::ngram::ChartState")
+    generate_pod!("lm::FullScoreReturn")
 
     generate!("util::LoadMethod")
     generate!("lm::base::Model")
     generate!("lm::base::Vocabulary")
     generate!("lm::base::LoadVirtualPtr")
+    generate!("lm::base::BaseScoreForgetState")
+    generate!("lm::base::StateHash")
+    generate!("lm::base::BaseScoreBatch")
+    generate!("lm::base::ChartStateHash")
+    generate!("lm::base::RuleScorer")
+    generate!("lm::base::RuleScorer_Create")
 
     generate!("lm::ngram::Config")
     generate!("lm::base::Config_Create")
     generate!("lm::ngram::Config_set_load_method")
     generate!("lm::ngram::Config_set_enumerate_callback")
+    generate!("lm::ngram::Config_set_show_progress")
+    generate!("lm::ngram::Config_set_messages_enabled")
+    generate!("lm::ngram::Config_set_unknown_missing")
+    generate!("lm::ngram::Config_set_sentence_marker_missing")
+    generate!("lm::ngram::Config_set_positive_log_probability")
+    generate!("lm::ngram::Config_set_unknown_missing_logprob")
+    generate!("lm::ngram::Config_set_probing_multiplier")
+    generate!("lm::ngram::Config_set_building_memory")
+    generate!("lm::ngram::Config_set_arpa_complain")
+    generate!("lm::ngram::Config_set_huge_pages")
+    generate!("lm::ngram::Config_set_lock_memory")
+    generate!("lm::ngram::Config_set_temporary_directory_prefix")
+    generate!("lm::ngram::Config_set_write_mmap")
+    generate!("lm::ngram::Config_set_include_vocab")
+    generate!("lm::ngram::Config_set_quantization_bits")
+    generate!("lm::ngram::Config_set_pointer_bhiksha_bits")
+    generate!("lm::ngram::EstimateSize")
+    generate!("lm::ngram::EstimateSizes")
     generate!("lm::WordIndex")
     generate!("StringPiece")
     subclass!("lm::EnumerateVocab", VocabFetchCallback)
@@ -53,16 +83,51 @@ impl Clone for lm::ngram::State {
     }
 }
 
+impl Clone for lm::ngram::Left {
+    fn clone(&self) -> Self {
+        Self {
+            pointers: self.pointers,
+            length: self.length,
+            full: self.full,
+        }
+    }
+}
+
+impl Clone for lm::ngram::ChartState {
+    fn clone(&self) -> Self {
+        Self {
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
 #[is_subclass(superclass("EnumerateVocab"))]
 #[derive(Default)]
 pub struct VocabFetchCallback {
-    pub vocab: Vec<String>,
+    /// The stored words paired with the index KenLM enumerated them with
+    pub vocab: Vec<(u32, String)>,
+    /// Called with every enumerated word, the word is only stored in `vocab` if `store` is set
+    pub on_word: Option<Box<dyn FnMut(u32, &str)>>,
+    pub store: bool,
+    /// Panic of `on_word` or of adding a word, unwinding into KenLM is undefined behavior so it
+    /// is caught and resumed once KenLM returns
+    pub panic: Option<Box<dyn Any + Send>>,
 }
 
-impl EnumerateVocab_methods for VocabFetchCallback {
-    fn Add(&mut self, index: WordIndex, string: &StringPiece) {
-        // make clippy happy
-        let _ = index;
+impl VocabFetchCallback {
+    fn add(&mut self, index: WordIndex, string: &StringPiece) {
+        if let Some(on_word) = self.on_word.as_mut() {
+            let string = string.as_string();
+            // safety: as below, this is only none if the kenlm vocab contains a null ptr
+            on_word(
+                u32::from(index),
+                &string.as_ref().unwrap().to_string_lossy(),
+            );
+        }
+        if !self.store {
+            return;
+        }
         let string = string
             .as_string()
             .as_ref()
@@ -74,7 +139,19 @@ impl EnumerateVocab_methods for VocabFetchCallback {
             .unwrap()
             .to_string();
 
-        self.vocab.push(string);
+        self.vocab.push((u32::from(index), string));
+    }
+}
+
+impl EnumerateVocab_methods for VocabFetchCallback {
+    fn Add(&mut self, index: WordIndex, string: &StringPiece) {
+        // the remaining words are skipped after a panic
+        if self.panic.is_some() {
+            return;
+        }
+        if let Err(panic) = catch_unwind(AssertUnwindSafe(|| self.add(index, string))) {
+            self.panic = Some(panic);
+        }
     }
 }
 
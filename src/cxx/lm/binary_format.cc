@@ -171,7 +171,7 @@ namespace lm
     const std::size_t kInvalidSize = static_cast<std::size_t>(-1);
 
     BinaryFormat::BinaryFormat(const Config &config)
-        : write_method_(config.write_method), write_mmap_(config.write_mmap), load_method_(config.load_method),
+        : write_method_(config.write_method), write_mmap_(config.write_mmap), load_method_(config.load_method), huge_pages_(config.huge_pages), lock_memory_(config.lock_memory),
           header_size_(kInvalidSize), vocab_size_(kInvalidSize), vocab_string_offset_(kInvalidOffset) {}
 
     void BinaryFormat::InitializeBinary(int fd, ModelType model_type, unsigned int search_version, Parameters &params)
@@ -197,7 +197,9 @@ namespace lm
       uint64_t total_map = static_cast<uint64_t>(header_size_) + static_cast<uint64_t>(size);
       UTIL_THROW_IF(file_size != util::kBadSize && file_size < total_map, FormatLoadException, "Binary file has size " << file_size << " but the headers say it should be at least " << total_map);
 
-      util::MapRead(load_method_, file_.get(), 0, util::CheckOverflow(total_map), mapping_);
+      util::MapRead(load_method_, file_.get(), 0, util::CheckOverflow(total_map), mapping_, huge_pages_);
+      if (lock_memory_)
+        util::LockOrThrow(mapping_.get(), mapping_.size());
 
       vocab_string_offset_ = total_map;
       return reinterpret_cast<uint8_t *>(mapping_.get()) + header_size_;
@@ -246,6 +248,11 @@ namespace lm
         assert(header_size_ == 0 || write_mmap_);
         vocab_base = reinterpret_cast<uint8_t *>(memory_vocab_.get()) + header_size_;
         util::AdviseHugePages(memory_search_.get(), memory_size);
+        if (lock_memory_ && !write_mmap_)
+        {
+          util::LockOrThrow(memory_vocab_.get(), memory_vocab_.size());
+          util::LockOrThrow(memory_search_.get(), memory_search_.size());
+        }
         return reinterpret_cast<uint8_t *>(memory_search_.get());
       }
 
@@ -82,6 +82,8 @@ class BinaryFormat {
     const Config::WriteMethod write_method_;
     const char *write_mmap_;
     util::LoadMethod load_method_;
+    bool huge_pages_;
+    bool lock_memory_;
 
     // File behind memory, if any.
     util::scoped_fd file_;
@@ -1,4 +1,5 @@
 #include "config.hh"
+#include "../util/file.hh"
 
 #include <iostream>
 
@@ -25,7 +26,10 @@ namespace lm
                        prob_bits(8),
                        backoff_bits(8),
                        pointer_bhiksha_bits(22),
-                       load_method(util::POPULATE_OR_READ)
+                       load_method(util::POPULATE_OR_READ),
+                       huge_pages(true),
+                       lock_memory(false),
+                       write_mmap_path("")
     {
     }
 
@@ -49,5 +53,74 @@ namespace lm
       EnumerateVocab* enumerateCallbackPtr = &enumerateCallback;
       config.enumerate_vocab = enumerateCallbackPtr;
     }
+    void Config_set_show_progress(Config &config, bool show_progress)
+    {
+      config.show_progress = show_progress;
+    }
+    void Config_set_messages_enabled(Config &config, bool enabled)
+    {
+      config.messages = enabled ? &std::cerr : NULL;
+    }
+    void Config_set_unknown_missing(Config &config, uint8_t action)
+    {
+      config.unknown_missing = static_cast<WarningAction>(action);
+    }
+    void Config_set_sentence_marker_missing(Config &config, uint8_t action)
+    {
+      config.sentence_marker_missing = static_cast<WarningAction>(action);
+    }
+    void Config_set_positive_log_probability(Config &config, uint8_t action)
+    {
+      config.positive_log_probability = static_cast<WarningAction>(action);
+    }
+    void Config_set_unknown_missing_logprob(Config &config, float logprob)
+    {
+      config.unknown_missing_logprob = logprob;
+    }
+    void Config_set_probing_multiplier(Config &config, float multiplier)
+    {
+      config.probing_multiplier = multiplier;
+    }
+    void Config_set_building_memory(Config &config, uint64_t bytes)
+    {
+      config.building_memory = bytes;
+    }
+    void Config_set_arpa_complain(Config &config, uint8_t complain)
+    {
+      config.arpa_complain = static_cast<Config::ARPALoadComplain>(complain);
+    }
+    void Config_set_huge_pages(Config &config, bool huge_pages)
+    {
+      config.huge_pages = huge_pages;
+    }
+    void Config_set_lock_memory(Config &config, bool lock_memory)
+    {
+      config.lock_memory = lock_memory;
+    }
+    void Config_set_temporary_directory_prefix(Config &config, const std::string &prefix)
+    {
+      config.temporary_directory_prefix = prefix;
+      util::NormalizeTempPrefix(config.temporary_directory_prefix);
+    }
+    void Config_set_write_mmap(Config &config, const std::string &path)
+    {
+      config.write_mmap_path = path;
+      config.write_mmap = config.write_mmap_path.c_str();
+    }
+    void Config_set_include_vocab(Config &config, bool include_vocab)
+    {
+      config.include_vocab = include_vocab;
+    }
+
+    void Config_set_quantization_bits(Config &config, uint8_t prob_bits, uint8_t backoff_bits)
+    {
+      config.prob_bits = prob_bits;
+      config.backoff_bits = backoff_bits;
+    }
+
+    void Config_set_pointer_bhiksha_bits(Config &config, uint8_t bits)
+    {
+      config.pointer_bhiksha_bits = bits;
+    }
   }
 }
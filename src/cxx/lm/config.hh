@@ -122,6 +122,15 @@ namespace lm
       // See util/mmap.hh for details of MapMethod.
       util::LoadMethod load_method;
 
+      // Whether the model may be backed by huge pages where available.
+      bool huge_pages;
+
+      // Whether the model is locked in memory after loading, see mlock(2).
+      bool lock_memory;
+
+      // Owns the path write_mmap points to if it is set through Config_set_write_mmap.
+      std::string write_mmap_path;
+
       // Set defaults.
       Config();
     };
@@ -136,6 +145,22 @@ namespace lm
     std::unique_ptr<Config> Config_Create();
     void Config_set_load_method(Config &config, util::LoadMethod load_method);
     void Config_set_enumerate_callback(Config &config, EnumerateVocab &enumerateCallback);
+    void Config_set_show_progress(Config &config, bool show_progress);
+    void Config_set_messages_enabled(Config &config, bool enabled);
+    void Config_set_unknown_missing(Config &config, uint8_t action);
+    void Config_set_sentence_marker_missing(Config &config, uint8_t action);
+    void Config_set_positive_log_probability(Config &config, uint8_t action);
+    void Config_set_unknown_missing_logprob(Config &config, float logprob);
+    void Config_set_probing_multiplier(Config &config, float multiplier);
+    void Config_set_building_memory(Config &config, uint64_t bytes);
+    void Config_set_arpa_complain(Config &config, uint8_t complain);
+    void Config_set_huge_pages(Config &config, bool huge_pages);
+    void Config_set_lock_memory(Config &config, bool lock_memory);
+    void Config_set_temporary_directory_prefix(Config &config, const std::string &prefix);
+    void Config_set_write_mmap(Config &config, const std::string &path);
+    void Config_set_include_vocab(Config &config, bool include_vocab);
+    void Config_set_quantization_bits(Config &config, uint8_t prob_bits, uint8_t backoff_bits);
+    void Config_set_pointer_bhiksha_bits(Config &config, uint8_t bits);
   }
 }
 
@@ -1,5 +1,6 @@
 #include "sizes.hh"
 #include "model.hh"
+#include "trie.hh"
 #include "../util/file_piece.hh"
 
 #include <vector>
@@ -53,6 +54,96 @@ void ShowSizes(const std::vector<uint64_t> &counts) {
   ShowSizes(counts, config);
 }
 
+namespace {
+
+uint64_t EstimateSizeOrThrow(const uint64_t *counts, std::size_t order, uint32_t model_type, const lm::ngram::Config &config) {
+  std::vector<uint64_t> count_vec(counts, counts + order);
+  switch (static_cast<ModelType>(model_type)) {
+    case PROBING:
+      return ProbingModel::Size(count_vec, config);
+    case REST_PROBING:
+      return RestProbingModel::Size(count_vec, config);
+    case TRIE:
+      return TrieModel::Size(count_vec, config);
+    case QUANT_TRIE:
+      return QuantTrieModel::Size(count_vec, config);
+    case ARRAY_TRIE:
+      return ArrayTrieModel::Size(count_vec, config);
+    case QUANT_ARRAY_TRIE:
+      return QuantArrayTrieModel::Size(count_vec, config);
+  }
+  UTIL_THROW(FormatLoadException, "Unknown model type " << model_type);
+}
+
+template <class Value> void HashedSizes(const std::vector<uint64_t> &counts, const Config &config, uint64_t *sizes) {
+  typedef util::ProbingHashTable<typename Value::ProbingEntry, util::IdentityHash> Middle;
+  typedef util::ProbingHashTable<detail::ProbEntry, util::IdentityHash> Longest;
+  sizes[0] = ProbingVocabulary::Size(counts[0], config);
+  sizes[1] = 0;
+  uint64_t higher = 0;
+  for (std::size_t n = 1; n < counts.size() - 1; ++n) {
+    sizes[2 + n] = Middle::Size(counts[n], config.probing_multiplier);
+    higher += sizes[2 + n];
+  }
+  sizes[1 + counts.size()] = Longest::Size(counts.back(), config.probing_multiplier);
+  higher += sizes[1 + counts.size()];
+  // The unigram table is private to HashedSearch, it is what remains of the search.
+  sizes[2] = detail::HashedSearch<Value>::Size(counts, config) - higher;
+}
+
+template <class Quant, class Bhiksha> void TrieSizes(const std::vector<uint64_t> &counts, const Config &config, uint64_t *sizes) {
+  sizes[0] = SortedVocabulary::Size(counts[0], config);
+  sizes[1] = Quant::Size(counts.size(), config);
+  sizes[2] = trie::Unigram::Size(counts[0]);
+  for (std::size_t i = 1; i < counts.size() - 1; ++i) {
+    sizes[2 + i] = trie::BitPackedMiddle<Bhiksha>::Size(Quant::MiddleBits(config), counts[i], counts[0], counts[i + 1], config);
+  }
+  sizes[1 + counts.size()] = trie::BitPackedLongest::Size(Quant::LongestBits(config), counts.back(), counts[0]);
+}
+
+void EstimateSizesOrThrow(const uint64_t *counts, std::size_t order, uint32_t model_type, const lm::ngram::Config &config, uint64_t *sizes) {
+  std::vector<uint64_t> count_vec(counts, counts + order);
+  switch (static_cast<ModelType>(model_type)) {
+    case PROBING:
+      return HashedSizes<BackoffValue>(count_vec, config, sizes);
+    case REST_PROBING:
+      return HashedSizes<RestValue>(count_vec, config, sizes);
+    case TRIE:
+      return TrieSizes<DontQuantize, trie::DontBhiksha>(count_vec, config, sizes);
+    case QUANT_TRIE:
+      return TrieSizes<SeparatelyQuantize, trie::DontBhiksha>(count_vec, config, sizes);
+    case ARRAY_TRIE:
+      return TrieSizes<DontQuantize, trie::ArrayBhiksha>(count_vec, config, sizes);
+    case QUANT_ARRAY_TRIE:
+      return TrieSizes<SeparatelyQuantize, trie::ArrayBhiksha>(count_vec, config, sizes);
+  }
+  UTIL_THROW(FormatLoadException, "Unknown model type " << model_type);
+}
+
+} // namespace
+
+// Exceptions would abort the process when crossing into Rust.
+uint64_t EstimateSize(const uint64_t *counts, std::size_t order, uint32_t model_type, const lm::ngram::Config &config, std::string &error) {
+  try {
+    return EstimateSizeOrThrow(counts, order, model_type, config);
+  } catch (const std::exception &e) {
+    error = e.what();
+  } catch (...) {
+    error = "unknown exception";
+  }
+  return 0;
+}
+
+void EstimateSizes(const uint64_t *counts, std::size_t order, uint32_t model_type, const lm::ngram::Config &config, uint64_t *sizes, std::string &error) {
+  try {
+    EstimateSizesOrThrow(counts, order, model_type, config, sizes);
+  } catch (const std::exception &e) {
+    error = e.what();
+  } catch (...) {
+    error = "unknown exception";
+  }
+}
+
 void ShowSizes(const char *file, const lm::ngram::Config &config) {
   std::vector<uint64_t> counts;
   util::FilePiece f(file);
@@ -1,6 +1,8 @@
 #ifndef LM_SIZES_H
 #define LM_SIZES_H
 
+#include <cstddef>
+#include <string>
 #include <vector>
 
 #include <stdint.h>
@@ -9,9 +11,21 @@ namespace lm { namespace ngram {
 
 struct Config;
 
+// These throw, they are only used by build_binary and not bound to Rust.
 void ShowSizes(const std::vector<uint64_t> &counts, const lm::ngram::Config &config);
 void ShowSizes(const std::vector<uint64_t> &counts);
 void ShowSizes(const char *file, const lm::ngram::Config &config);
 
+// Size of a model of model_type with order counts in bytes, model_type is a ModelType.
+// Exceptions, e.g. for an unknown model type or an order above KENLM_MAX_ORDER, are caught and
+// their message is written to error, 0 is returned then.
+uint64_t EstimateSize(const uint64_t *counts, std::size_t order, uint32_t model_type, const lm::ngram::Config &config, std::string &error);
+
+// Sizes of the parts of a model of model_type with order counts in bytes, they add up to
+// EstimateSize.  Writes order + 2 sizes: the vocabulary, the quantization tables and the n-grams
+// of each order starting with the unigrams.  Exceptions are caught like in EstimateSize, the
+// sizes are not written then.
+void EstimateSizes(const uint64_t *counts, std::size_t order, uint32_t model_type, const lm::ngram::Config &config, uint64_t *sizes, std::string &error);
+
 }} // namespaces
 #endif // LM_SIZES_H
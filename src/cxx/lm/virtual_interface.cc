@@ -3,6 +3,7 @@
 #include "lm_exception.hh"
 #include <memory>
 #include "model.hh"
+#include "left.hh"
 #include "model_type.hh"
 #include "config.hh"
 #include <iostream>
@@ -23,31 +24,123 @@ namespace lm
 
     Model::~Model() {}
 
-    ::std::unique_ptr<base::Model> LoadVirtualPtr(const ::std::string &file_name, const ::lm::ngram::Config &config)
+    namespace
     {
-      lm::ngram::ModelType model_type = lm::ngram::ModelType::PROBING;
-      lm::ngram::RecognizeBinary(file_name.c_str(), model_type);
-      switch (model_type)
+      ::std::unique_ptr<base::Model> LoadVirtualPtrOrThrow(const ::std::string &file_name, const ::lm::ngram::Config &config, uint32_t arpa_model_type)
       {
-      case lm::ngram::PROBING:
-        return ::std::make_unique<::lm::ngram::ProbingModel>(file_name.c_str(), config);
-      case lm::ngram::REST_PROBING:
-        return ::std::make_unique<::lm::ngram::RestProbingModel>(file_name.c_str(), config);
-      case lm::ngram::TRIE:
-        return ::std::make_unique<::lm::ngram::TrieModel>(file_name.c_str(), config);
-      case lm::ngram::QUANT_TRIE:
-        return ::std::make_unique<::lm::ngram::QuantTrieModel>(file_name.c_str(), config);
-      case lm::ngram::ARRAY_TRIE:
-        return ::std::make_unique<::lm::ngram::ArrayTrieModel>(file_name.c_str(), config);
-      case lm::ngram::QUANT_ARRAY_TRIE:
-        return ::std::make_unique<::lm::ngram::QuantArrayTrieModel>(file_name.c_str(), config);
-      default:
-        UTIL_THROW(FormatLoadException, "Confused by model type " << model_type);
+        // RecognizeBinary only overwrites the type for binaries
+        lm::ngram::ModelType model_type = static_cast<lm::ngram::ModelType>(arpa_model_type);
+        lm::ngram::RecognizeBinary(file_name.c_str(), model_type);
+        switch (model_type)
+        {
+        case lm::ngram::PROBING:
+          return ::std::make_unique<::lm::ngram::ProbingModel>(file_name.c_str(), config);
+        case lm::ngram::REST_PROBING:
+          return ::std::make_unique<::lm::ngram::RestProbingModel>(file_name.c_str(), config);
+        case lm::ngram::TRIE:
+          return ::std::make_unique<::lm::ngram::TrieModel>(file_name.c_str(), config);
+        case lm::ngram::QUANT_TRIE:
+          return ::std::make_unique<::lm::ngram::QuantTrieModel>(file_name.c_str(), config);
+        case lm::ngram::ARRAY_TRIE:
+          return ::std::make_unique<::lm::ngram::ArrayTrieModel>(file_name.c_str(), config);
+        case lm::ngram::QUANT_ARRAY_TRIE:
+          return ::std::make_unique<::lm::ngram::QuantArrayTrieModel>(file_name.c_str(), config);
+        default:
+          UTIL_THROW(FormatLoadException, "Confused by model type " << model_type);
+        }
       }
+    } // namespace
+
+    ::std::unique_ptr<base::Model> LoadVirtualPtr(const ::std::string &file_name, const ::lm::ngram::Config &config, uint32_t arpa_model_type, ::std::string &error)
+    {
+      try
+      {
+        return LoadVirtualPtrOrThrow(file_name, config, arpa_model_type);
+      }
+      catch (const std::exception &e)
+      {
+        error = e.what();
+      }
+      catch (...)
+      {
+        error = "unknown exception";
+      }
+      return nullptr;
     }
     std::unique_ptr<Config> Config_Create()
     {
       return std::make_unique<Config>();
     }
+    float BaseScoreForgetState(const Model &model, const void *in_state, const WordIndex new_word)
+    {
+      // All models loadable through LoadVirtualPtr use lm::ngram::State.
+      lm::ngram::State out_state;
+      return model.BaseScore(in_state, new_word, &out_state);
+    }
+    uint64_t StateHash(const ngram::State &state)
+    {
+      return ngram::hash_value(state);
+    }
+    void BaseScoreBatch(const Model &model, ngram::State *states, const WordIndex *words, float *scores, std::size_t count)
+    {
+      lm::ngram::State out_state;
+      for (std::size_t i = 0; i < count; ++i)
+      {
+        scores[i] = model.BaseScore(&states[i], words[i], &out_state);
+        states[i] = out_state;
+      }
+    }
+    uint64_t ChartStateHash(const ngram::ChartState &state)
+    {
+      return ngram::hash_value(state);
+    }
+
+    RuleScorer::~RuleScorer() {}
+
+    namespace
+    {
+      template <class M>
+      class RuleScorerImpl : public RuleScorer
+      {
+      public:
+        // out_ is constructed before score_ as part of the base class
+        explicit RuleScorerImpl(const M &model) : score_(model, out_) {}
+
+        void BeginSentence() override { score_.BeginSentence(); }
+        void Terminal(WordIndex word) override { score_.Terminal(word); }
+        void BeginNonTerminal(const ngram::ChartState &in, float prob) override { score_.BeginNonTerminal(in, prob); }
+        void NonTerminal(const ngram::ChartState &in, float prob) override { score_.NonTerminal(in, prob); }
+        float Finish() override { return score_.Finish(); }
+        void Reset() override { score_.Reset(); }
+
+      private:
+        ngram::RuleScore<M> score_;
+      };
+
+      template <class M>
+      ::std::unique_ptr<RuleScorer> TryCreate(const Model &model)
+      {
+        const M *concrete = dynamic_cast<const M *>(&model);
+        if (!concrete)
+          return nullptr;
+        return ::std::make_unique<RuleScorerImpl<M>>(*concrete);
+      }
+    } // namespace
+
+    ::std::unique_ptr<RuleScorer> RuleScorer_Create(const Model &model)
+    {
+      ::std::unique_ptr<RuleScorer> scorer = TryCreate<ngram::ProbingModel>(model);
+      if (!scorer)
+        scorer = TryCreate<ngram::RestProbingModel>(model);
+      if (!scorer)
+        scorer = TryCreate<ngram::TrieModel>(model);
+      if (!scorer)
+        scorer = TryCreate<ngram::QuantTrieModel>(model);
+      if (!scorer)
+        scorer = TryCreate<ngram::ArrayTrieModel>(model);
+      if (!scorer)
+        scorer = TryCreate<ngram::QuantArrayTrieModel>(model);
+      return scorer;
+    }
   } // namespace base
 } // namespace lm
@@ -5,9 +5,12 @@
 #include "word_index.hh"
 #include "../util/string_piece.hh"
 #include "config.hh"
+#include "state.hh"
 #include <string>
 #include <cstring>
 #include <memory>
+#include <cstdint>
+#include <cstddef>
 
 namespace lm
 {
@@ -168,9 +171,64 @@ namespace lm
       Model &operator=(const Model &);
     };
 
-    ::std::unique_ptr<base::Model> LoadVirtualPtr(const ::std::string &file_name, const ::lm::ngram::Config &config);
+    // Loads the model, ARPA files are loaded as arpa_model_type, binaries as the type they are
+    // stored as. Exceptions are caught and their message is written to error, the returned
+    // pointer is null then. They would abort the process when crossing into Rust. Scoring needs
+    // no guard: the scoring functions of GenericModel in model.cc, the searches and vocabularies
+    // they call and the shims below only read the tables built while loading. None of them
+    // allocates or calls UTIL_THROW, all throwing code of model.cc is on the load path.
+    ::std::unique_ptr<base::Model> LoadVirtualPtr(const ::std::string &file_name, const ::lm::ngram::Config &config, uint32_t arpa_model_type, ::std::string &error);
     ::std::unique_ptr<Config> Config_Create();
 
+    // Score new_word given in_state. KenLM always writes the out state, it is written to a
+    // temporary on the stack and discarded, which spares the caller from providing one.
+    float BaseScoreForgetState(const Model &model, const void *in_state, const WordIndex new_word);
+
+    // hash_value of the state, hash_value itself is overloaded which autocxx can't bind.
+    uint64_t StateHash(const ngram::State &state);
+
+    // Scores words[i] given states[i] for every i < count, states[i] is replaced by its out state.
+    void BaseScoreBatch(const Model &model, ngram::State *states, const WordIndex *words, float *scores, std::size_t count);
+
+    // hash_value of the chart state, see StateHash.
+    uint64_t ChartStateHash(const ngram::ChartState &state);
+
+    /* lm::ngram::RuleScore for a Model loaded through LoadVirtualPtr.
+     *
+     * RuleScore is a template over the concrete model classes since it needs ExtendLeft and
+     * UnRest, which the virtual interface lacks. RuleScorer_Create downcasts the model to the
+     * six model types, the methods forward to a RuleScore of the matching type. The rule is
+     * scored into the owned ChartState returned by Out. See left.hh for the intended usage.
+     */
+    class RuleScorer
+    {
+    public:
+      virtual ~RuleScorer();
+
+      virtual void BeginSentence() = 0;
+      virtual void Terminal(WordIndex word) = 0;
+      virtual void BeginNonTerminal(const ngram::ChartState &in, float prob) = 0;
+      virtual void NonTerminal(const ngram::ChartState &in, float prob) = 0;
+      virtual float Finish() = 0;
+      virtual void Reset() = 0;
+
+      const ngram::ChartState &Out() const { return out_; }
+
+    protected:
+      RuleScorer() {}
+
+      ngram::ChartState out_;
+
+    private:
+      // Disable copy constructors, RuleScore points to out_.
+      RuleScorer(const RuleScorer &);
+      RuleScorer &operator=(const RuleScorer &);
+    };
+
+    // Null if model is none of the six model types, which can't happen for models loaded
+    // through LoadVirtualPtr. The model has to outlive the scorer.
+    ::std::unique_ptr<RuleScorer> RuleScorer_Create(const Model &model);
+
   } // mamespace base
 } // namespace lm
 
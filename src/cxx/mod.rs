@@ -1,8 +1,8 @@
-use std::{cell::RefCell, ops::Deref, rc::Rc};
+use std::{cell::RefCell, marker::PhantomData, ops::Deref, rc::Rc};
 
 use ::cxx::UniquePtr;
 
-use crate::Error;
+use crate::{ConfigBuilder, Error, ModelType};
 
 use self::bridge::VocabFetchCallback;
 
@@ -10,6 +10,12 @@ pub(crate) mod bridge;
 
 pub struct CxxModel(UniquePtr<bridge::lm::base::Model>);
 
+// SAFETY: KenLM models are immutable once loaded. All methods reachable through the bridge are
+// `const` in C++ and write only to caller-provided states, KenLM documents its models as safe to
+// query from multiple threads. The vocabulary lookup only reads the hash table built at load time.
+unsafe impl Send for CxxModel {}
+unsafe impl Sync for CxxModel {}
+
 impl Deref for CxxModel {
     type Target = UniquePtr<bridge::lm::base::Model>;
 
@@ -19,27 +25,55 @@ impl Deref for CxxModel {
 }
 
 impl CxxModel {
-    pub fn load_from_file_with_config(filename: &str, config: &Config) -> Self {
+    pub fn load_from_file_with_config(filename: &str, config: &Config) -> Result<Self, Error> {
         cxx::let_cxx_string!(file_name = filename);
-        Self(bridge::lm::base::LoadVirtualPtr(&file_name, &config.inner))
+        cxx::let_cxx_string!(error = "");
+        let model = bridge::lm::base::LoadVirtualPtr(
+            &file_name,
+            &config.inner,
+            config.arpa_model_type as u32,
+            error.as_mut(),
+        );
+        if let Some(panic) = config.take_callback_panic() {
+            std::panic::resume_unwind(panic);
+        }
+        if model.is_null() {
+            return Err(Error::KenLMError(error.to_string_lossy().into_owned()));
+        }
+        Ok(Self(model))
     }
 }
 
-pub struct Config {
+/// KenLM's config, the vocab callback may borrow for `'a`
+pub struct Config<'a> {
     inner: UniquePtr<bridge::lm::ngram::Config>,
     vocab_callback: Option<Rc<RefCell<VocabFetchCallback>>>,
+    on_word: PhantomData<&'a mut ()>,
+    /// Passed to `LoadVirtualPtr`, it is not part of KenLM's config
+    arpa_model_type: ModelType,
 }
 
-impl Default for Config {
+impl Default for Config<'_> {
     fn default() -> Self {
         Config {
             inner: bridge::lm::base::Config_Create(),
             vocab_callback: None,
+            on_word: PhantomData,
+            arpa_model_type: ModelType::Probing,
         }
     }
 }
 
-impl Config {
+impl Drop for Config<'_> {
+    fn drop(&mut self) {
+        // `on_word` may borrow for no longer than the config, see add_vocab_callback
+        if let Some(callback) = &self.vocab_callback {
+            callback.borrow_mut().on_word = None;
+        }
+    }
+}
+
+impl<'a> Config<'a> {
     pub fn set_load_method(&mut self, method: LoadMethod) -> Result<(), Error> {
         bridge::lm::ngram::Config_set_load_method(
             self.inner
@@ -51,8 +85,139 @@ impl Config {
         Ok(())
     }
 
-    pub fn add_vocab_fetch_callback(&mut self) {
+    /// Sets the bits of quantized probabilities and backoffs, binaries store them in the model
+    pub fn set_quantization_bits(&mut self, prob_bits: u8, backoff_bits: u8) {
+        bridge::lm::ngram::Config_set_quantization_bits(
+            self.inner.as_mut().unwrap(),
+            prob_bits,
+            backoff_bits,
+        );
+    }
+
+    /// Sets the bits chopped off array-compressed trie pointers, binaries store them in the model
+    pub fn set_pointer_bhiksha_bits(&mut self, bits: u8) {
+        bridge::lm::ngram::Config_set_pointer_bhiksha_bits(self.inner.as_mut().unwrap(), bits);
+    }
+
+    pub fn apply(&mut self, options: &ConfigBuilder) {
+        self.arpa_model_type = options.arpa_model_type;
+        // There should always be a config here given that Default creates one.
+        let mut config = self.inner.as_mut().unwrap();
+        bridge::lm::ngram::Config_set_show_progress(config.as_mut(), options.show_progress);
+        bridge::lm::ngram::Config_set_messages_enabled(config.as_mut(), options.messages);
+        bridge::lm::ngram::Config_set_unknown_missing(
+            config.as_mut(),
+            options.unknown_missing as u8,
+        );
+        bridge::lm::ngram::Config_set_sentence_marker_missing(
+            config.as_mut(),
+            options.sentence_marker_missing as u8,
+        );
+        bridge::lm::ngram::Config_set_positive_log_probability(
+            config.as_mut(),
+            options.positive_log_probability as u8,
+        );
+        bridge::lm::ngram::Config_set_unknown_missing_logprob(
+            config.as_mut(),
+            options.unknown_missing_log_prob,
+        );
+        bridge::lm::ngram::Config_set_probing_multiplier(
+            config.as_mut(),
+            options.probing_multiplier,
+        );
+        bridge::lm::ngram::Config_set_building_memory(config.as_mut(), options.building_memory);
+        bridge::lm::ngram::Config_set_arpa_complain(config.as_mut(), options.arpa_complain as u8);
+        bridge::lm::ngram::Config_set_huge_pages(config.as_mut(), options.huge_pages);
+        bridge::lm::ngram::Config_set_lock_memory(config.as_mut(), options.lock_memory);
+        bridge::lm::ngram::Config_set_include_vocab(config.as_mut(), options.include_vocab);
+        if let Some(path) = options.write_binary_path() {
+            cxx::let_cxx_string!(path = path);
+            bridge::lm::ngram::Config_set_write_mmap(config.as_mut(), &path);
+        }
+        if let Some(prefix) = options.temporary_directory_prefix() {
+            cxx::let_cxx_string!(prefix = prefix);
+            bridge::lm::ngram::Config_set_temporary_directory_prefix(config, &prefix);
+        }
+    }
+
+    /// Size in bytes KenLM allocates for a model of `model_type` with `counts`, `counts` must at
+    /// least cover bigrams
+    pub fn estimate_size(&self, counts: &[u64], model_type: ModelType) -> Result<u64, Error> {
+        debug_assert!(counts.len() >= 2);
+        cxx::let_cxx_string!(error = "");
+        // SAFETY: KenLM only reads `counts.len()` counts
+        let size = unsafe {
+            bridge::lm::ngram::EstimateSize(
+                counts.as_ptr(),
+                counts.len(),
+                model_type as u32,
+                &self.inner,
+                error.as_mut(),
+            )
+        };
+        if !error.is_empty() {
+            return Err(Error::SizeEstimateError(
+                error.to_string_lossy().into_owned(),
+            ));
+        }
+        Ok(size)
+    }
+
+    /// Sizes in bytes of the parts of a model of `model_type` with `counts`, see
+    /// [crate::MemoryFootprint], `counts` must at least cover bigrams
+    ///
+    /// Returns the size of the vocab, of the quantization tables and of the n-grams per order.
+    pub fn estimate_sizes(
+        &self,
+        counts: &[u64],
+        model_type: ModelType,
+    ) -> Result<(u64, u64, Vec<u64>), Error> {
+        debug_assert!(counts.len() >= 2);
+        cxx::let_cxx_string!(error = "");
+        let mut sizes = vec![0u64; counts.len() + 2];
+        // SAFETY: KenLM only reads `counts.len()` counts and writes `counts.len() + 2` sizes
+        unsafe {
+            bridge::lm::ngram::EstimateSizes(
+                counts.as_ptr(),
+                counts.len(),
+                model_type as u32,
+                &self.inner,
+                sizes.as_mut_ptr(),
+                error.as_mut(),
+            )
+        }
+        if !error.is_empty() {
+            return Err(Error::SizeEstimateError(
+                error.to_string_lossy().into_owned(),
+            ));
+        }
+        let orders = sizes.split_off(2);
+        Ok((sizes[0], sizes[1], orders))
+    }
+
+    /// Sets up vocab enumeration, `on_word` is called with each word and its index during load
+    ///
+    /// The words are only collected for `get_vocab` if `store` is set.
+    pub fn add_vocab_callback(
+        &mut self,
+        store: bool,
+        on_word: Option<Box<dyn FnMut(u32, &str) + 'a>>,
+    ) {
+        // SAFETY: the subclass can't carry a lifetime. `on_word` is only called while KenLM loads
+        // a model with this config and is dropped with the config, which doesn't outlive `'a`.
+        let on_word =
+            on_word.map(|on_word| unsafe {
+                std::mem::transmute::<
+                    Box<dyn FnMut(u32, &str) + 'a>,
+                    Box<dyn FnMut(u32, &str) + 'static>,
+                >(on_word)
+            });
         let callback = bridge::get_vocab_call_back();
+        {
+            let mut callback = callback.borrow_mut();
+            callback.store = store;
+            callback.on_word = on_word;
+        }
         let cb = callback.clone();
         let mut callback_ref = callback.borrow_mut();
         let callback_pin_mut = callback_ref.pin_mut();
@@ -64,8 +229,18 @@ impl Config {
         self.vocab_callback = Some(cb);
     }
 
-    pub fn get_vocab(&mut self) -> Option<Vec<String>> {
+    /// Takes the panic the vocab callback raised during the last load
+    fn take_callback_panic(&self) -> Option<Box<dyn std::any::Any + Send>> {
+        self.vocab_callback
+            .as_ref()
+            .and_then(|callback| callback.borrow_mut().panic.take())
+    }
+
+    pub fn get_vocab(&mut self) -> Option<Vec<(u32, String)>> {
         if let Some(voc) = self.vocab_callback.as_ref() {
+            if !voc.borrow().store {
+                return None;
+            }
             let mut vocab = vec![];
             std::mem::swap(&mut voc.borrow_mut().vocab, &mut vocab);
             return Some(vocab);
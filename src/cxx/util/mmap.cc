@@ -139,6 +139,14 @@ void SyncOrThrow(void *start, size_t length) {
 #endif
 }
 
+void LockOrThrow(void *start, size_t length) {
+#if defined(_WIN32) || defined(_WIN64)
+  UTIL_THROW_IF(length && !::VirtualLock(start, length), ErrnoException, "Failed to lock " << length << " bytes in memory");
+#else
+  UTIL_THROW_IF(length && mlock(start, length), ErrnoException, "Failed to lock " << length << " bytes in memory, check RLIMIT_MEMLOCK");
+#endif
+}
+
 void UnmapOrThrow(void *start, size_t length) {
 #if defined(_WIN32) || defined(_WIN64)
   UTIL_THROW_IF(!::UnmapViewOfFile(start), ErrnoException, "Failed to unmap a file");
@@ -299,7 +307,7 @@ void HugeRealloc(std::size_t to, bool zero_new, scoped_memory &mem) {
   }
 }
 
-void MapRead(LoadMethod method, int fd, uint64_t offset, std::size_t size, scoped_memory &out) {
+void MapRead(LoadMethod method, int fd, uint64_t offset, std::size_t size, scoped_memory &out, bool huge_pages) {
   switch (method) {
     case LAZY:
       out.reset(MapOrThrow(size, false, kFileFlags, false, fd, offset), size, scoped_memory::MMAP_ALLOCATED);
@@ -314,7 +322,11 @@ void MapRead(LoadMethod method, int fd, uint64_t offset, std::size_t size, scope
     case POPULATE_OR_READ:
 #endif
     case READ:
-      HugeMalloc(size, false, out);
+      if (huge_pages) {
+        HugeMalloc(size, false, out);
+      } else {
+        out.reset(MallocOrThrow(size), size, scoped_memory::MALLOC_ALLOCATED);
+      }
       SeekOrThrow(fd, offset);
       ReadOrThrow(fd, out.get(), size);
       break;
@@ -322,6 +334,12 @@ void MapRead(LoadMethod method, int fd, uint64_t offset, std::size_t size, scope
       UTIL_THROW(Exception, "Parallel read was removed from this repo.");
       break;
   }
+#ifdef MADV_NOHUGEPAGE
+  // MapOrThrow always asks for huge pages.
+  if (!huge_pages && out.source() == scoped_memory::MMAP_ALLOCATED) {
+    madvise(out.get(), size, MADV_NOHUGEPAGE);
+  }
+#endif
 }
 
 void *MapZeroedWrite(int fd, std::size_t size) {
@@ -126,6 +126,9 @@ void *MapOrThrow(std::size_t size, bool for_write, int flags, bool prefault, int
 // msync wrapper
 void SyncOrThrow(void *start, size_t length);
 
+// mlock wrapper, keeps the pages in memory until they are unmapped.
+void LockOrThrow(void *start, size_t length);
+
 // Cross-platform, error-checking wrapper for munmap().
 void UnmapOrThrow(void *start, size_t length);
 
@@ -155,7 +158,8 @@ enum LoadMethod {
   PARALLEL_READ,
 };
 
-void MapRead(LoadMethod method, int fd, uint64_t offset, std::size_t size, scoped_memory &out);
+// With huge_pages unset, the memory is not backed by huge pages, even if they are available.
+void MapRead(LoadMethod method, int fd, uint64_t offset, std::size_t size, scoped_memory &out, bool huge_pages = true);
 
 // Open file name with mmap of size bytes, all of which are initially zero.
 void *MapZeroedWrite(int fd, std::size_t size);
@@ -64,6 +64,12 @@ impl Counts {
         // it is impossible to construct this struct with an empty counts vec
         self.counts.last().as_ref().unwrap()
     }
+
+    /// Drops the counts of orders above `order`
+    pub(crate) fn truncate(&mut self, order: NonZeroUsize) {
+        // order is at least one, so at least one count remains
+        self.counts.truncate(order.get());
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
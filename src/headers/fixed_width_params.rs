@@ -2,6 +2,7 @@ use std::io::Read;
 
 use zerocopy::{AsBytes, FromBytes};
 
+use super::{InvalidModelType, ModelType};
 use crate::cxx::bridge;
 use crate::Error;
 
@@ -18,7 +19,7 @@ pub struct FixedParameters {
     pub order: u8,
     /// Probing multiplier for the probing storage model
     pub probing_multiplier: f32,
-    /// The model type, see src/cxx/lm/model_type.hh for further info
+    /// The model type, see [ModelType] and src/cxx/lm/model_type.hh for further info
     ///
     /// PROBING = 0,
     /// REST_PROBING = 1,
@@ -44,6 +45,11 @@ impl FixedParameters {
         self.has_vocabulary != 0
     }
 
+    /// The [ModelType] encoded in `model_type`
+    pub fn model_type(&self) -> Result<ModelType, InvalidModelType> {
+        ModelType::try_from(self.model_type)
+    }
+
     #[cfg(test)]
     fn from_file_manually_parsed(fd: &mut std::fs::File) -> Result<Self, Error> {
         use std::io::{Seek, SeekFrom};
@@ -1,8 +1,10 @@
 mod counts;
 pub(crate) mod fixed_width_params;
+mod model_type;
 pub(crate) mod sanity;
 pub use counts::{Counts, InvalidCounts, NGramCardinality};
 pub use fixed_width_params::FixedParameters;
+pub use model_type::{InvalidModelType, ModelType};
 pub(crate) use sanity::Sanity;
 
 #[cfg(test)]
@@ -109,8 +111,8 @@ mod test {
     }
 }
 
-#[cfg(test)]
-fn total_header_size(order: usize) -> usize {
+/// Size of the headers of a binary of `order`, the model starts right after them
+pub(crate) fn total_header_size(order: usize) -> usize {
     align8(
         std::mem::size_of::<FixedParameters>()
             + std::mem::size_of::<Sanity>()
@@ -0,0 +1,74 @@
+/// Storage type of a KenLM model
+///
+/// Mirrors `lm::ngram::ModelType` in src/cxx/lm/model_type.hh, the discriminants are the values
+/// stored in [crate::headers::FixedParameters::model_type]. ARPA files are loaded as
+/// [crate::ConfigBuilder::arpa_model_type].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModelType {
+    Probing = 0,
+    RestProbing = 1,
+    Trie = 2,
+    QuantTrie = 3,
+    ArrayTrie = 4,
+    QuantArrayTrie = 5,
+}
+
+impl ModelType {
+    /// Whether the model is stored in a probing hash table
+    pub fn is_probing(&self) -> bool {
+        matches!(self, ModelType::Probing | ModelType::RestProbing)
+    }
+
+    /// Whether the model is stored in a trie
+    pub fn is_trie(&self) -> bool {
+        !self.is_probing()
+    }
+
+    /// Whether probabilities and backoffs are quantized
+    pub fn is_quantized(&self) -> bool {
+        matches!(self, ModelType::QuantTrie | ModelType::QuantArrayTrie)
+    }
+
+    /// Whether trie pointers are compressed
+    pub fn is_array_compressed(&self) -> bool {
+        matches!(self, ModelType::ArrayTrie | ModelType::QuantArrayTrie)
+    }
+}
+
+impl TryFrom<u32> for ModelType {
+    type Error = InvalidModelType;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ModelType::Probing),
+            1 => Ok(ModelType::RestProbing),
+            2 => Ok(ModelType::Trie),
+            3 => Ok(ModelType::QuantTrie),
+            4 => Ok(ModelType::ArrayTrie),
+            5 => Ok(ModelType::QuantArrayTrie),
+            _ => Err(InvalidModelType(value)),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("{0} is not a known model type")]
+pub struct InvalidModelType(pub u32);
+
+#[cfg(test)]
+mod test {
+    use super::ModelType;
+
+    #[test]
+    fn converts_from_u32() {
+        for value in 0..6u32 {
+            let model_type = ModelType::try_from(value).unwrap();
+            assert_eq!(model_type as u32, value);
+        }
+        assert_eq!(ModelType::try_from(2).unwrap(), ModelType::Trie);
+        assert!(ModelType::try_from(6).is_err());
+        assert!(ModelType::QuantArrayTrie.is_quantized());
+        assert!(ModelType::QuantArrayTrie.is_array_compressed());
+        assert!(ModelType::RestProbing.is_probing());
+    }
+}
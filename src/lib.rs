@@ -1,16 +1,35 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+#[cfg(any(feature = "uniffi", feature = "napi"))]
+pub mod bindings;
 mod cxx;
 pub mod headers;
 pub(crate) mod model;
+mod probe;
 pub mod reader;
+pub mod vocab;
 
 pub use crate::cxx::LoadMethod;
 
-use headers::InvalidCounts;
-pub use model::{Model, State, WordIdx};
+pub use headers::ModelType;
+use headers::{InvalidCounts, InvalidModelType};
+#[cfg(feature = "unicode")]
+pub use model::NormalizationForm;
+pub use model::{
+    AppliedBackoff, ArpaComplain, BidirectionalScorer, ChartState, Checkpoint, ConfigBuilder,
+    CoverageStats, FallbackRule, FullScore, HotReloadingModel, JobReport, MemoryFootprint,
+    MemoryUsage, Model, ModelBuilder, ModelManager, OcrAlternative, OcrCorrection, OcrCorrector,
+    OovFallback, OovScore, QueryPrinter, ResolvedBy, RuleScore, Score, ScoreOptions, ScoreSession,
+    ScoreTrace, Scorer, ScoringJob, SentenceScore, SessionSnapshot, SpecialTokens, State,
+    Suggestion, TokenClasses, TokenScore, WarningAction, WordIdx,
+};
+pub use probe::{probe, FileKind};
 
 #[derive(thiserror::Error, Debug)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
 pub enum Error {
     #[error("File not found: {0}")]
     FileNotFound(String),
@@ -23,12 +42,51 @@ pub enum Error {
     ModelHasNoVocab,
     #[error("Decoding the fixed width parameter header failed, likely the model file is broken or incompatible.")]
     ParamHeaderFormatError,
+    #[error("The fixed width parameter header contains an unknown model type: {0}")]
+    UnknownModelType(#[from] InvalidModelType),
     #[error("Decoding the count header failed, likely the model file is broken or incompatible.")]
     CountHeaderError(#[from] InvalidCounts),
     #[error("Decoding the sanity header failed, likely the model file is broken or incompatible.")]
     SanityFormatError,
     #[error("The sanity header did not match the reference header. Likely the model is broken or incompatible.")]
     SanityMismatch,
+    #[error("A token class pattern could not be compiled: {0}")]
+    InvalidTokenClass(#[from] regex::Error),
+    #[error("Building the completion index failed: {0}")]
+    CompletionIndexError(#[from] fst::Error),
+    #[error("The model needs an estimated {required} bytes, exceeding the memory budget of {budget} bytes")]
+    MemoryBudgetExceeded { required: u64, budget: u64 },
+    #[error("KenLM failed to load the model: {0}")]
+    KenLMError(String),
+    #[error("KenLM failed to estimate the size of the model: {0}")]
+    SizeEstimateError(String),
+    #[error("The ARPA file is missing <unk>")]
+    UnknownMissing,
+    #[error("The n-gram \"{0}\" of the ARPA file has a positive log-probability")]
+    PositiveLogProbability(String),
+    #[error("The ARPA file is missing the sentence marker {0}")]
+    SentenceMarkerMissing(String),
+    #[error("KenLM only supports models of order two or higher, got order {0}")]
+    UnsupportedOrder(usize),
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("The vocabulary at the end of the binary file is malformed, likely the model file is broken or truncated.")]
+    VocabFormatError,
+    #[error("Locking the model needs {required} bytes together with the models locked before, but RLIMIT_MEMLOCK only allows {limit} bytes. Raise it with `ulimit -l` or disable lock_memory.")]
+    MemoryLockLimit { required: u64, limit: u64 },
+    #[error("The model was loaded from a buffer and can't be reloaded.")]
+    NotReloadable,
+    #[error("The session snapshot does not fit the model.")]
+    InvalidSnapshot,
+    #[error("The checkpoint {0} could not be parsed.")]
+    InvalidCheckpoint(String),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[cfg(feature = "remote")]
+    #[error("Downloading the model failed: {0}")]
+    DownloadError(#[from] reqwest::Error),
+    #[error("The checksum of the model does not match. Expected: {expected}; Got: {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("The checksum of {0} is missing, the sidecar is missing or empty or the url has no #sha256= fragment")]
+    ChecksumMissing(String),
 }
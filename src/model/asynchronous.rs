@@ -0,0 +1,62 @@
+use super::{Model, ModelBuilder};
+use crate::Error;
+
+impl ModelBuilder {
+    /// Loads the model on tokio's blocking thread pool
+    ///
+    /// Reading the headers and KenLM's load block for a long time with large models, running
+    /// them on the blocking pool keeps the async runtime responsive. Panics while loading are
+    /// propagated to the caller. Must be called from within a tokio runtime.
+    /// ```
+    /// use kenlm_rs::Model;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let model = Model::builder("test_data/test.bin")
+    ///     .store_vocab(true)
+    ///     .build_async()
+    ///     .await
+    ///     .unwrap();
+    /// assert!(model.get_vocab().is_some());
+    /// # });
+    /// ```
+    pub async fn build_async(self) -> Result<Model, Error> {
+        match tokio::task::spawn_blocking(move || self.build()).await {
+            Ok(model) => model,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(std::io::Error::new(std::io::ErrorKind::Interrupted, err).into()),
+        }
+    }
+}
+
+impl Model {
+    /// Loads the model from `file_name` on tokio's blocking thread pool, see `Model::new`
+    ///
+    /// Use [ModelBuilder::build_async] for further options.
+    pub async fn load_async(file_name: &str, store_vocab: bool) -> Result<Model, Error> {
+        Model::builder(file_name)
+            .store_vocab(store_vocab)
+            .build_async()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Error, Model};
+
+    #[tokio::test]
+    async fn loads_like_blocking_load() {
+        let expected = Model::new("test_data/test.bin", false).expect("should load");
+        let model = Model::load_async("test_data/test.bin", false)
+            .await
+            .expect("should load");
+        assert_eq!(
+            model.score_sentence(&["i", "have"], true, true),
+            expected.score_sentence(&["i", "have"], true, true)
+        );
+        assert!(matches!(
+            Model::load_async("test_data/missing.bin", false).await,
+            Err(Error::FileNotFound(_))
+        ));
+    }
+}
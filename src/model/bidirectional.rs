@@ -0,0 +1,101 @@
+use super::{Model, ScoreOptions};
+
+/// Combines a forward and a backward (right-to-left) model for rescoring
+///
+/// The backward model is a regular KenLM model trained on sentences with reversed word order,
+/// it is loaded like any other model. `BidirectionalScorer` scores sentences left-to-right with
+/// the forward model and right-to-left with the backward model and returns the weighted sum of
+/// both scores. Since the sentence is reversed, `<s>` and `</s>` keep their meaning for the
+/// backward model, the same [ScoreOptions] are used for both directions.
+/// ```
+/// use kenlm_rs::{BidirectionalScorer, Model, ScoreOptions};
+/// let forward = Model::new("test_data/test.bin", false).unwrap();
+/// // Use a model trained on reversed sentences here.
+/// let backward = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let scorer = BidirectionalScorer::new(&forward, &backward).with_weights(0.7, 0.3);
+/// let score = scorer.score_sentence(&["what", "a", "lovely", "sentence"], ScoreOptions::new(true, true));
+/// eprintln!("{score:?}");
+/// ```
+pub struct BidirectionalScorer<'m> {
+    forward: &'m Model,
+    backward: &'m Model,
+    forward_weight: f32,
+    backward_weight: f32,
+}
+
+impl<'m> BidirectionalScorer<'m> {
+    /// Constructs the scorer, both directions are weighted with `0.5`
+    pub fn new(forward: &'m Model, backward: &'m Model) -> Self {
+        Self {
+            forward,
+            backward,
+            forward_weight: 0.5,
+            backward_weight: 0.5,
+        }
+    }
+
+    /// Sets the weights of the forward and the backward score
+    pub fn with_weights(mut self, forward_weight: f32, backward_weight: f32) -> Self {
+        self.forward_weight = forward_weight;
+        self.backward_weight = backward_weight;
+        self
+    }
+
+    /// Returns the unweighted forward and backward scores of `sentence` in log10-space
+    pub fn score_sentence_parts(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+    ) -> (f32, f32) {
+        let sentence = sentence.into_iter().collect::<Vec<_>>();
+        let forward = self.forward.score_sentence_with_options(&sentence, options);
+        let backward = self
+            .backward
+            .score_sentence_with_options(sentence.iter().rev(), options);
+        (forward, backward)
+    }
+
+    /// Returns the weighted sum of the forward and backward scores of `sentence`
+    pub fn score_sentence(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+    ) -> f32 {
+        let (forward, backward) = self.score_sentence_parts(sentence, options);
+        self.forward_weight * forward + self.backward_weight * backward
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BidirectionalScorer;
+    use crate::{Model, ScoreOptions};
+
+    #[test]
+    fn combines_both_directions() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let sentence = &["i", "have", "a", "good", "deal"];
+        let reversed = &["deal", "good", "a", "have", "i"];
+        let options = ScoreOptions::new(true, true);
+
+        let forward = model.score_sentence_with_options(sentence, options);
+        let backward = model.score_sentence_with_options(reversed, options);
+        let scorer = BidirectionalScorer::new(&model, &model);
+        assert_eq!(
+            scorer.score_sentence_parts(sentence, options),
+            (forward, backward)
+        );
+
+        let only_forward = scorer
+            .with_weights(1., 0.)
+            .score_sentence(sentence, options);
+        approx::assert_abs_diff_eq!(forward, only_forward, epsilon = f32::EPSILON);
+        let scorer = BidirectionalScorer::new(&model, &model).with_weights(0.25, 0.75);
+        approx::assert_abs_diff_eq!(
+            0.25 * forward + 0.75 * backward,
+            scorer.score_sentence(sentence, options),
+            epsilon = f32::EPSILON
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::hash_map::RandomState;
+use std::fs::{File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use super::ModelBuilder;
+use crate::{Error, LoadMethod, Model};
+
+/// A model buffer copied into a file KenLM can open, the file is gone when this is dropped
+struct BufferFile {
+    // keeps the in-memory file alive, KenLM opens it through its path
+    _file: File,
+    path: String,
+    spilled: bool,
+}
+
+impl BufferFile {
+    /// Copies `bytes` into an anonymous in-memory file
+    #[cfg(target_os = "linux")]
+    fn write(bytes: &[u8]) -> Result<Self, Error> {
+        use std::os::unix::io::{AsRawFd, FromRawFd};
+
+        extern "C" {
+            fn memfd_create(
+                name: *const std::os::raw::c_char,
+                flags: std::os::raw::c_uint,
+            ) -> std::os::raw::c_int;
+        }
+        const MFD_CLOEXEC: std::os::raw::c_uint = 1;
+
+        // SAFETY: the name is NUL-terminated, the returned descriptor is owned by `file` only
+        let fd = unsafe { memfd_create(b"kenlm-rs-buffer\0".as_ptr().cast(), MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(bytes)?;
+        Ok(BufferFile {
+            path: format!("/proc/self/fd/{}", file.as_raw_fd()),
+            _file: file,
+            spilled: false,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn write(bytes: &[u8]) -> Result<Self, Error> {
+        Self::spill(bytes)
+    }
+
+    /// Writes `bytes` to a new file with a random name in the temp directory
+    ///
+    /// The file is created exclusively, so an existing file or symlink is never written to.
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    fn spill(bytes: &[u8]) -> Result<Self, Error> {
+        let (mut file, path) = create_unique(&std::env::temp_dir(), "kenlm-rs-buffer")?;
+        let path = match path.into_os_string().into_string() {
+            Ok(path) => path,
+            Err(path) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(Error::FileNotFound(path.to_string_lossy().into_owned()));
+            }
+        };
+        let spilled = BufferFile {
+            _file: file.try_clone()?,
+            path,
+            spilled: true,
+        };
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        Ok(spilled)
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Drop for BufferFile {
+    fn drop(&mut self) {
+        if self.spilled {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Creates a new file in `dir` whose name starts with `prefix` and can't be predicted by other
+/// processes
+///
+/// The file is created exclusively, so an existing file or symlink is never written to.
+#[cfg_attr(all(target_os = "linux", not(feature = "zstd")), allow(dead_code))]
+pub(super) fn create_unique(dir: &Path, prefix: &str) -> Result<(File, PathBuf), Error> {
+    loop {
+        // RandomState is seeded randomly for every instance
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        let path = dir.join(format!("{}-{:016x}.tmp", prefix, hasher.finish()));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Loads a model from `bytes` with the options of `builder`
+///
+/// KenLM only loads models from files. On Linux, `bytes` are copied into a `memfd` which KenLM
+/// opens through `/proc/self/fd`. Elsewhere they are spilled to the temp directory. Either way
+/// the model is read into memory with [LoadMethod::Read] and the file is gone once it is loaded,
+/// so the model can't be reloaded.
+pub(crate) fn load(bytes: &[u8], builder: ModelBuilder) -> Result<Model, Error> {
+    let file = BufferFile::write(bytes)?;
+    builder
+        .with_file_name(file.path())
+        .with_load_method(LoadMethod::Read)
+        .not_reloadable()
+        .build()
+}
+
+#[cfg(test)]
+mod test {
+    use super::BufferFile;
+    use crate::model::test::TEST_SENTENCE;
+    use crate::{Error, Model};
+
+    #[test]
+    fn loads_model_from_buffer() {
+        let expected = Model::new("test_data/test.bin", false).expect("should exist");
+        let bytes = std::fs::read("test_data/test.bin").unwrap();
+        let model = Model::from_bytes(&bytes, true).expect("should load");
+        assert!(model.get_vocab().is_some());
+        assert_eq!(
+            model.score_sentence(TEST_SENTENCE, true, true),
+            expected.score_sentence(TEST_SENTENCE, true, true)
+        );
+        assert!(matches!(model.reload(), Err(Error::NotReloadable)));
+
+        let arpa = std::fs::read("test_data/arpa/lm_small.arpa").unwrap();
+        assert!(Model::from_bytes(arpa, false).is_ok());
+    }
+
+    #[test]
+    fn rejects_broken_buffer() {
+        let bytes = std::fs::read("test_data/test.bin").unwrap();
+        assert!(matches!(
+            Model::from_bytes(&bytes[..64], false),
+            Err(Error::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn spilled_files_are_unique_and_removed() {
+        let first = BufferFile::spill(b"first").unwrap();
+        let second = BufferFile::spill(b"second").unwrap();
+        assert_ne!(first.path(), second.path());
+        assert_eq!(std::fs::read(first.path()).unwrap(), b"first");
+        let path = second.path().to_string();
+        drop(second);
+        assert!(!std::path::Path::new(&path).exists());
+    }
+}
@@ -1,38 +1,175 @@
-use std::io::{BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 
 use crate::headers::{Counts, FixedParameters, Sanity};
 use crate::reader::arpa::ArpaReader;
-use crate::{headers, Error, LoadMethod};
+use crate::{headers, Error, LoadMethod, ModelType};
 
 use crate::cxx::bridge::get_max_order;
 
-use super::Model;
+use autocxx::c_uint;
 
-pub(crate) struct ModelBuilder {
+use super::{ConfigBuilder, MemoryFootprint, Model, WarningAction, WordIdx};
+
+/// Builder for a [Model] with control over how it is loaded
+///
+/// Obtained through [Model::builder]. By default, the model is loaded lazily without storing its
+/// vocab, like `Model::new(file_name, false)`.
+/// ```
+/// use kenlm_rs::{LoadMethod, Model};
+///
+/// let model = Model::builder("test_data/test.bin")
+///     .with_load_method(LoadMethod::PopulateOrRead)
+///     .store_vocab(true)
+///     .build()
+///     .unwrap();
+/// assert!(model.get_vocab().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModelBuilder {
     vocab: bool,
+    sort_vocab: bool,
+    #[cfg(feature = "checksum")]
+    verify_checksum: bool,
+    config: ConfigBuilder,
     file_name: String,
     load_method: LoadMethod,
+    max_memory_bytes: Option<u64>,
+    /// Unset for models loaded from a buffer, their file is gone after loading
+    reloadable: bool,
 }
 
 impl ModelBuilder {
-    pub(crate) fn new(file_name: &str) -> Self {
+    /// Constructs a builder loading the model from `file_name`
+    pub fn new(file_name: &str) -> Self {
         Self {
             vocab: false,
+            sort_vocab: false,
+            #[cfg(feature = "checksum")]
+            verify_checksum: false,
+            config: ConfigBuilder::default(),
             file_name: file_name.into(),
             load_method: LoadMethod::Lazy,
+            max_memory_bytes: None,
+            reloadable: true,
         }
     }
 
-    pub(crate) fn with_load_method(mut self, load_method: LoadMethod) -> Self {
+    pub(crate) fn with_file_name(mut self, file_name: &str) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Marks the model as loaded from a file which does not outlive loading
+    pub(crate) fn not_reloadable(mut self) -> Self {
+        self.reloadable = false;
+        self
+    }
+
+    pub(crate) fn is_reloadable(&self) -> bool {
+        self.reloadable
+    }
+
+    /// The storage type ARPA files are loaded as, binaries store their own
+    pub(crate) fn arpa_model_type(&self) -> ModelType {
+        self.config.arpa_model_type
+    }
+
+    /// The probing multiplier ARPA files are loaded with, binaries store their own
+    pub(crate) fn probing_multiplier(&self) -> f32 {
+        self.config.probing_multiplier
+    }
+
+    pub(crate) fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Sets how KenLM loads the model, defaults to [LoadMethod::Lazy]
+    ///
+    /// [LoadMethod::PopulateOrRead] avoids page faults while scoring the first queries at the
+    /// cost of reading the whole file while loading.
+    pub fn with_load_method(mut self, load_method: LoadMethod) -> Self {
         self.load_method = load_method;
         self
     }
 
-    pub(crate) fn store_vocab(mut self, store_vocab: bool) -> Self {
+    /// Sets whether the vocab is copied into the [Model], see `Model::new`
+    pub fn store_vocab(mut self, store_vocab: bool) -> Self {
         self.vocab = store_vocab;
         self
     }
 
+    /// Sets whether the stored vocab is sorted, see `Model::new_with_sorted_vocab`
+    pub fn sort_vocab(mut self, sort_vocab: bool) -> Self {
+        self.sort_vocab = sort_vocab;
+        self
+    }
+
+    /// Sets whether the model is verified against its checksum sidecar, see
+    /// `Model::new_with_checksum`
+    #[cfg(feature = "checksum")]
+    pub fn verify_checksum(mut self, verify_checksum: bool) -> Self {
+        self.verify_checksum = verify_checksum;
+        self
+    }
+
+    /// Sets further KenLM options, see [ConfigBuilder]
+    pub fn config(mut self, config: ConfigBuilder) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets a memory budget in bytes the model has to fit into, unlimited by default
+    ///
+    /// The size of the model is estimated from its headers before KenLM loads it, with KenLM's
+    /// own computation like [ConfigBuilder::estimate_memory]. Models exceeding the budget are
+    /// rejected with [Error::MemoryBudgetExceeded] instead of running out of memory halfway
+    /// through loading, models whose size can't be estimated are rejected with the error of the
+    /// estimate. Quantized binaries are assumed to use KenLM's default number of bits. The vocab
+    /// copy of [ModelBuilder::store_vocab] is only known after loading, the model is dropped
+    /// again if it exceeds the budget together with the copy.
+    /// ```
+    /// use kenlm_rs::{Error, Model};
+    ///
+    /// let result = Model::builder("test_data/carol.bin").max_memory_bytes(1024).build();
+    /// assert!(matches!(result, Err(Error::MemoryBudgetExceeded { .. })));
+    /// ```
+    pub fn max_memory_bytes(mut self, bytes: u64) -> Self {
+        self.max_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Lowers the memory budget to `bytes` if it is larger or unset
+    pub(crate) fn limit_memory(mut self, bytes: u64) -> Self {
+        self.max_memory_bytes = Some(self.max_memory_bytes.map_or(bytes, |max| max.min(bytes)));
+        self
+    }
+
+    /// Rejects `required` bytes exceeding the memory budget
+    fn verify_memory_budget(&self, required: u64) -> Result<(), Error> {
+        match self.max_memory_bytes {
+            Some(budget) if required > budget => {
+                Err(Error::MemoryBudgetExceeded { required, budget })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Splits the enumerated vocab into words and the indices KenLM passed for them, sorts it by
+    /// word if requested
+    fn index_vocab(
+        &self,
+        vocab: Option<Vec<(u32, String)>>,
+    ) -> Option<(Vec<String>, Vec<WordIdx>)> {
+        let mut indexed = vocab?
+            .into_iter()
+            .map(|(idx, word)| (word, WordIdx(c_uint(idx))))
+            .collect::<Vec<_>>();
+        if self.sort_vocab {
+            indexed.sort_by(|(w1, _), (w2, _)| w1.cmp(w2));
+        }
+        Some(indexed.into_iter().unzip())
+    }
+
     fn verify_sanity(&self, sanity_header: Sanity) -> Result<(), Error> {
         if sanity_header != Sanity::REFERENCE {
             eprintln!(
@@ -45,14 +182,19 @@ impl ModelBuilder {
         Ok(())
     }
 
-    pub(crate) fn verify(&self, fixed_params: &FixedParameters) -> Result<(), Error> {
+    pub(crate) fn verify(
+        &self,
+        fixed_params: &FixedParameters,
+        enumerate_vocab: bool,
+    ) -> Result<(), Error> {
         if get_max_order() < fixed_params.order {
             return Err(Error::IncompatibleMaxOrder {
                 max_order: get_max_order().into(),
                 model_order: fixed_params.order.into(),
             });
         }
-        if self.vocab && !fixed_params.has_vocabulary() {
+        fixed_params.model_type()?;
+        if enumerate_vocab && !fixed_params.has_vocabulary() {
             return Err(Error::ModelHasNoVocab);
         }
         Ok(())
@@ -68,39 +210,248 @@ impl ModelBuilder {
         Ok(())
     }
 
-    pub(crate) fn build(self) -> Result<Model, Error> {
+    /// Estimates the memory in bytes the model takes when loaded as `model_type`
+    ///
+    /// Only the headers of the file are read, so this is cheap even for large models. The counts
+    /// are taken from the ARPA header or the binary header and passed on to
+    /// [ConfigBuilder::estimate_memory] with the options of this builder. Remote and compressed
+    /// files are not supported.
+    /// ```
+    /// use kenlm_rs::{Model, ModelType};
+    ///
+    /// let builder = Model::builder("test_data/arpa/lm_small.arpa");
+    /// let bytes = builder.estimate_memory(ModelType::Probing).unwrap();
+    /// assert!(bytes > 0);
+    /// ```
+    pub fn estimate_memory(&self, model_type: ModelType) -> Result<u64, Error> {
+        let (counts, _, _) = self.read_counts()?;
+        self.config
+            .estimate_memory(&cardinalities(&counts), model_type)
+    }
+
+    /// Estimates the memory in bytes the model takes when loaded as the type it is stored as
+    ///
+    /// ARPA files are loaded as [ConfigBuilder::arpa_model_type].
+    pub(crate) fn estimate_load_memory(&self) -> Result<u64, Error> {
+        let (counts, model_type, probing_multiplier) = self.read_counts()?;
+        model_bytes(&counts, model_type, probing_multiplier)
+    }
+
+    /// Reads the n-gram counts, the model type and the probing multiplier of an ARPA or binary file
+    fn read_counts(&self) -> Result<(Counts, ModelType, f32), Error> {
+        let mut fd = std::fs::File::open(&self.file_name)
+            .map_err(|_| Error::FileNotFound(self.file_name.to_string()))?;
+        if let Ok(arpa_reader) = ArpaReader::new(BufReader::new(&mut fd)) {
+            return Ok((
+                arpa_reader.counts().clone(),
+                self.config.arpa_model_type,
+                self.config.probing_multiplier,
+            ));
+        }
+        fd.seek(SeekFrom::Start(0))?;
+        self.verify_sanity(Sanity::from_file(&mut fd)?)?;
+        let fixed_params = headers::FixedParameters::from_file(&mut fd)?;
+        let counts = Counts::from_kenlm_binary(&mut fd, &fixed_params)?;
+        Ok((
+            counts,
+            fixed_params.model_type()?,
+            fixed_params.probing_multiplier,
+        ))
+    }
+
+    /// Rejects ARPA files with irregularities KenLM is configured to throw on
+    ///
+    /// Only the unigram section is read: missing `<unk>`, `<s>` and `</s>` and unigrams with a
+    /// positive log-probability are checked in Rust for more specific errors than KenLM's
+    /// exceptions. Higher orders are left to KenLM, scanning them as well would parse the whole
+    /// file twice, positive log-probabilities there surface as [Error::KenLMError]. Files the
+    /// Rust reader fails on are left to KenLM too.
+    fn verify_arpa_policies<B: BufRead>(
+        &self,
+        arpa_reader: &mut ArpaReader<B>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        let unknown = config.unknown_missing == WarningAction::ThrowUp;
+        let markers = config.sentence_marker_missing == WarningAction::ThrowUp;
+        let positive = config.positive_log_probability == WarningAction::ThrowUp;
+        if !unknown && !markers && !positive {
+            return Ok(());
+        }
+        // KenLM looks for the literal spellings, regardless of the model's `SpecialTokens`
+        let (missing, positive_unigram) =
+            match arpa_reader.missing_unigrams(&["<unk>", "<s>", "</s>"]) {
+                Ok(scanned) => scanned,
+                Err(_) => return Ok(()),
+            };
+        for word in missing {
+            match word {
+                "<unk>" if unknown => return Err(Error::UnknownMissing),
+                "<s>" | "</s>" if markers => {
+                    return Err(Error::SentenceMarkerMissing(word.to_string()))
+                }
+                _ => {}
+            }
+        }
+        match positive_unigram {
+            Some(ngram) if positive => Err(Error::PositiveLogProbability(ngram)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Loads the model
+    pub fn build(self) -> Result<Model, Error> {
+        self.load(None)
+    }
+
+    /// Loads the model, calling `on_word` with every word of the vocab and its index
+    ///
+    /// The words are streamed while KenLM loads the vocab, so custom lookup structures can be
+    /// built without first copying the vocab into the [Model]. The vocab is still stored if
+    /// [ModelBuilder::store_vocab] is set. Like `store_vocab`, this fails with
+    /// [Error::ModelHasNoVocab] if the model has no vocab. `on_word` is only called before this
+    /// returns, it is not kept for [Model::reload].
+    /// ```
+    /// use kenlm_rs::Model;
+    ///
+    /// let mut words = vec![];
+    /// let model = Model::builder("test_data/test.bin")
+    ///     .build_with_vocab_callback(|index, word| words.push((index, word.to_string())))
+    ///     .unwrap();
+    /// assert_eq!(words.len(), model.vocab_size());
+    /// assert!(model.get_vocab().is_none());
+    /// ```
+    pub fn build_with_vocab_callback(
+        self,
+        mut on_word: impl FnMut(WordIdx, &str) + '_,
+    ) -> Result<Model, Error> {
+        self.load(Some(Box::new(move |index, word| {
+            on_word(WordIdx::from_u32(index), word)
+        })))
+    }
+
+    #[cfg_attr(not(any(feature = "remote", feature = "zstd")), allow(unused_mut))]
+    fn load(mut self, on_word: Option<Box<dyn FnMut(u32, &str) + '_>>) -> Result<Model, Error> {
+        let enumerate_vocab = self.vocab || on_word.is_some();
+        // kept for reloading, before the file name is replaced by a downloaded or decompressed one
+        let builder = self.clone();
+        self.config.validate()?;
+        #[cfg(feature = "checksum")]
+        #[cfg_attr(not(feature = "remote"), allow(unused_mut))]
+        let mut verify_sidecar = self.verify_checksum;
+        // URLs are downloaded to the cache, the model is then loaded from there
+        #[cfg(feature = "remote")]
+        if super::remote::is_remote(&self.file_name) {
+            // downloads are verified against the url's fragment, there is no sidecar
+            if verify_sidecar && !super::remote::has_checksum(&self.file_name) {
+                return Err(Error::ChecksumMissing(self.file_name.clone()));
+            }
+            verify_sidecar = false;
+            let path = super::remote::fetch(&self.file_name)?;
+            self.file_name = path
+                .to_str()
+                .ok_or_else(|| Error::FileNotFound(path.display().to_string()))?
+                .to_string();
+        }
+        // the local file as it is on disk, compressed models before they are decompressed
+        #[cfg(feature = "checksum")]
+        if verify_sidecar {
+            super::checksum::verify_sidecar(&self.file_name)?;
+        }
+        // Compressed models are decompressed first, temporary files live until the end of `build`
+        #[cfg(feature = "zstd")]
+        let _decompressed = if super::compressed::is_compressed(&self.file_name) {
+            let decompressed = super::compressed::decompress(&self.file_name, self.load_method)?;
+            self.file_name = decompressed
+                .path()
+                .to_str()
+                .ok_or_else(|| Error::FileNotFound(decompressed.path().display().to_string()))?
+                .to_string();
+            Some(decompressed)
+        } else {
+            None
+        };
         let mut fd = std::fs::File::open(&self.file_name)
             .map_err(|_| Error::FileNotFound(self.file_name.to_string()))?;
         let mut config = crate::cxx::Config::default();
-        config.set_load_method(self.load_method)?;
-        if self.vocab {
-            config.add_vocab_fetch_callback();
+        config.set_load_method(self.config.effective_load_method(self.load_method))?;
+        config.apply(&self.config);
+        if enumerate_vocab {
+            config.add_vocab_callback(self.vocab, on_word);
         };
 
-        if let Ok(arpa_reader) = ArpaReader::new(BufReader::new(&mut fd)) {
+        if let Ok(mut arpa_reader) = ArpaReader::new(BufReader::new(&mut fd)) {
             self.verify_arpa(arpa_reader.counts())?;
-            let inner = crate::cxx::CxxModel::load_from_file_with_config(&self.file_name, &config);
-            Ok(Model {
+            self.verify_arpa_policies(&mut arpa_reader)?;
+            // surfaces the IO error instead of KenLM's exception if the binary can't be created
+            if let Some(path) = &self.config.write_binary {
+                std::fs::File::create(path)?;
+            }
+            let model_bytes = model_bytes(
+                arpa_reader.counts(),
+                self.config.arpa_model_type,
+                self.config.probing_multiplier,
+            )?;
+            self.verify_memory_budget(model_bytes)?;
+            let locked_memory = self.config.validate_lock_limit(model_bytes)?;
+            let inner = crate::cxx::CxxModel::load_from_file_with_config(&self.file_name, &config)?;
+            let mut model = Model::from_parts(
                 inner,
-                vocab: config.get_vocab(),
-                fixed_parameters: None,
-                count_header: arpa_reader.counts().clone(),
-            })
+                builder,
+                None,
+                arpa_reader.counts().clone(),
+                model_bytes,
+                self.index_vocab(config.get_vocab()),
+            );
+            model.locked_memory = locked_memory;
+            self.verify_memory_budget(model.memory_usage().total())?;
+            Ok(model)
         } else {
             fd.seek(SeekFrom::Start(0))?;
             let sanity_header = Sanity::from_file(&mut fd)?;
             self.verify_sanity(sanity_header)?;
             let fixed_params = headers::FixedParameters::from_file(&mut fd)?;
-            self.verify(&fixed_params)?;
+            self.verify(&fixed_params, enumerate_vocab)?;
             let count_header = Counts::from_kenlm_binary(&mut fd, &fixed_params)?;
+            let model_type = fixed_params.model_type()?;
+            let model_bytes =
+                model_bytes(&count_header, model_type, fixed_params.probing_multiplier)?;
+            self.verify_memory_budget(model_bytes)?;
+            let locked_memory = self.config.validate_lock_limit(model_bytes)?;
 
-            let inner = crate::cxx::CxxModel::load_from_file_with_config(&self.file_name, &config);
-            Ok(Model {
+            let inner = crate::cxx::CxxModel::load_from_file_with_config(&self.file_name, &config)?;
+            let mut model = Model::from_parts(
                 inner,
-                vocab: config.get_vocab(),
-                fixed_parameters: Some(fixed_params),
+                builder,
+                Some(fixed_params),
                 count_header,
-            })
+                model_bytes,
+                self.index_vocab(config.get_vocab()),
+            );
+            model.locked_memory = locked_memory;
+            self.verify_memory_budget(model.memory_usage().total())?;
+            Ok(model)
         }
     }
 }
+
+/// Size KenLM allocates for the n-grams and the vocab of a model, see [MemoryFootprint]
+///
+/// The size is computed by KenLM, like [ConfigBuilder::estimate_memory]. Fails for unigram
+/// models, which KenLM refuses to load as well.
+fn model_bytes(
+    counts: &Counts,
+    model_type: ModelType,
+    probing_multiplier: f32,
+) -> Result<u64, Error> {
+    MemoryFootprint::estimate(&cardinalities(counts), model_type, probing_multiplier)
+        .map(|footprint| footprint.total())
+}
+
+/// Number of n-grams per order, starting with the unigrams
+fn cardinalities(counts: &Counts) -> Vec<u64> {
+    counts
+        .counts()
+        .iter()
+        .map(|count| count.cardinality as u64)
+        .collect()
+}
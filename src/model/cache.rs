@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+/// Environment variable overriding the directory models are cached in
+const CACHE_DIR_ENV: &str = "KENLM_RS_CACHE";
+
+/// Returns the directory downloaded and decompressed models are cached in, creates it if needed
+pub(crate) fn cache_dir() -> std::io::Result<PathBuf> {
+    let cache_dir = std::env::var_os(CACHE_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("kenlm-rs"));
+    std::fs::create_dir_all(&cache_dir)?;
+    Ok(cache_dir)
+}
@@ -0,0 +1,216 @@
+use autocxx::prelude::*;
+
+use super::{Model, State, WordIdx};
+use crate::cxx::bridge;
+
+/// Left and right state of a sentence fragment, wraps KenLM's `lm::ngram::ChartState`
+///
+/// The right state is the [State] after the last word of the fragment. The left state points to
+/// the n-grams at the start of the fragment whose scores still change once words are added to
+/// its left. Chart decoders store one with every chart entry, see [RuleScore].
+#[derive(Debug, Clone)]
+pub struct ChartState(bridge::lm::ngram::ChartState);
+
+// SAFETY: `lm::ngram::ChartState` is a plain struct of words, backoffs, n-gram offsets and
+// lengths, the offsets are not dereferenced outside of scoring with the model.
+unsafe impl Send for ChartState {}
+unsafe impl Sync for ChartState {}
+
+impl ChartState {
+    /// Constructs the state of an empty fragment
+    pub fn new() -> Self {
+        // SAFETY: `lm::ngram::ChartState` only holds plain numbers and a bool, for which all zero
+        // bytes are valid, it is the empty state `RuleScore` starts with
+        Self(unsafe { std::mem::zeroed() })
+    }
+
+    /// The state after the last word of the fragment, for continuing left-to-right
+    pub fn right(&self) -> State {
+        State(self.0.right.clone())
+    }
+
+    /// Number of words at the start of the fragment whose n-grams may extend to the left
+    pub fn left_len(&self) -> usize {
+        self.0.left.length as usize
+    }
+
+    /// Whether words added to the left leave the scores of the fragment's words unchanged
+    pub fn is_left_full(&self) -> bool {
+        self.0.left.full
+    }
+}
+
+impl Default for ChartState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Chart states are equal if they score the same in any context, like KenLM's `operator==`
+impl PartialEq for ChartState {
+    fn eq(&self, other: &Self) -> bool {
+        let (left, other_left) = (&self.0.left, &other.0.left);
+        let length = left.length as usize;
+        let left_eq = length == other_left.length as usize
+            && (length == 0
+                || (left.pointers[length - 1] == other_left.pointers[length - 1]
+                    && left.full == other_left.full));
+        left_eq && self.right() == other.right()
+    }
+}
+
+impl Eq for ChartState {}
+
+/// Hashes the chart state with KenLM's `hash_value`, consistent with [PartialEq]
+impl std::hash::Hash for ChartState {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(bridge::lm::base::ChartStateHash(&self.0));
+    }
+}
+
+impl std::fmt::Debug for bridge::lm::ngram::ChartState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChartState")
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for bridge::lm::ngram::Left {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Left")
+            .field("pointers", &self.pointers)
+            .field("length", &self.length)
+            .field("full", &self.full)
+            .finish()
+    }
+}
+
+/// Scores rule applications of chart decoders, wraps KenLM's `lm::ngram::RuleScore`
+///
+/// Fragments grow on both sides: a rule's right-hand side is scored from left to right by
+/// passing its words to `terminal` and the [ChartState]s of its non-terminals to
+/// `non_terminal`. `finish` returns the log10 probability of the rule and the [ChartState] of
+/// the resulting fragment. The probabilities of words whose context is incomplete are
+/// estimates which are corrected once the fragment is used as a non-terminal.
+/// ```
+/// use kenlm_rs::{Model, RuleScore};
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let mut rule = RuleScore::new(&model);
+/// rule.terminal("have");
+/// rule.terminal("a");
+/// let (have_a, have_a_state) = rule.finish();
+///
+/// rule.begin_sentence();
+/// rule.terminal("i");
+/// rule.non_terminal(&have_a_state, have_a);
+/// rule.terminal("good");
+/// rule.terminal("deal");
+/// rule.terminal("</s>");
+/// let (total, _) = rule.finish();
+///
+/// let expected = model.score_sentence(&["i", "have", "a", "good", "deal"], true, true);
+/// assert!((total - expected).abs() < 1e-4);
+/// ```
+pub struct RuleScore<'m> {
+    model: &'m Model,
+    inner: UniquePtr<bridge::lm::base::RuleScorer>,
+}
+
+impl<'m> RuleScore<'m> {
+    /// Constructs a scorer for rules of `model`
+    pub fn new(model: &'m Model) -> Self {
+        let inner = bridge::lm::base::RuleScorer_Create(&model.inner);
+        // LoadVirtualPtr only loads the model types RuleScorer_Create downcasts to
+        assert!(!inner.is_null(), "model is none of KenLM's model types");
+        RuleScore { model, inner }
+    }
+
+    /// Starts the rule with the `<s>` context
+    ///
+    /// Must be the first call of a rule, `<s>` must not be passed to `terminal`.
+    pub fn begin_sentence(&mut self) {
+        self.inner.pin_mut().BeginSentence();
+    }
+
+    /// Appends `word` to the rule
+    pub fn terminal(&mut self, word: &str) {
+        self.terminal_index(self.model.get_word_idx(word));
+    }
+
+    /// Appends the word at `index` to the rule
+    pub fn terminal_index(&mut self, index: WordIdx) {
+        self.inner.pin_mut().Terminal(index.0);
+    }
+
+    /// Starts the rule with the fragment of `state`, faster than `non_terminal` as first call
+    ///
+    /// `log_prob` is added to the rule's probability, pass the fragment's score to get scores
+    /// including it or `0.` for scores relative to it.
+    pub fn begin_non_terminal(&mut self, state: &ChartState, log_prob: f32) {
+        self.inner.pin_mut().BeginNonTerminal(&state.0, log_prob);
+    }
+
+    /// Appends the fragment of `state` to the rule, see `begin_non_terminal` for `log_prob`
+    pub fn non_terminal(&mut self, state: &ChartState, log_prob: f32) {
+        self.inner.pin_mut().NonTerminal(&state.0, log_prob);
+    }
+
+    /// Returns the log10 probability and the [ChartState] of the rule and starts a new rule
+    pub fn finish(&mut self) -> (f32, ChartState) {
+        let log_prob = self.inner.pin_mut().Finish();
+        let state = ChartState(self.inner.Out().clone());
+        self.inner.pin_mut().Reset();
+        (log_prob, state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ChartState, RuleScore};
+    use crate::Model;
+
+    #[test]
+    fn rules_add_up_to_the_sentence_score() {
+        let words = ["i", "have", "a", "good", "deal", "of", "will"];
+        for model_file in ["test_data/test.bin", "test_data/carol_probing_bigram.bin"] {
+            let model = Model::new(model_file, false).unwrap();
+            let expected = model.score_sentence(&words, true, true);
+
+            let mut rule = RuleScore::new(&model);
+            for word in &words[3..] {
+                rule.terminal(word);
+            }
+            let (suffix, suffix_state) = rule.finish();
+            rule.terminal(words[1]);
+            rule.terminal(words[2]);
+            let (middle, middle_state) = rule.finish();
+
+            rule.begin_sentence();
+            rule.terminal(words[0]);
+            rule.non_terminal(&middle_state, middle);
+            rule.non_terminal(&suffix_state, suffix);
+            rule.terminal("</s>");
+            let (total, state) = rule.finish();
+            assert!((total - expected).abs() < 1e-4, "{model_file}");
+            assert!(state.is_left_full());
+            assert_eq!(suffix_state.right(), model.state_from_context(&words[3..]));
+        }
+    }
+
+    #[test]
+    fn equal_fragments_have_equal_states() {
+        let model = Model::new("test_data/test.bin", false).unwrap();
+        let mut rule = RuleScore::new(&model);
+        rule.terminal("good");
+        rule.terminal("deal");
+        let (_, first) = rule.finish();
+        rule.terminal("good");
+        rule.terminal("deal");
+        let (_, second) = rule.finish();
+        assert_eq!(first, second);
+        assert_ne!(first, ChartState::new());
+    }
+}
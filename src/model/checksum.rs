@@ -0,0 +1,102 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::Error;
+
+const SIDECAR_EXTENSION: &str = ".sha256";
+
+/// Returns the hex encoded sha256 digest of the file at `path`
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Fails with [Error::ChecksumMismatch] if the digest of `path` is not `expected`
+pub(crate) fn verify_digest(path: &Path, expected: &str) -> Result<(), Error> {
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(Error::ChecksumMismatch {
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn sidecar_path(file_name: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}", file_name, SIDECAR_EXTENSION))
+}
+
+/// Writes `<file_name>.sha256` in the format of `sha256sum`, returns its path
+pub(crate) fn write_sidecar(file_name: &str) -> Result<PathBuf, Error> {
+    let path = Path::new(file_name);
+    let digest = sha256_file(path).map_err(|_| Error::FileNotFound(file_name.into()))?;
+    let base_name = path
+        .file_name()
+        .ok_or_else(|| Error::FileNotFound(file_name.into()))?
+        .to_string_lossy();
+    let sidecar = sidecar_path(file_name);
+    writeln!(File::create(&sidecar)?, "{}  {}", digest, base_name)?;
+    Ok(sidecar)
+}
+
+/// Verifies `file_name` against the digest stored in `<file_name>.sha256`
+pub(crate) fn verify_sidecar(file_name: &str) -> Result<(), Error> {
+    let sidecar = sidecar_path(file_name);
+    let contents = std::fs::read_to_string(&sidecar)
+        .map_err(|_| Error::ChecksumMissing(sidecar.display().to_string()))?;
+    let expected = contents
+        .split_ascii_whitespace()
+        .next()
+        .ok_or_else(|| Error::ChecksumMissing(sidecar.display().to_string()))?;
+    verify_digest(Path::new(file_name), expected)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    use std::path::Path;
+
+    use super::{verify_digest, verify_sidecar, write_sidecar};
+    use crate::Error;
+
+    #[test]
+    fn writes_and_verifies_sidecar() {
+        let file_name = std::env::temp_dir().join(format!(
+            "kenlm-rs-checksum-test-{}-{:016x}.arpa",
+            std::process::id(),
+            RandomState::new().build_hasher().finish()
+        ));
+        std::fs::copy("test_data/arpa/lm_small.arpa", &file_name).unwrap();
+        let file_name = file_name.to_str().unwrap();
+
+        assert!(matches!(
+            verify_sidecar(file_name),
+            Err(Error::ChecksumMissing(_))
+        ));
+        let sidecar = write_sidecar(file_name).unwrap();
+        verify_sidecar(file_name).unwrap();
+
+        std::fs::write(file_name, "\\data\\\n").unwrap();
+        assert!(matches!(
+            verify_sidecar(file_name),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        std::fs::remove_file(file_name).unwrap();
+        std::fs::remove_file(sidecar).unwrap();
+    }
+
+    #[test]
+    fn rejects_wrong_digest() {
+        let path = Path::new("test_data/arpa/lm_small.arpa");
+        assert!(matches!(
+            verify_digest(path, "00"),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+    }
+}
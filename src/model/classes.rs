@@ -0,0 +1,83 @@
+use regex::Regex;
+
+use crate::Error;
+
+/// Placeholder classes for tokens
+///
+/// It is common to replace numbers, URLs or e-mail addresses by placeholders such as `<num>`
+/// before training a model. `TokenClasses` applies the same mapping at query time, otherwise
+/// all of these tokens end up as `<unk>`. Each class is a regex and a placeholder, a token is
+/// replaced by the placeholder of the first class whose regex matches the whole token.
+#[derive(Debug, Clone, Default)]
+pub struct TokenClasses {
+    classes: Vec<(Regex, String)>,
+}
+
+impl TokenClasses {
+    /// Constructs an empty set of classes which leaves all tokens untouched
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Classes for e-mail addresses, URLs and numbers mapping to `<email>`, `<url>` and `<num>`
+    pub fn with_defaults() -> Self {
+        Self::new()
+            .add(r"[^@\s]+@[^@\s]+\.[^@\s]+", "<email>")
+            .and_then(|classes| classes.add(r"(?:https?://|www\.)\S+", "<url>"))
+            .and_then(|classes| classes.add(r"[+-]?\d+(?:[.,]\d+)*", "<num>"))
+            // These patterns are known to compile.
+            .unwrap()
+    }
+
+    /// Adds a class mapping tokens fully matching `pattern` to `placeholder`
+    ///
+    /// Classes are tried in the order they were added. Returns an error if `pattern` is not a
+    /// valid regex.
+    pub fn add(mut self, pattern: &str, placeholder: &str) -> Result<Self, Error> {
+        let regex = Regex::new(&format!("^(?:{pattern})$"))?;
+        self.classes.push((regex, placeholder.to_string()));
+        Ok(self)
+    }
+
+    /// Returns the placeholder of the first matching class or `token` itself
+    pub fn classify<'a>(&'a self, token: &'a str) -> &'a str {
+        self.classes
+            .iter()
+            .find(|(regex, _)| regex.is_match(token))
+            .map(|(_, placeholder)| placeholder.as_str())
+            .unwrap_or(token)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TokenClasses;
+
+    #[test]
+    fn default_classes() {
+        let classes = TokenClasses::with_defaults();
+        assert_eq!(classes.classify("42"), "<num>");
+        assert_eq!(classes.classify("-3,141.5"), "<num>");
+        assert_eq!(classes.classify("https://example.com/a?b=c"), "<url>");
+        assert_eq!(classes.classify("www.example.com"), "<url>");
+        assert_eq!(classes.classify("someone@example.com"), "<email>");
+        assert_eq!(classes.classify("word"), "word");
+        assert_eq!(classes.classify("covid19"), "covid19");
+    }
+
+    #[test]
+    fn first_class_wins() {
+        let classes = TokenClasses::new()
+            .add(r"\d{4}", "<year>")
+            .unwrap()
+            .add(r"\d+", "<num>")
+            .unwrap();
+        assert_eq!(classes.classify("1984"), "<year>");
+        assert_eq!(classes.classify("19"), "<num>");
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(TokenClasses::new().add("(", "<broken>").is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use fst::{Automaton, IntoStreamer, Map};
+
+/// Prefix-completion index over a vocabulary
+///
+/// An [fst::Map] from word to its index in the language model. Words sharing a prefix are
+/// adjacent in the map, so completions are found by streaming the range below the prefix.
+pub(crate) struct CompletionIndex {
+    map: Map<Vec<u8>>,
+}
+
+impl CompletionIndex {
+    pub(crate) fn new(mut entries: Vec<(&str, u32)>) -> Result<Self, fst::Error> {
+        // fst requires lexicographically sorted and unique keys
+        entries.sort_unstable();
+        entries.dedup_by(|(w1, _), (w2, _)| w1 == w2);
+        let map = Map::from_iter(entries.into_iter().map(|(w, idx)| (w, u64::from(idx))))?;
+        Ok(Self { map })
+    }
+
+    /// Returns the indices of all words starting with `prefix` in lexicographic order
+    pub(crate) fn complete(&self, prefix: &str) -> Vec<u32> {
+        let matcher = fst::automaton::Str::new(prefix).starts_with();
+        self.map
+            .search(matcher)
+            .into_stream()
+            .into_values()
+            .into_iter()
+            // all values were inserted from u32
+            .map(|idx| idx as u32)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CompletionIndex;
+
+    #[test]
+    fn completes_prefixes() {
+        let index =
+            CompletionIndex::new(vec![("have", 3), ("had", 1), ("a", 0), ("hall", 2)]).unwrap();
+        assert_eq!(index.complete("ha"), &[1, 2, 3]);
+        assert_eq!(index.complete("hal"), &[2]);
+        assert_eq!(index.complete(""), &[0, 1, 2, 3]);
+        assert!(index.complete("x").is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use sha2::{Digest, Sha256};
+
+use super::buffer::create_unique;
+use super::cache::cache_dir;
+use crate::{Error, LoadMethod};
+
+const ZSTD_EXTENSION: &str = ".zst";
+
+/// Returns whether `file_name` is zstd-compressed and has to be decompressed before loading
+pub(crate) fn is_compressed(file_name: &str) -> bool {
+    file_name.ends_with(ZSTD_EXTENSION)
+}
+
+/// A decompressed model file
+///
+/// Temporary files are removed when this is dropped, which is safe once KenLM has read the
+/// model into memory.
+pub(crate) struct Decompressed {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl Decompressed {
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Decompressed {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Decompresses `file_name` for loading it with `load_method`
+///
+/// With [LoadMethod::Read] and [LoadMethod::ParallelRead], KenLM copies the model into memory,
+/// so it is decompressed into a temporary file that is removed after loading. The other load
+/// methods map the file, so it is decompressed into the cache directory and kept there. It is
+/// written under a unique name and renamed into place once complete. A cached file is reused as
+/// long as the compressed file's size and modification time do not change.
+pub(crate) fn decompress(file_name: &str, load_method: LoadMethod) -> Result<Decompressed, Error> {
+    let compressed = File::open(file_name).map_err(|_| Error::FileNotFound(file_name.into()))?;
+    let key = cache_key(file_name, &compressed)?;
+    let temporary = matches!(load_method, LoadMethod::Read | LoadMethod::ParallelRead);
+
+    let dir = cache_dir()?;
+    let cached = dir.join(&key);
+    if !temporary && cached.exists() {
+        return Ok(Decompressed {
+            path: cached,
+            temporary,
+        });
+    }
+
+    // Concurrent loads of the same model each write their own file, removed again on errors
+    let (mut file, path) = create_unique(&dir, &key)?;
+    let mut decompressed = Decompressed {
+        path,
+        temporary: true,
+    };
+    let mut decoder = zstd::Decoder::new(BufReader::new(compressed))?;
+    std::io::copy(&mut decoder, &mut file)?;
+    drop(file);
+    if !temporary {
+        std::fs::rename(&decompressed.path, &cached)?;
+        decompressed.path = cached;
+        decompressed.temporary = false;
+    }
+    Ok(decompressed)
+}
+
+/// Name of the cached file, a sha256 of the path, size and modification time of `compressed`
+///
+/// The hash has to be stable across Rust releases for the cache to outlive them, which
+/// `DefaultHasher` does not guarantee.
+fn cache_key(file_name: &str, compressed: &File) -> Result<String, Error> {
+    let metadata = compressed.metadata()?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(
+        std::fs::canonicalize(file_name)?
+            .to_string_lossy()
+            .as_bytes(),
+    );
+    hasher.update([0]);
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(modified.as_secs().to_le_bytes());
+    hasher.update(modified.subsec_nanos().to_le_bytes());
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use super::{cache_key, decompress};
+    use crate::{LoadMethod, Model};
+
+    #[test]
+    fn cache_key_is_a_sha256() {
+        let file_name = "test_data/test.bin.zst";
+        let key = cache_key(file_name, &File::open(file_name).unwrap()).unwrap();
+        assert_eq!(key.len(), 64);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(
+            key,
+            cache_key(file_name, &File::open(file_name).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn temporary_files_are_unique() {
+        let first = decompress("test_data/test.bin.zst", LoadMethod::Read).unwrap();
+        let second = decompress("test_data/test.bin.zst", LoadMethod::Read).unwrap();
+        assert_ne!(first.path(), second.path());
+        assert_eq!(
+            std::fs::read(first.path()).unwrap(),
+            std::fs::read("test_data/test.bin").unwrap()
+        );
+        let path = second.path().to_path_buf();
+        drop(second);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn loads_compressed_model() {
+        let expected = Model::new("test_data/test.bin", false).expect("should exist");
+        for load_method in [LoadMethod::Lazy, LoadMethod::Read] {
+            let model = Model::new_with_load_method("test_data/test.bin.zst", false, load_method)
+                .expect("should decompress");
+            assert_eq!(
+                model.score_sentence(&["i", "have", "a", "good", "deal"], true, true),
+                expected.score_sentence(&["i", "have", "a", "good", "deal"], true, true)
+            );
+        }
+    }
+}
@@ -0,0 +1,609 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Error, LoadMethod, ModelType};
+
+/// What KenLM does when it encounters an irregularity in an ARPA file
+///
+/// Mirrors `lm::WarningAction` in src/cxx/lm/lm_exception.hh.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WarningAction {
+    /// Fail loading with an [Error]
+    ThrowUp = 0,
+    /// Print a warning and carry on
+    Complain = 1,
+    /// Silently carry on
+    Silent = 2,
+}
+
+/// How much KenLM complains about ARPA files being slow to load
+///
+/// Mirrors `lm::ngram::Config::ARPALoadComplain` in src/cxx/lm/config.hh.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArpaComplain {
+    All = 0,
+    Expensive = 1,
+    None = 2,
+}
+
+/// Options of KenLM's `lm::ngram::Config` which are not covered by [crate::ModelBuilder]
+///
+/// Pass it to [crate::ModelBuilder::config]. The defaults are KenLM's defaults. The ARPA options
+/// only take effect when loading ARPA files, see src/cxx/lm/config.hh for details.
+/// ```
+/// use kenlm_rs::{ConfigBuilder, Model, WarningAction};
+///
+/// let config = ConfigBuilder::new()
+///     .show_progress(false)
+///     .positive_log_probability(WarningAction::Complain);
+/// let model = Model::builder("test_data/arpa/lm_small.arpa")
+///     .config(config)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigBuilder {
+    pub(crate) show_progress: bool,
+    pub(crate) messages: bool,
+    pub(crate) unknown_missing: WarningAction,
+    pub(crate) sentence_marker_missing: WarningAction,
+    pub(crate) positive_log_probability: WarningAction,
+    pub(crate) unknown_missing_log_prob: f32,
+    pub(crate) probing_multiplier: f32,
+    pub(crate) building_memory: u64,
+    pub(crate) arpa_complain: ArpaComplain,
+    pub(crate) populate: bool,
+    pub(crate) huge_pages: bool,
+    pub(crate) lock_memory: bool,
+    pub(crate) temporary_directory: Option<PathBuf>,
+    pub(crate) write_binary: Option<PathBuf>,
+    pub(crate) include_vocab: bool,
+    pub(crate) arpa_model_type: ModelType,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        ConfigBuilder {
+            show_progress: true,
+            messages: true,
+            unknown_missing: WarningAction::Complain,
+            sentence_marker_missing: WarningAction::ThrowUp,
+            positive_log_probability: WarningAction::ThrowUp,
+            unknown_missing_log_prob: -100.,
+            probing_multiplier: 1.5,
+            building_memory: 1 << 30,
+            arpa_complain: ArpaComplain::All,
+            populate: false,
+            huge_pages: true,
+            lock_memory: false,
+            temporary_directory: None,
+            write_binary: None,
+            include_vocab: true,
+            arpa_model_type: ModelType::Probing,
+        }
+    }
+}
+
+impl ConfigBuilder {
+    /// Constructs a config with KenLM's defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether a progress bar is printed while loading, defaults to `true`
+    pub fn show_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    /// Sets whether KenLM prints anything to stderr, defaults to `true`
+    ///
+    /// Disabling messages also disables the progress bar and all warnings.
+    pub fn messages(mut self, messages: bool) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Sets what happens if `<unk>` is missing, defaults to [WarningAction::Complain]
+    ///
+    /// With [WarningAction::ThrowUp], the unigrams of ARPA files are checked before loading and
+    /// a missing `<unk>` fails with [Error::UnknownMissing]. Otherwise, KenLM adds it with
+    /// [ConfigBuilder::unknown_missing_log_prob].
+    pub fn unknown_missing(mut self, action: WarningAction) -> Self {
+        self.unknown_missing = action;
+        self
+    }
+
+    /// Sets what happens if `<s>` or `</s>` are missing, defaults to [WarningAction::ThrowUp]
+    ///
+    /// With [WarningAction::ThrowUp], the unigrams of ARPA files are checked before loading and
+    /// missing markers fail with [Error::SentenceMarkerMissing]. Otherwise, KenLM adds them.
+    pub fn sentence_marker_missing(mut self, action: WarningAction) -> Self {
+        self.sentence_marker_missing = action;
+        self
+    }
+
+    /// Sets what happens on positive log probabilities, defaults to [WarningAction::ThrowUp]
+    ///
+    /// With [WarningAction::Complain] and [WarningAction::Silent], they are mapped to `0`. With
+    /// [WarningAction::ThrowUp], ARPA files are scanned before loading and positive
+    /// log-probabilities fail with [Error::PositiveLogProbability], which reads the file twice.
+    pub fn positive_log_probability(mut self, action: WarningAction) -> Self {
+        self.positive_log_probability = action;
+        self
+    }
+
+    /// Sets the log10 probability assigned to `<unk>` if it is missing, defaults to `-100`
+    pub fn unknown_missing_log_prob(mut self, log_prob: f32) -> Self {
+        self.unknown_missing_log_prob = log_prob;
+        self
+    }
+
+    /// Sets the size multiplier of probing hash tables, defaults to `1.5`, must be larger than `1`
+    pub fn probing_multiplier(mut self, multiplier: f32) -> Self {
+        self.probing_multiplier = multiplier;
+        self
+    }
+
+    /// Sets the sort buffer size in bytes used when building tries, defaults to 1 GiB
+    ///
+    /// Only applies when a trie is built from an ARPA file, the actual memory usage is higher.
+    pub fn building_memory(mut self, bytes: u64) -> Self {
+        self.building_memory = bytes;
+        self
+    }
+
+    /// Sets the storage type ARPA files are loaded as, defaults to [ModelType::Probing]
+    ///
+    /// Tries take less memory than probing hash tables but are slower to query. KenLM sorts the
+    /// n-grams on disk while building a trie, see [ConfigBuilder::building_memory] and
+    /// [ConfigBuilder::temporary_directory]. Binary models are loaded as the type they are
+    /// stored as.
+    /// ```
+    /// use kenlm_rs::{ConfigBuilder, Model, ModelType};
+    ///
+    /// let config = ConfigBuilder::new().arpa_model_type(ModelType::Trie);
+    /// let model = Model::builder("test_data/arpa/lm_small.arpa")
+    ///     .config(config)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(model.model_type(), ModelType::Trie);
+    /// ```
+    pub fn arpa_model_type(mut self, model_type: ModelType) -> Self {
+        self.arpa_model_type = model_type;
+        self
+    }
+
+    /// Sets where temporary files are written when building tries, defaults to next to the model
+    ///
+    /// If `directory` is an existing directory, the files are created inside of it, otherwise it
+    /// is used as a file name prefix. KenLM writes the sorted n-grams of ARPA files to disk while
+    /// building a trie, so this should point to a file system with enough space.
+    pub fn temporary_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.temporary_directory = Some(directory.into());
+        self
+    }
+
+    /// The prefix KenLM uses for temporary files, `None` if not set or not valid UTF-8
+    pub(crate) fn temporary_directory_prefix(&self) -> Option<&str> {
+        self.temporary_directory.as_ref()?.to_str()
+    }
+
+    /// Sets a path the model is written to as a binary while an ARPA file is loaded, unset by
+    /// default
+    ///
+    /// KenLM converts ARPA files to its binary format while loading them anyway, this keeps the
+    /// result so later loads can map the binary instead of parsing the ARPA file again. The
+    /// binary is of the type set with [ConfigBuilder::arpa_model_type], as if built with
+    /// `build_binary`. Binary models are loaded as is.
+    /// ```
+    /// use kenlm_rs::{ConfigBuilder, Model};
+    ///
+    /// let binary = std::env::temp_dir().join("kenlm-rs-doc-lm_small.bin");
+    /// let config = ConfigBuilder::new().messages(false).write_binary(&binary);
+    /// Model::builder("test_data/arpa/lm_small.arpa")
+    ///     .config(config)
+    ///     .build()
+    ///     .unwrap();
+    /// let model = Model::new(binary.to_str().unwrap(), true).unwrap();
+    /// # std::fs::remove_file(binary).unwrap();
+    /// ```
+    pub fn write_binary(mut self, path: impl Into<PathBuf>) -> Self {
+        self.write_binary = Some(path.into());
+        self
+    }
+
+    /// Sets whether the vocab is included in binaries written with
+    /// [ConfigBuilder::write_binary], defaults to `true`
+    ///
+    /// Without the vocab, the binary is smaller but can't be loaded with a stored vocab.
+    pub fn include_vocab(mut self, include_vocab: bool) -> Self {
+        self.include_vocab = include_vocab;
+        self
+    }
+
+    /// The path binaries are written to, `None` if not set or not valid UTF-8
+    pub(crate) fn write_binary_path(&self) -> Option<&str> {
+        self.write_binary.as_ref()?.to_str()
+    }
+
+    /// Sets how much KenLM complains about slow ARPA loading, defaults to [ArpaComplain::All]
+    pub fn arpa_complain(mut self, complain: ArpaComplain) -> Self {
+        self.arpa_complain = complain;
+        self
+    }
+
+    /// Sets whether the pages of a mapped model are faulted in while loading, defaults to `false`
+    ///
+    /// Turns [LoadMethod::Lazy] into [LoadMethod::PopulateOrLazy], so the first queries don't pay
+    /// for page faults. Populating is Linux specific, other systems load lazily. The other load
+    /// methods already populate or read the model.
+    pub fn populate(mut self, populate: bool) -> Self {
+        self.populate = populate;
+        self
+    }
+
+    /// Sets whether the model may be backed by huge pages, defaults to `true`
+    ///
+    /// KenLM asks the kernel for transparent huge pages where available, which reduces TLB
+    /// misses for large models. On Linux, this only takes effect for models read into memory,
+    /// e.g. with [LoadMethod::Read], file-backed mappings are not backed by huge pages.
+    pub fn huge_pages(mut self, huge_pages: bool) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    /// Sets whether the model is locked in memory after loading, defaults to `false`
+    ///
+    /// Locked pages are never swapped out, so queries don't hit page faults under memory
+    /// pressure. The model, together with the models locked before by this process, has to fit
+    /// into the `RLIMIT_MEMLOCK` limit of the process (see `ulimit -l`), loading fails with
+    /// [Error::MemoryLockLimit] otherwise. The size of the model is estimated like
+    /// [crate::Model::memory_usage]. Memory locked outside of this crate is not accounted for.
+    pub fn lock_memory(mut self, lock_memory: bool) -> Self {
+        self.lock_memory = lock_memory;
+        self
+    }
+
+    /// Estimates the memory in bytes a model of `model_type` with `counts` takes with these options
+    ///
+    /// `counts` holds the number of n-grams per order, starting with the unigrams. This is KenLM's
+    /// own size computation, the one `build_binary` reports before building. The estimate covers
+    /// the vocab and the n-grams, quantized models assume the default of 8 bits. Fails with
+    /// [Error::UnsupportedOrder] for fewer than two orders and with [Error::SizeEstimateError] if
+    /// KenLM can't size the model, e.g. for orders above `KENLM_MAX_ORDER`.
+    /// ```
+    /// use kenlm_rs::{ConfigBuilder, ModelType};
+    ///
+    /// let config = ConfigBuilder::new();
+    /// let probing = config.estimate_memory(&[10_000, 50_000, 80_000], ModelType::Probing).unwrap();
+    /// let trie = config.estimate_memory(&[10_000, 50_000, 80_000], ModelType::Trie).unwrap();
+    /// assert!(trie < probing);
+    /// ```
+    pub fn estimate_memory(&self, counts: &[u64], model_type: ModelType) -> Result<u64, Error> {
+        if counts.len() < 2 {
+            return Err(Error::UnsupportedOrder(counts.len()));
+        }
+        self.validate()?;
+        let mut config = crate::cxx::Config::default();
+        config.apply(self);
+        config.estimate_size(counts, model_type)
+    }
+
+    /// The load method used for `load_method` with these options
+    pub(crate) fn effective_load_method(&self, load_method: LoadMethod) -> LoadMethod {
+        match load_method {
+            LoadMethod::Lazy if self.populate => LoadMethod::PopulateOrLazy,
+            load_method => load_method,
+        }
+    }
+
+    /// Rejects values KenLM would fail on
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.probing_multiplier.is_nan() || self.probing_multiplier <= 1. {
+            return Err(Error::InvalidConfig(format!(
+                "probing_multiplier must be larger than 1, got {}",
+                self.probing_multiplier
+            )));
+        }
+        for (name, path) in [
+            ("temporary_directory", &self.temporary_directory),
+            ("write_binary", &self.write_binary),
+        ] {
+            if let Some(path) = path {
+                if path.to_str().map_or(true, str::is_empty) {
+                    return Err(Error::InvalidConfig(format!(
+                        "{} must be non-empty UTF-8, got {:?}",
+                        name, path
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reserves `required` bytes of locked memory if the model is locked
+    ///
+    /// Rejects the model if the bytes locked by the loaded models and `required` exceed the
+    /// `RLIMIT_MEMLOCK` of the process. KenLM throws if `mlock` fails, the limit is checked
+    /// upfront for a more helpful error. The reservation is released when it is dropped.
+    pub(crate) fn validate_lock_limit(&self, required: u64) -> Result<Option<LockedMemory>, Error> {
+        if !self.lock_memory {
+            return Ok(None);
+        }
+        let locked = LockedMemory::new(required);
+        match memlock_limit() {
+            Some(limit) if limit < locked.total => Err(Error::MemoryLockLimit {
+                required: locked.total,
+                limit,
+            }),
+            _ => Ok(Some(locked)),
+        }
+    }
+}
+
+/// Bytes locked by the loaded models of the process, see [ConfigBuilder::lock_memory]
+static LOCKED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Locked memory of a model, released when the model is dropped
+pub(crate) struct LockedMemory {
+    bytes: u64,
+    /// Bytes locked by all models including this one when it was reserved
+    total: u64,
+}
+
+impl LockedMemory {
+    fn new(bytes: u64) -> Self {
+        let total = LOCKED_BYTES
+            .fetch_add(bytes, Ordering::Relaxed)
+            .saturating_add(bytes);
+        LockedMemory { bytes, total }
+    }
+}
+
+impl Drop for LockedMemory {
+    fn drop(&mut self) {
+        LOCKED_BYTES.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+/// The soft `RLIMIT_MEMLOCK` of the process in bytes, `None` if it is unlimited or unknown
+// Solaris and illumos have no RLIMIT_MEMLOCK, rlim_t is 32 bits wide on some targets
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+#[allow(clippy::unnecessary_cast)]
+fn memlock_limit() -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit only writes the limit of the resource to `limit`
+    if unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) } != 0
+        || limit.rlim_cur == libc::RLIM_INFINITY
+    {
+        return None;
+    }
+    Some(limit.rlim_cur as u64)
+}
+
+#[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+fn memlock_limit() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConfigBuilder, WarningAction};
+    use crate::{Error, LoadMethod, Model, ModelType};
+
+    #[test]
+    fn config_applies_to_arpa_loading() {
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .positive_log_probability(WarningAction::Silent);
+        let model = Model::builder("test_data/arpa/lm_small.arpa")
+            .config(config)
+            .build()
+            .expect("should load");
+        let expected = Model::new("test_data/arpa/lm_small.arpa", false).expect("should load");
+        assert_eq!(
+            model.score_sentence(&["i", "have"], true, true),
+            expected.score_sentence(&["i", "have"], true, true)
+        );
+    }
+
+    #[test]
+    fn missing_sentence_markers_follow_policy() {
+        let result = Model::builder("test_data/arpa/lm_no_markers.arpa").build();
+        assert!(matches!(result, Err(Error::SentenceMarkerMissing(marker)) if marker == "<s>"));
+
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .sentence_marker_missing(WarningAction::Silent);
+        let model = Model::builder("test_data/arpa/lm_no_markers.arpa")
+            .config(config)
+            .build()
+            .expect("should load");
+        assert!(model.get_word_idx_opt("<s>").is_some());
+    }
+
+    #[test]
+    fn missing_unknown_follows_policy() {
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .unknown_missing(WarningAction::ThrowUp);
+        let result = Model::builder("test_data/arpa/lm_no_unk.arpa")
+            .config(config)
+            .build();
+        assert!(matches!(result, Err(Error::UnknownMissing)));
+
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .unknown_missing(WarningAction::Silent)
+            .unknown_missing_log_prob(-42.);
+        let model = Model::builder("test_data/arpa/lm_no_unk.arpa")
+            .config(config)
+            .build()
+            .expect("should load");
+        approx::assert_abs_diff_eq!(model.unk_score(), -42., epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn positive_log_probability_follows_policy() {
+        let result = Model::builder("test_data/arpa/lm_positive_prob.arpa").build();
+        // only unigrams are checked in Rust, KenLM rejects the bigram
+        assert!(matches!(result, Err(Error::KenLMError(_))));
+        let result = Model::builder("test_data/arpa/lm_positive_unigram.arpa").build();
+        assert!(matches!(result, Err(Error::PositiveLogProbability(ngram)) if ngram == "a"));
+
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .positive_log_probability(WarningAction::Complain);
+        assert!(Model::builder("test_data/arpa/lm_positive_prob.arpa")
+            .config(config)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn memory_options_keep_scores() {
+        let expected = Model::new("test_data/test.bin", false).expect("should load");
+        for load_method in [LoadMethod::Lazy, LoadMethod::Read] {
+            let config = ConfigBuilder::new().populate(true).huge_pages(false);
+            assert!(matches!(
+                config.effective_load_method(load_method),
+                LoadMethod::PopulateOrLazy | LoadMethod::Read
+            ));
+            let model = Model::builder("test_data/test.bin")
+                .with_load_method(load_method)
+                .config(config)
+                .build()
+                .expect("should load");
+            assert_eq!(
+                model.score_sentence(&["i", "have"], true, true),
+                expected.score_sentence(&["i", "have"], true, true)
+            );
+        }
+    }
+
+    #[test]
+    fn lock_limit_is_only_checked_when_locking() {
+        assert!(ConfigBuilder::new()
+            .validate_lock_limit(u64::MAX)
+            .unwrap()
+            .is_none());
+        if super::memlock_limit().is_some() {
+            assert!(matches!(
+                ConfigBuilder::new()
+                    .lock_memory(true)
+                    .validate_lock_limit(u64::MAX),
+                Err(Error::MemoryLockLimit { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn lock_limit_counts_locked_models() {
+        let config = ConfigBuilder::new().lock_memory(true);
+        if let Some(limit) = super::memlock_limit() {
+            let locked = config.validate_lock_limit(limit / 2 + 1).unwrap();
+            assert!(matches!(
+                config.validate_lock_limit(limit / 2 + 1),
+                Err(Error::MemoryLockLimit { .. })
+            ));
+            drop(locked);
+            assert!(config.validate_lock_limit(limit / 2 + 1).is_ok());
+        }
+    }
+
+    #[test]
+    fn building_options_keep_scores() {
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .arpa_model_type(ModelType::Trie)
+            .building_memory(1 << 20)
+            .temporary_directory(std::env::temp_dir());
+        assert!(config.temporary_directory_prefix().is_some());
+        let model = Model::builder("test_data/arpa/lm_small.arpa")
+            .config(config.clone())
+            .build()
+            .expect("should load");
+        assert_eq!(model.model_type(), ModelType::Trie);
+        let expected = Model::new("test_data/arpa/lm_small.arpa", false).expect("should load");
+        assert_eq!(
+            model.score_sentence(&["i", "have"], true, true),
+            expected.score_sentence(&["i", "have"], true, true)
+        );
+
+        // the sorted n-grams can't be written into a missing directory
+        let missing = std::env::temp_dir().join(format!("kenlm-rs-missing-{}", std::process::id()));
+        let result = Model::builder("test_data/arpa/lm_small.arpa")
+            .config(config.temporary_directory(missing.join("prefix")))
+            .build();
+        assert!(matches!(result, Err(Error::KenLMError(_))));
+    }
+
+    #[test]
+    fn writes_binary_while_loading_arpa() {
+        let binary =
+            std::env::temp_dir().join(format!("kenlm-rs-write-binary-{}.bin", std::process::id()));
+        let binary_name = binary.to_str().unwrap();
+        let config = ConfigBuilder::new()
+            .messages(false)
+            .write_binary(&binary)
+            .include_vocab(false);
+        let model = Model::builder("test_data/arpa/lm_small.arpa")
+            .config(config)
+            .build()
+            .expect("should load");
+
+        let written = Model::new(binary_name, false).expect("should load");
+        assert_eq!(written.model_type(), ModelType::Probing);
+        assert_eq!(
+            written.score_sentence(&["i", "have"], true, true),
+            model.score_sentence(&["i", "have"], true, true)
+        );
+        assert!(matches!(
+            Model::new(binary_name, true),
+            Err(Error::ModelHasNoVocab)
+        ));
+        std::fs::remove_file(&binary).unwrap();
+    }
+
+    #[test]
+    fn rejects_empty_temporary_directory() {
+        let config = ConfigBuilder::new().temporary_directory("");
+        assert!(matches!(
+            Model::builder("test_data/test.bin").config(config).build(),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn estimate_memory_grows_with_probing_multiplier() {
+        let counts = [100, 300, 500];
+        let small = ConfigBuilder::new()
+            .probing_multiplier(1.2)
+            .estimate_memory(&counts, ModelType::Probing)
+            .unwrap();
+        let large = ConfigBuilder::new()
+            .probing_multiplier(2.)
+            .estimate_memory(&counts, ModelType::Probing)
+            .unwrap();
+        assert!(small < large);
+        assert!(matches!(
+            ConfigBuilder::new().estimate_memory(&[100], ModelType::Trie),
+            Err(Error::UnsupportedOrder(1))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_probing_multiplier() {
+        for multiplier in [1., 0.5, f32::NAN] {
+            let config = ConfigBuilder::new().probing_multiplier(multiplier);
+            assert!(matches!(
+                Model::builder("test_data/test.bin").config(config).build(),
+                Err(Error::InvalidConfig(_))
+            ));
+        }
+    }
+}
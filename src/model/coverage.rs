@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use super::{Model, ScoreOptions};
+
+/// N-gram coverage of a corpus
+///
+/// Tracks per order how many n-grams were found in the model and how many backed off to a lower
+/// order. A token is attributed to the order of the longest n-gram its context allows, i.e. in
+/// a trigram model the second word of a sentence without `<s>` is attributed to bigrams. If
+/// the matched n-gram is shorter, the n-gram is counted as backed off and remembered as missing.
+/// Out-of-vocabulary words always count as backed off, also as unigrams.
+/// ```
+/// use kenlm_rs::{CoverageStats, Model, ScoreOptions};
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let mut coverage = CoverageStats::new(&model);
+/// for line in ["i have a good deal", "what a lovely sentence"] {
+///     coverage.add_sentence(&model, line.split_ascii_whitespace(), ScoreOptions::new(true, true));
+/// }
+/// for order in 1..=model.get_order() as usize {
+///     eprintln!("{order}: {} found, {} backed off", coverage.found(order), coverage.backed_off(order));
+/// }
+/// eprintln!("{:?}", coverage.most_frequent_missing(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CoverageStats {
+    found: Vec<usize>,
+    backed_off: Vec<usize>,
+    missing: HashMap<String, usize>,
+}
+
+impl CoverageStats {
+    /// Constructs empty statistics for the order of `model`
+    pub fn new(model: &Model) -> Self {
+        let order = model.get_order() as usize;
+        Self {
+            found: vec![0; order],
+            backed_off: vec![0; order],
+            missing: HashMap::new(),
+        }
+    }
+
+    /// Scores `sentence` and adds the coverage of its n-grams
+    pub fn add_sentence(
+        &mut self,
+        model: &Model,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+    ) {
+        let sentence = sentence.into_iter().collect::<Vec<_>>();
+        let order = self.found.len();
+        let mut words: Vec<&str> = Vec::with_capacity(sentence.len() + 2);
+        if options.bos {
+            words.push("<s>");
+        }
+        let offset = words.len();
+        words.extend(sentence.iter().map(|word| word.as_ref()));
+        if options.eos {
+            words.push("</s>");
+        }
+
+        let scores = model.score_sentence_detailed(&sentence, options);
+        for (position, score) in (offset..).zip(scores) {
+            let expected = (position + 1).min(order);
+            // KenLM matches out-of-vocabulary words as the `<unk>` unigram
+            if !score.oov && score.ngram_length as usize >= expected {
+                self.found[expected - 1] += 1;
+            } else {
+                self.backed_off[expected - 1] += 1;
+                let ngram = words[position + 1 - expected..=position].join(" ");
+                *self.missing.entry(ngram).or_default() += 1;
+            }
+        }
+    }
+
+    /// Number of n-grams of `order` that were found in the model
+    pub fn found(&self, order: usize) -> usize {
+        order
+            .checked_sub(1)
+            .and_then(|idx| self.found.get(idx))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Number of n-grams of `order` that backed off to a lower order
+    pub fn backed_off(&self, order: usize) -> usize {
+        order
+            .checked_sub(1)
+            .and_then(|idx| self.backed_off.get(idx))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the `n` most frequent n-grams that were not found in the model
+    ///
+    /// Ties are broken lexicographically to keep the output deterministic.
+    pub fn most_frequent_missing(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut missing = self
+            .missing
+            .iter()
+            .map(|(ngram, count)| (ngram.as_str(), *count))
+            .collect::<Vec<_>>();
+        missing.sort_by(|(ngram1, count1), (ngram2, count2)| {
+            count2.cmp(count1).then_with(|| ngram1.cmp(ngram2))
+        });
+        missing.truncate(n);
+        missing
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CoverageStats;
+    use crate::{Model, ScoreOptions};
+
+    #[test]
+    fn counts_found_and_backed_off() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut coverage = CoverageStats::new(&model);
+        coverage.add_sentence(
+            &model,
+            &["i", "have", "toast"],
+            ScoreOptions::new(true, true),
+        );
+        coverage.add_sentence(
+            &model,
+            &["i", "have", "toast"],
+            ScoreOptions::new(true, true),
+        );
+        // <s> i: bigram found, <s> i have: trigram found, toast and </s> back off to unigrams
+        assert_eq!(coverage.found(2), 2);
+        assert_eq!(coverage.found(3), 2);
+        assert_eq!(coverage.backed_off(3), 4);
+        assert_eq!(coverage.backed_off(0), 0);
+        assert_eq!(coverage.most_frequent_missing(1), &[("have toast </s>", 2)]);
+
+        // without <s>, the first word is a unigram
+        let mut coverage = CoverageStats::new(&model);
+        coverage.add_sentence(&model, ["toast", "i"], ScoreOptions::new(false, false));
+        assert_eq!(coverage.found(1), 0);
+        assert_eq!(coverage.backed_off(1), 1);
+        assert_eq!(coverage.most_frequent_missing(1), &[("toast", 1)]);
+    }
+}
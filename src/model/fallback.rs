@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+
+use super::TokenClasses;
+
+/// A step of an [OovFallback] chain
+///
+/// Each rule rewrites a token into a candidate which is looked up in the vocabulary. A rule is
+/// skipped if it does not change the token, the unchanged token is always looked up before the
+/// rules are tried.
+#[derive(Debug, Clone)]
+pub enum FallbackRule {
+    /// The token as is, it is looked up first whether or not the chain contains this rule
+    Exact,
+    /// The lowercased token
+    Lowercase,
+    /// The token with leading and trailing ASCII punctuation removed
+    StripPunctuation,
+    /// The placeholder of the token's class, see [TokenClasses]
+    Placeholder(TokenClasses),
+    /// The token normalized by a user-supplied function, e.g. case folding or transliteration
+    Normalize(fn(&str) -> String),
+}
+
+impl FallbackRule {
+    pub(crate) fn apply<'a>(&'a self, token: &'a str) -> Option<Cow<'a, str>> {
+        match self {
+            FallbackRule::Exact => Some(Cow::Borrowed(token)),
+            FallbackRule::Lowercase => {
+                let lowercased = token.to_lowercase();
+                (lowercased != token).then_some(Cow::Owned(lowercased))
+            }
+            FallbackRule::StripPunctuation => {
+                let stripped = token.trim_matches(|c: char| c.is_ascii_punctuation());
+                (stripped != token && !stripped.is_empty()).then_some(Cow::Borrowed(stripped))
+            }
+            FallbackRule::Placeholder(classes) => {
+                let placeholder = classes.classify(token);
+                (placeholder != token).then_some(Cow::Borrowed(placeholder))
+            }
+            FallbackRule::Normalize(normalize) => {
+                let normalized = normalize(token);
+                (normalized != token).then_some(Cow::Owned(normalized))
+            }
+        }
+    }
+
+    pub(crate) fn resolved_by(&self) -> ResolvedBy {
+        match self {
+            FallbackRule::Exact => ResolvedBy::Exact,
+            FallbackRule::Lowercase => ResolvedBy::Lowercase,
+            FallbackRule::StripPunctuation => ResolvedBy::StripPunctuation,
+            FallbackRule::Placeholder(_) => ResolvedBy::Placeholder,
+            FallbackRule::Normalize(_) => ResolvedBy::Normalized,
+        }
+    }
+}
+
+/// The rule which mapped a token to its index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolvedBy {
+    Exact,
+    Lowercase,
+    StripPunctuation,
+    Placeholder,
+    Normalized,
+    /// No rule matched, the token was mapped to `<unk>`
+    Unknown,
+}
+
+/// Ordered chain of rules tried for tokens that are not in the vocabulary
+///
+/// Tokens found in the vocabulary as they are resolve to themselves. For other tokens the rules
+/// are tried in order, the first candidate found in the vocabulary determines the index of a
+/// token. If no rule matches, the token is mapped to `<unk>`. The default chain only contains
+/// [FallbackRule::Exact].
+/// ```
+/// use kenlm_rs::{FallbackRule, Model, OovFallback, TokenClasses};
+/// let mut model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// model.set_oov_fallback(OovFallback::new(vec![
+///     FallbackRule::Exact,
+///     FallbackRule::Lowercase,
+///     FallbackRule::StripPunctuation,
+///     FallbackRule::Placeholder(TokenClasses::with_defaults()),
+/// ]));
+/// assert!(model.get_word_idx_opt("Some").is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OovFallback {
+    rules: Vec<FallbackRule>,
+}
+
+impl Default for OovFallback {
+    fn default() -> Self {
+        Self {
+            rules: vec![FallbackRule::Exact],
+        }
+    }
+}
+
+impl OovFallback {
+    pub fn new(rules: Vec<FallbackRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Chain for models trained on lowercased text, tries the exact token, then its lowercase form
+    /// ```
+    /// use kenlm_rs::{Model, OovFallback};
+    /// let mut model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// model.set_oov_fallback(OovFallback::case_folding());
+    /// assert_eq!(model.get_word_idx("Have"), model.get_word_idx("have"));
+    /// ```
+    pub fn case_folding() -> Self {
+        Self::new(vec![FallbackRule::Exact, FallbackRule::Lowercase])
+    }
+
+    pub fn rules(&self) -> &[FallbackRule] {
+        &self.rules
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FallbackRule, ResolvedBy};
+    use crate::TokenClasses;
+
+    #[test]
+    fn rules_rewrite_tokens() {
+        assert_eq!(FallbackRule::Exact.apply("Word").as_deref(), Some("Word"));
+        assert_eq!(
+            FallbackRule::Lowercase.apply("Word").as_deref(),
+            Some("word")
+        );
+        assert_eq!(FallbackRule::Lowercase.apply("word"), None);
+        assert_eq!(
+            FallbackRule::StripPunctuation.apply("\"word!\"").as_deref(),
+            Some("word")
+        );
+        assert_eq!(FallbackRule::StripPunctuation.apply("word"), None);
+        assert_eq!(FallbackRule::StripPunctuation.apply("..."), None);
+        let placeholder = FallbackRule::Placeholder(TokenClasses::with_defaults());
+        assert_eq!(placeholder.apply("42").as_deref(), Some("<num>"));
+        assert_eq!(placeholder.apply("word"), None);
+        assert_eq!(placeholder.resolved_by(), ResolvedBy::Placeholder);
+        let normalize = FallbackRule::Normalize(|token| token.replace('ß', "ss"));
+        assert_eq!(normalize.apply("straße").as_deref(), Some("strasse"));
+        assert_eq!(normalize.apply("strasse"), None);
+        assert_eq!(normalize.resolved_by(), ResolvedBy::Normalized);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::headers::ModelType;
+use crate::{ConfigBuilder, Error};
+
+/// Approximate memory used by a loaded model, in bytes
+///
+/// Returned by [crate::Model::memory_footprint]. The sizes are computed by the `Size` functions
+/// of KenLM's vocabulary, quantization and search structures, which KenLM sizes its memory with
+/// when loading, see `EstimateSizes` in src/cxx/lm/sizes.cc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryFootprint {
+    /// Size of the vocabulary lookup structure
+    pub vocab: u64,
+    /// Size of the quantization tables, `0` for unquantized models
+    pub quantization: u64,
+    /// Size of the n-gram storage per order, starting with the unigrams
+    pub orders: Vec<u64>,
+}
+
+impl MemoryFootprint {
+    /// Total size of all structures
+    pub fn total(&self) -> u64 {
+        self.vocab + self.quantization + self.orders.iter().sum::<u64>()
+    }
+
+    /// Estimates the footprint of a model of `model_type` with `counts`
+    ///
+    /// KenLM stores the number of quantization bits and the bits chopped off array-compressed
+    /// pointers in the model itself, for those models the estimate assumes KenLM's defaults.
+    /// Fails with [Error::UnsupportedOrder] for models of order one.
+    pub(crate) fn estimate(
+        counts: &[u64],
+        model_type: ModelType,
+        probing_multiplier: f32,
+    ) -> Result<MemoryFootprint, Error> {
+        if counts.len() < 2 {
+            return Err(Error::UnsupportedOrder(counts.len()));
+        }
+        let mut config = crate::cxx::Config::default();
+        config.apply(&ConfigBuilder::new().probing_multiplier(probing_multiplier));
+        let (vocab, quantization, orders) = config.estimate_sizes(counts, model_type)?;
+        Ok(MemoryFootprint {
+            vocab,
+            quantization,
+            orders,
+        })
+    }
+}
+
+/// Memory used by a loaded model, in bytes
+///
+/// Returned by [crate::Model::memory_usage].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Size of the data structures KenLM mapped or allocated for the model
+    pub model: u64,
+    /// Size of the vocab copies held on the Rust side, `0` if none are stored
+    pub vocab: u64,
+}
+
+impl MemoryUsage {
+    /// Total size of the model and the vocab copies
+    pub fn total(&self) -> u64 {
+        self.model + self.vocab
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryFootprint;
+    use crate::headers::ModelType;
+    use crate::{ConfigBuilder, Error};
+
+    #[test]
+    fn matches_kenlm_sizes() {
+        // totals as reported by KenLM's `GenericModel::Size` with the default config
+        let expectations: &[(&[u64], [u64; 6])] = &[
+            (&[24, 24, 24], [1648, 1892, 974, 3835, 997, 3858]),
+            (
+                &[4415, 18349, 25612],
+                [1016184, 1143940, 455620, 277256, 438199, 259836],
+            ),
+            (
+                &[4415, 18349],
+                [445076, 462740, 206933, 155209, 206933, 155209],
+            ),
+            (
+                &[100000, 2000000, 5000000, 3000000],
+                [
+                    224600016, 267000020, 109900096, 60155210, 96337634, 46592748,
+                ],
+            ),
+        ];
+        for (counts, sizes) in expectations {
+            for (model_type, size) in sizes.iter().enumerate() {
+                let model_type = ModelType::try_from(model_type as u32).unwrap();
+                let footprint = MemoryFootprint::estimate(counts, model_type, 1.5).unwrap();
+                assert_eq!(footprint.orders.len(), counts.len());
+                assert_eq!(footprint.total(), *size, "{counts:?} type {model_type}");
+                let estimate = ConfigBuilder::new().estimate_memory(counts, model_type);
+                assert_eq!(footprint.total(), estimate.unwrap());
+            }
+        }
+        assert!(matches!(
+            MemoryFootprint::estimate(&[24], ModelType::Probing, 1.5),
+            Err(Error::UnsupportedOrder(1))
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::{Model, ScoreOptions};
+use crate::Error;
+
+/// Aggregate statistics of a scoring job
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JobReport {
+    /// Number of sentences scored
+    pub sentences: u64,
+    /// Number of scored tokens, including `</s>` if [ScoreOptions::eos] is set
+    pub tokens: u64,
+    /// Number of out-of-vocabulary tokens
+    pub oov: u64,
+    /// Sum of the log10 probabilities of all tokens
+    pub log_prob: f64,
+    /// Sum of the log10 probabilities of the out-of-vocabulary tokens
+    pub oov_log_prob: f64,
+}
+
+impl JobReport {
+    /// Perplexity over all tokens
+    pub fn perplexity(&self) -> f64 {
+        10f64.powf(-(self.log_prob / self.tokens as f64))
+    }
+
+    /// Perplexity over all tokens which are in the vocabulary
+    pub fn perplexity_excluding_oov(&self) -> f64 {
+        10f64.powf(-((self.log_prob - self.oov_log_prob) / (self.tokens - self.oov) as f64))
+    }
+}
+
+/// Resumable job scoring a corpus with one whitespace tokenized sentence per line
+///
+/// Every `checkpoint_every` lines, the byte offset into the corpus and the aggregate statistics
+/// are written to the checkpoint file. If the checkpoint exists when the job is run, scoring
+/// resumes from the stored offset with the stored statistics, so a job that crashed produces the
+/// same [JobReport] as one that ran through. The checkpoint is kept after the job finished,
+/// running the job again returns the final report without scoring anything.
+pub struct ScoringJob {
+    corpus: PathBuf,
+    checkpoint: PathBuf,
+    checkpoint_every: usize,
+    options: ScoreOptions,
+}
+
+impl ScoringJob {
+    /// Constructs a job scoring `corpus` which checkpoints to `checkpoint`
+    pub fn new(corpus: impl Into<PathBuf>, checkpoint: impl Into<PathBuf>) -> Self {
+        ScoringJob {
+            corpus: corpus.into(),
+            checkpoint: checkpoint.into(),
+            checkpoint_every: 100_000,
+            options: ScoreOptions::new(true, true),
+        }
+    }
+
+    /// Sets after how many lines a checkpoint is written, defaults to `100_000`
+    pub fn checkpoint_every(mut self, lines: usize) -> Self {
+        self.checkpoint_every = lines.max(1);
+        self
+    }
+
+    /// Sets the options sentences are scored with, defaults to `bos` and `eos`
+    pub fn options(mut self, options: ScoreOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Runs the job to the end of the corpus, resuming from the checkpoint if there is one
+    pub fn run(&self, model: &Model) -> Result<JobReport, Error> {
+        self.run_lines(model, None)
+    }
+
+    fn run_lines(&self, model: &Model, max_lines: Option<usize>) -> Result<JobReport, Error> {
+        let (mut offset, mut report) = match read_checkpoint(&self.checkpoint)? {
+            Some(checkpoint) => checkpoint,
+            None => (0, JobReport::default()),
+        };
+
+        let mut corpus = File::open(&self.corpus)
+            .map_err(|_| Error::FileNotFound(self.corpus.display().to_string()))?;
+        corpus.seek(SeekFrom::Start(offset))?;
+        let mut corpus = BufReader::new(corpus);
+
+        let mut line = String::new();
+        let mut lines = 0;
+        while max_lines.map_or(true, |max_lines| lines < max_lines) {
+            line.clear();
+            let n_bytes = corpus.read_line(&mut line)?;
+            if n_bytes == 0 {
+                break;
+            }
+            offset += n_bytes as u64;
+            lines += 1;
+
+            let sentence = line.split_ascii_whitespace().collect::<Vec<_>>();
+            for score in model.score_sentence_detailed(&sentence, self.options) {
+                report.tokens += 1;
+                report.log_prob += f64::from(score.log_prob);
+                if score.oov {
+                    report.oov += 1;
+                    report.oov_log_prob += f64::from(score.log_prob);
+                }
+            }
+            report.sentences += 1;
+
+            if lines % self.checkpoint_every == 0 {
+                write_checkpoint(&self.checkpoint, offset, &report)?;
+            }
+        }
+        write_checkpoint(&self.checkpoint, offset, &report)?;
+        Ok(report)
+    }
+}
+
+fn write_checkpoint(path: &Path, offset: u64, report: &JobReport) -> Result<(), Error> {
+    // Write to a temporary file first, a crash while writing must not destroy the last checkpoint
+    let partial = path.with_extension("part");
+    let mut file = File::create(&partial)?;
+    writeln!(file, "offset={}", offset)?;
+    writeln!(file, "sentences={}", report.sentences)?;
+    writeln!(file, "tokens={}", report.tokens)?;
+    writeln!(file, "oov={}", report.oov)?;
+    writeln!(file, "log_prob={}", report.log_prob)?;
+    writeln!(file, "oov_log_prob={}", report.oov_log_prob)?;
+    file.sync_all()?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+fn read_checkpoint(path: &Path) -> Result<Option<(u64, JobReport)>, Error> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let invalid = || Error::InvalidCheckpoint(path.display().to_string());
+
+    let mut offset = None;
+    let mut report = JobReport::default();
+    for line in contents.lines() {
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        match key {
+            "offset" => offset = Some(value.parse().map_err(|_| invalid())?),
+            "sentences" => report.sentences = value.parse().map_err(|_| invalid())?,
+            "tokens" => report.tokens = value.parse().map_err(|_| invalid())?,
+            "oov" => report.oov = value.parse().map_err(|_| invalid())?,
+            "log_prob" => report.log_prob = value.parse().map_err(|_| invalid())?,
+            "oov_log_prob" => report.oov_log_prob = value.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(Some((offset.ok_or_else(invalid)?, report)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ScoringJob;
+    use crate::model::test::{TEST_SENTENCE, TEST_WITH_OOV};
+    use crate::Model;
+
+    #[test]
+    fn resumed_job_matches_uninterrupted_job() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let dir = std::env::temp_dir().join(format!("kenlm-rs-job-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let corpus = dir.join("corpus.txt");
+        let sentences = [TEST_SENTENCE, TEST_WITH_OOV, TEST_SENTENCE, TEST_WITH_OOV];
+        let corpus_text = sentences.map(|s| s.join(" ")).join("\n");
+        std::fs::write(&corpus, corpus_text).unwrap();
+
+        let uninterrupted = ScoringJob::new(&corpus, dir.join("full.checkpoint"))
+            .run(&model)
+            .unwrap();
+        assert_eq!(uninterrupted.sentences, 4);
+        assert_eq!(uninterrupted.oov, 2);
+
+        let job = ScoringJob::new(&corpus, dir.join("resumed.checkpoint")).checkpoint_every(1);
+        let partial = job.run_lines(&model, Some(3)).unwrap();
+        assert_eq!(partial.sentences, 3);
+        assert_eq!(job.run(&model).unwrap(), uninterrupted);
+        // finished jobs return their final report
+        assert_eq!(job.run(&model).unwrap(), uninterrupted);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use super::{Model, ModelBuilder};
+use crate::Error;
+
+/// Registry loading, sharing and evicting multiple models
+///
+/// Models are registered under a name with the [ModelBuilder] to load them with and are loaded
+/// on first use. Names that were not registered are loaded as paths with the default options.
+/// Loaded models are shared as `Arc<Model>`, so they can be used from several threads, e.g.
+/// with a [crate::Scorer] per request.
+///
+/// The least recently used models are evicted when the number of loaded models or their
+/// [Model::memory_usage] exceeds the limits of the manager. Room is made before a model is
+/// loaded, using the size estimated from its headers. Evicted models stay alive until the last
+/// handle to them is dropped, they are loaded again on their next use.
+/// ```
+/// use kenlm_rs::{Model, ModelManager, Scorer};
+///
+/// let manager = ModelManager::new().with_max_models(1);
+/// manager.register("carol", Model::builder("test_data/carol.bin"));
+///
+/// let model = manager.get("carol").unwrap();
+/// let mut scorer = Scorer::new(&model, true);
+/// scorer.push_word("the");
+///
+/// let test = manager.get("test_data/test.bin").unwrap();
+/// assert_eq!(manager.loaded(), vec!["test_data/test.bin".to_string()]);
+/// ```
+#[derive(Default)]
+pub struct ModelManager {
+    max_models: Option<usize>,
+    memory_budget: Option<u64>,
+    inner: Mutex<Inner>,
+    /// Notified when a model finished loading
+    load_done: Condvar,
+}
+
+#[derive(Default)]
+struct Inner {
+    builders: HashMap<String, ModelBuilder>,
+    loaded: HashMap<String, Loaded>,
+    /// Names being loaded and the bytes reserved for them
+    loading: HashMap<String, u64>,
+    /// Incremented by `register`, models loaded with an older builder are not stored
+    generations: HashMap<String, u64>,
+    clock: u64,
+}
+
+struct Loaded {
+    model: Arc<Model>,
+    bytes: u64,
+    last_used: u64,
+}
+
+impl ModelManager {
+    /// Constructs a manager without limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of loaded models
+    pub fn with_max_models(mut self, max_models: usize) -> Self {
+        self.max_models = Some(max_models);
+        self
+    }
+
+    /// Sets the memory budget in bytes of all loaded models
+    ///
+    /// Models which on their own exceed the budget fail to load with
+    /// [Error::MemoryBudgetExceeded], see [ModelBuilder::max_memory_bytes].
+    pub fn with_memory_budget(mut self, bytes: u64) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Registers `builder` under `name`, the model is loaded on first use
+    ///
+    /// A model loaded under `name` before is evicted. A model which is being loaded under `name`
+    /// is still returned to the callers waiting for it, but it is not kept.
+    pub fn register(&self, name: &str, builder: ModelBuilder) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.loaded.remove(name);
+        inner.builders.insert(name.to_string(), builder);
+        *inner.generations.entry(name.to_string()).or_default() += 1;
+    }
+
+    /// Returns the model registered as `name`, loading it if necessary
+    ///
+    /// Unregistered names are loaded as paths. Models are loaded without holding the lock of
+    /// the manager, so other models can be used in the meantime. A model is loaded only once,
+    /// concurrent calls for the same name wait for it. Before loading, the size of the model is
+    /// estimated from its headers and reserved, least recently used models are evicted to make
+    /// room for it. Loading fails if the model does not fit into the memory budget on its own.
+    pub fn get(&self, name: &str) -> Result<Arc<Model>, Error> {
+        let (builder, generation) = {
+            let mut inner = self.inner.lock().unwrap();
+            while inner.loading.contains_key(name) {
+                inner = self.load_done.wait(inner).unwrap();
+            }
+            if let Some(model) = inner.touch(name) {
+                return Ok(model);
+            }
+            inner.loading.insert(name.to_string(), 0);
+            let builder = inner
+                .builders
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Model::builder(name));
+            (builder, inner.generation(name))
+        };
+        let _slot = LoadingSlot {
+            manager: self,
+            name,
+        };
+
+        // models which can't be estimated, e.g. remote ones, are checked by the builder
+        let reserved = match self.memory_budget {
+            Some(_) => builder.estimate_load_memory().unwrap_or(0),
+            None => 0,
+        };
+        let builder = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.loading.insert(name.to_string(), reserved);
+            inner.evict(name, self.max_models, self.memory_budget);
+            match self.memory_budget {
+                Some(budget) => builder.limit_memory(budget),
+                None => builder,
+            }
+        };
+        let model = Arc::new(builder.build()?);
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.loading.remove(name);
+        // the builder was replaced while loading, the model is stale
+        if inner.generation(name) != generation {
+            return Ok(model);
+        }
+        inner.clock += 1;
+        let loaded = Loaded {
+            model: model.clone(),
+            bytes: model.memory_usage().total(),
+            last_used: inner.clock,
+        };
+        inner.loaded.insert(name.to_string(), loaded);
+        inner.evict(name, self.max_models, self.memory_budget);
+        Ok(model)
+    }
+
+    /// Evicts the model loaded as `name`, returns whether it was loaded
+    pub fn evict(&self, name: &str) -> bool {
+        self.inner.lock().unwrap().loaded.remove(name).is_some()
+    }
+
+    /// Names of the loaded models, from least to most recently used
+    pub fn loaded(&self) -> Vec<String> {
+        let inner = self.inner.lock().unwrap();
+        let mut loaded = inner.loaded.iter().collect::<Vec<_>>();
+        loaded.sort_by_key(|(_, loaded)| loaded.last_used);
+        loaded.into_iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Memory used by the loaded models in bytes, see [Model::memory_usage]
+    pub fn memory_usage(&self) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        inner.loaded.values().map(|loaded| loaded.bytes).sum()
+    }
+}
+
+/// Releases the loading slot of a name and wakes up the callers waiting for it
+///
+/// Dropped after loading, also if it failed or panicked, so the waiting callers don't hang.
+struct LoadingSlot<'a> {
+    manager: &'a ModelManager,
+    name: &'a str,
+}
+
+impl Drop for LoadingSlot<'_> {
+    fn drop(&mut self) {
+        let mut inner = self
+            .manager
+            .inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        inner.loading.remove(self.name);
+        self.manager.load_done.notify_all();
+    }
+}
+
+impl Inner {
+    /// Number of times a builder was registered as `name`
+    fn generation(&self, name: &str) -> u64 {
+        self.generations.get(name).copied().unwrap_or(0)
+    }
+
+    /// Returns the model loaded as `name` and marks it as used
+    fn touch(&mut self, name: &str) -> Option<Arc<Model>> {
+        self.clock += 1;
+        let clock = self.clock;
+        let loaded = self.loaded.get_mut(name)?;
+        loaded.last_used = clock;
+        Some(loaded.model.clone())
+    }
+
+    /// Evicts the least recently used models other than `keep` until the limits are met
+    ///
+    /// Models which are being loaded count towards the limits with their reserved size.
+    fn evict(&mut self, keep: &str, max_models: Option<usize>, memory_budget: Option<u64>) {
+        loop {
+            let bytes = self.loaded.values().map(|loaded| loaded.bytes).sum::<u64>()
+                + self.loading.values().sum::<u64>();
+            let models = self.loaded.len() + self.loading.len();
+            let too_many = max_models.map_or(false, |max| models > max);
+            let too_large = memory_budget.map_or(false, |budget| bytes > budget);
+            if !too_many && !too_large {
+                return;
+            }
+            let least_recent = self
+                .loaded
+                .iter()
+                .filter(|(name, _)| name.as_str() != keep)
+                .min_by_key(|(_, loaded)| loaded.last_used)
+                .map(|(name, _)| name.clone());
+            match least_recent {
+                Some(name) => self.loaded.remove(&name),
+                None => return,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::ModelManager;
+    use crate::{Error, Model};
+
+    #[test]
+    fn shares_loaded_models() {
+        let manager = ModelManager::new();
+        manager.register("test", Model::builder("test_data/test.bin"));
+        let first = manager.get("test").expect("should load");
+        let second = manager.get("test").expect("should load");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(manager.evict("test"));
+        assert!(!Arc::ptr_eq(
+            &first,
+            &manager.get("test").expect("should load")
+        ));
+        assert!(matches!(
+            manager.get("missing"),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn loads_concurrently_requested_models_once() {
+        let manager = ModelManager::new();
+        let models = std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| scope.spawn(|| manager.get("test_data/test.bin").expect("should load")))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert!(models.iter().all(|model| Arc::ptr_eq(model, &models[0])));
+        assert_eq!(manager.loaded(), vec!["test_data/test.bin"]);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let manager = ModelManager::new().with_max_models(2);
+        manager.get("test_data/test.bin").expect("should load");
+        manager.get("test_data/carol.bin").expect("should load");
+        manager.get("test_data/test.bin").expect("should load");
+        manager
+            .get("test_data/arpa/lm_small.arpa")
+            .expect("should load");
+        assert_eq!(
+            manager.loaded(),
+            vec!["test_data/test.bin", "test_data/arpa/lm_small.arpa"]
+        );
+    }
+
+    #[test]
+    fn evicts_to_memory_budget() {
+        let carol = Model::new("test_data/carol.bin", false).expect("should load");
+        let budget = carol.memory_usage().total();
+        let manager = ModelManager::new().with_memory_budget(budget);
+        manager.get("test_data/test.bin").expect("should load");
+        manager.get("test_data/carol.bin").expect("should load");
+        assert_eq!(manager.loaded(), vec!["test_data/carol.bin"]);
+        assert_eq!(manager.memory_usage(), budget);
+
+        let manager = ModelManager::new().with_memory_budget(budget - 1);
+        assert!(matches!(
+            manager.get("test_data/carol.bin"),
+            Err(Error::MemoryBudgetExceeded { .. })
+        ));
+        assert!(manager.loaded().is_empty());
+    }
+}
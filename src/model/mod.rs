@@ -1,14 +1,74 @@
+#[cfg(feature = "tokio")]
+mod asynchronous;
+mod bidirectional;
+mod buffer;
 mod builder;
+#[cfg(any(feature = "remote", feature = "zstd"))]
+mod cache;
+mod chart;
+#[cfg(feature = "checksum")]
+mod checksum;
+mod classes;
+mod completion;
+#[cfg(feature = "zstd")]
+mod compressed;
+mod config;
+mod coverage;
+mod fallback;
+mod footprint;
+mod job;
+mod manager;
+#[cfg(feature = "unicode")]
+mod normalize;
+mod ocr;
+mod options;
+mod query;
+mod reload;
+#[cfg(feature = "remote")]
+mod remote;
+mod score;
+mod scorer;
+mod session;
+mod special;
+mod suggest;
+mod trace;
 
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::ops::Deref;
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::sync::{Mutex, PoisonError};
 
-use crate::headers::{Counts, FixedParameters};
+use crate::headers::{Counts, FixedParameters, ModelType};
 use crate::{Error, LoadMethod};
 use autocxx::prelude::*;
 
 use crate::cxx::{bridge, CxxModel};
 
-use self::builder::ModelBuilder;
+pub use self::bidirectional::BidirectionalScorer;
+pub use self::builder::ModelBuilder;
+pub use self::chart::{ChartState, RuleScore};
+pub use self::classes::TokenClasses;
+use self::completion::CompletionIndex;
+use self::config::LockedMemory;
+pub use self::config::{ArpaComplain, ConfigBuilder, WarningAction};
+pub use self::coverage::CoverageStats;
+pub use self::fallback::{FallbackRule, OovFallback, ResolvedBy};
+pub use self::footprint::{MemoryFootprint, MemoryUsage};
+pub use self::job::{JobReport, ScoringJob};
+pub use self::manager::ModelManager;
+#[cfg(feature = "unicode")]
+pub use self::normalize::NormalizationForm;
+pub use self::ocr::{OcrAlternative, OcrCorrection, OcrCorrector};
+pub use self::options::ScoreOptions;
+pub use self::query::QueryPrinter;
+pub use self::reload::HotReloadingModel;
+pub use self::score::{FullScore, OovScore, Score, SentenceScore, TokenScore};
+pub use self::scorer::Scorer;
+pub use self::session::{Checkpoint, ScoreSession, SessionSnapshot};
+pub use self::special::SpecialTokens;
+use self::suggest::SuggestionIndex;
+pub use self::trace::{AppliedBackoff, ScoreTrace};
 
 /// KenLM NGram model
 ///
@@ -17,12 +77,80 @@ use self::builder::ModelBuilder;
 /// parameters, it also stores the vocab as a [Vec<String>].
 pub struct Model {
     inner: CxxModel,
+    /// Reservation of the memory KenLM locked for the model, see [ConfigBuilder::lock_memory]
+    locked_memory: Option<LockedMemory>,
+    builder: ModelBuilder,
     fixed_parameters: Option<FixedParameters>,
     count_header: Counts,
+    model_bytes: u64,
     vocab: Option<Vec<String>>,
+    vocab_indices: Option<Vec<WordIdx>>,
+    /// Position of each index in a sorted vocab, `None` if the vocab is in the order of indices
+    vocab_positions: Option<Vec<u32>>,
+    token_classes: Option<TokenClasses>,
+    #[cfg(feature = "unicode")]
+    normalization: Option<NormalizationForm>,
+    oov_fallback: OovFallback,
+    suggestion_index: Option<SuggestionIndex>,
+    completion_index: Option<CompletionIndex>,
+    lookup_cache: Option<HashMap<String, WordIdx>>,
+    /// Index KenLM returns for words missing from the vocabulary
+    not_found_idx: WordIdx,
+    bos_idx: WordIdx,
+    eos_idx: WordIdx,
+    unk_idx: WordIdx,
+    bos_state: State,
+    null_state: State,
 }
 
 impl Model {
+    fn from_parts(
+        inner: CxxModel,
+        builder: ModelBuilder,
+        fixed_parameters: Option<FixedParameters>,
+        count_header: Counts,
+        model_bytes: u64,
+        vocab: Option<(Vec<String>, Vec<WordIdx>)>,
+    ) -> Self {
+        let mut bos_state = State::new_for_model(&inner);
+        unsafe { inner.BeginSentenceWrite(bos_state.as_mut_ptr()) }
+        let mut null_state = State::new_for_model(&inner);
+        unsafe { inner.NullContextWrite(null_state.as_mut_ptr()) }
+        let cxx_vocab = inner.BaseVocabulary();
+        let bos_idx = WordIdx(cxx_vocab.BeginSentence());
+        let eos_idx = WordIdx(cxx_vocab.EndSentence());
+        let not_found_idx = WordIdx(cxx_vocab.NotFound());
+        let (vocab, vocab_indices) = match vocab {
+            Some((vocab, indices)) => (Some(vocab), Some(indices)),
+            None => (None, None),
+        };
+        let vocab_positions = vocab_indices.as_deref().and_then(vocab_positions);
+        Self {
+            inner,
+            locked_memory: None,
+            builder,
+            fixed_parameters,
+            count_header,
+            model_bytes,
+            vocab,
+            vocab_indices,
+            vocab_positions,
+            token_classes: None,
+            #[cfg(feature = "unicode")]
+            normalization: None,
+            oov_fallback: OovFallback::default(),
+            suggestion_index: None,
+            completion_index: None,
+            lookup_cache: None,
+            not_found_idx,
+            bos_idx,
+            eos_idx,
+            unk_idx: not_found_idx,
+            bos_state,
+            null_state,
+        }
+    }
+
     /// Initializes the model from `file_name`, stores vocab if `store_vocab` is true.
     ///
     /// Initializes the model from `file_name`, initialization happens in C++ land. Setting
@@ -41,6 +169,49 @@ impl Model {
             .build()
     }
 
+    /// Returns a [ModelBuilder] loading the model from `file_name`
+    ///
+    /// Use the builder to combine options which have no dedicated constructor, e.g. a
+    /// [LoadMethod] with a sorted vocab.
+    pub fn builder(file_name: &str) -> ModelBuilder {
+        ModelBuilder::new(file_name)
+    }
+
+    /// Loads the model again from the file it was loaded from, with the same options
+    ///
+    /// Returns a new model, `self` stays usable until it is replaced, e.g. by swapping it in an
+    /// `Arc`. Settings applied after loading, like [TokenClasses] or an [OovFallback] chain,
+    /// have to be applied again. Models loaded from a buffer can't be reloaded, this fails with
+    /// [Error::NotReloadable] for them. See [HotReloadingModel] for reloading models
+    /// whenever their file changes. A callback of [ModelBuilder::build_with_vocab_callback] is
+    /// not called again, use [Model::reload_with_vocab_callback] to stream the vocab once more.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", true).unwrap();
+    ///
+    /// let model = model.reload().unwrap();
+    /// assert!(model.get_vocab().is_some());
+    /// ```
+    pub fn reload(&self) -> Result<Model, Error> {
+        if !self.builder.is_reloadable() {
+            return Err(Error::NotReloadable);
+        }
+        self.builder.clone().build()
+    }
+
+    /// Loads the model again like [Model::reload], calling `on_word` with every word of the vocab
+    ///
+    /// See [ModelBuilder::build_with_vocab_callback].
+    pub fn reload_with_vocab_callback(
+        &self,
+        on_word: impl FnMut(WordIdx, &str) + '_,
+    ) -> Result<Model, Error> {
+        if !self.builder.is_reloadable() {
+            return Err(Error::NotReloadable);
+        }
+        self.builder.clone().build_with_vocab_callback(on_word)
+    }
+
     /// Initializes the model with load_method, may store vocab
     ///
     /// Initializes the model from `file_name`, initialization happens in C++ land. Setting
@@ -64,6 +235,69 @@ impl Model {
             .build()
     }
 
+    /// Initializes the model and stores its vocab sorted lexicographically
+    ///
+    /// The order of the vocab returned by `get_vocab` depends on the order in which KenLM
+    /// enumerates it, which differs between model types. With this constructor, the vocab is
+    /// sorted so that artifacts derived from it are reproducible across model formats. The index
+    /// of each word is available through `get_vocab_indices`.
+    ///
+    /// Like `new` with `store_vocab=true`, this returns an error if the model has no vocab.
+    pub fn new_with_sorted_vocab(file_name: &str) -> Result<Self, Error> {
+        ModelBuilder::new(file_name)
+            .store_vocab(true)
+            .sort_vocab(true)
+            .build()
+    }
+
+    /// Initializes the model after verifying it against its checksum sidecar
+    ///
+    /// Like `new`, but first computes the sha256 digest of `file_name` and compares it to the one
+    /// stored in `<file_name>.sha256`, as written by `write_checksum` or `sha256sum`. Fails with
+    /// [Error::ChecksumMissing] if there is no sidecar and with [Error::ChecksumMismatch] if the
+    /// digests differ, e.g. because the model directory was only partially synced.
+    ///
+    /// Compressed models are verified as they are on disk, before they are decompressed. URLs
+    /// have no sidecar, they are verified against their `#sha256=<hex digest>` fragment, which
+    /// is required. Requires the `checksum` feature.
+    #[cfg(feature = "checksum")]
+    pub fn new_with_checksum(file_name: &str, store_vocab: bool) -> Result<Self, Error> {
+        ModelBuilder::new(file_name)
+            .store_vocab(store_vocab)
+            .verify_checksum(true)
+            .build()
+    }
+
+    /// Initializes the model from an in-memory buffer, e.g. one embedded with `include_bytes!`
+    ///
+    /// `bytes` hold a binary or ARPA model like a file passed to `new`. KenLM only loads models
+    /// from files: on Linux the buffer is copied into an anonymous in-memory file created with
+    /// `memfd_create`, no filesystem is touched. Elsewhere it is spilled to a newly created file
+    /// with a random name in the temp directory, which has to be writable, and removed before
+    /// this returns. The model is read into memory with [LoadMethod::Read] and can't be
+    /// [reloaded](Model::reload).
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let bytes = std::fs::read("test_data/test.bin").unwrap();
+    ///
+    /// let model = Model::from_bytes(bytes, false).unwrap();
+    /// assert_eq!(model.get_order(), 3);
+    /// ```
+    pub fn from_bytes(bytes: impl AsRef<[u8]>, store_vocab: bool) -> Result<Self, Error> {
+        buffer::load(
+            bytes.as_ref(),
+            ModelBuilder::new("").store_vocab(store_vocab),
+        )
+    }
+
+    /// Writes the sha256 digest of `file_name` to `<file_name>.sha256`, returns the sidecar's path
+    ///
+    /// Requires the `checksum` feature.
+    #[cfg(feature = "checksum")]
+    pub fn write_checksum(file_name: &str) -> Result<std::path::PathBuf, Error> {
+        checksum::write_sidecar(file_name)
+    }
+
     /// Get some information about the currently loaded model, binary only
     ///
     /// This will be None if you did load an arpa format model.
@@ -82,28 +316,317 @@ impl Model {
         &self.count_header
     }
 
+    /// Returns the number of words in the vocabulary according to the unigram count
+    ///
+    /// The count is read from the count header of binaries or the `\data\` section of ARPA
+    /// files, so this works without `store_vocab` and for models without a stored vocab.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test_no_vocab.bin", false).unwrap();
+    ///
+    /// assert_eq!(model.vocab_size(), 24);
+    /// ```
+    pub fn vocab_size(&self) -> usize {
+        self.count_header.counts()[0].cardinality
+    }
+
+    /// Returns the log10 probability of `<unk>` as stored in the model
+    ///
+    /// This is the score out-of-vocabulary words receive without any context, with context the
+    /// backoffs of the context are added on top.
+    pub fn unk_score(&self) -> f32 {
+        self.score_final_index(&self.null_state, self.unk_idx())
+    }
+
+    /// Returns the approximate memory used by the loaded model per n-gram order
+    ///
+    /// The estimate is derived from the count header and, for binary models, the model type and
+    /// probing multiplier in the [FixedParameters] header. ARPA files are loaded with the model
+    /// type and the probing multiplier of the [ConfigBuilder], which are assumed for them.
+    /// The total is the `model` size of [Model::memory_usage]. Returns `None` for unigram models
+    /// and if KenLM fails to size the model.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/carol.bin", false).unwrap();
+    ///
+    /// let footprint = model.memory_footprint().unwrap();
+    /// assert_eq!(footprint.orders.len(), usize::from(model.get_order()));
+    /// assert_eq!(footprint.total(), model.memory_usage().model);
+    /// ```
+    pub fn memory_footprint(&self) -> Option<MemoryFootprint> {
+        let counts = self
+            .count_header
+            .counts()
+            .iter()
+            .map(|count| count.cardinality as u64)
+            .collect::<Vec<_>>();
+        let multiplier = self
+            .fixed_parameters
+            .map_or(self.builder.probing_multiplier(), |params| {
+                params.probing_multiplier
+            });
+        MemoryFootprint::estimate(&counts, self.model_type(), multiplier).ok()
+    }
+
+    /// Returns the memory used by the loaded model
+    ///
+    /// `model` is the size of the n-gram and vocab structures KenLM mapped or allocated. It is
+    /// estimated from the headers before loading, like [Model::memory_footprint], KenLM does not
+    /// report the size it allocated. Models loaded lazily may not be resident in full. `vocab`
+    /// covers the copies held on the Rust side: the stored vocab with its indices and the lookup
+    /// cache, the completion and suggestion indices are not included.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/carol.bin", true).unwrap();
+    ///
+    /// let usage = model.memory_usage();
+    /// assert!(usage.vocab > 0);
+    /// assert_eq!(usage.total(), usage.model + usage.vocab);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let strings = |words: &mut dyn Iterator<Item = &String>| {
+            words
+                .map(|word| (word.capacity() + std::mem::size_of::<String>()) as u64)
+                .sum::<u64>()
+        };
+        let vocab = self
+            .vocab
+            .as_ref()
+            .map_or(0, |vocab| strings(&mut vocab.iter()));
+        let indices = self.vocab_indices.as_ref().map_or(0, |indices| {
+            (indices.len() * std::mem::size_of::<WordIdx>()) as u64
+        });
+        let positions = self.vocab_positions.as_ref().map_or(0, |positions| {
+            (positions.len() * std::mem::size_of::<u32>()) as u64
+        });
+        let cache = self.lookup_cache.as_ref().map_or(0, |cache| {
+            strings(&mut cache.keys()) + (cache.len() * std::mem::size_of::<WordIdx>()) as u64
+        });
+        MemoryUsage {
+            model: self.model_bytes,
+            vocab: vocab + indices + positions + cache,
+        }
+    }
+
+    /// Returns the storage type of the model
+    ///
+    /// ARPA files are loaded as [ConfigBuilder::arpa_model_type], [ModelType::Probing] by default.
+    /// ```
+    /// use kenlm_rs::{Model, ModelType};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// assert_eq!(model.model_type(), ModelType::Trie);
+    /// ```
+    pub fn model_type(&self) -> ModelType {
+        // the model type is verified when a binary model is loaded
+        self.fixed_parameters
+            .and_then(|params| params.model_type().ok())
+            .unwrap_or_else(|| self.builder.arpa_model_type())
+    }
+
+    /// Whether `word` is resolved to a word of the vocab, see `get_word_idx`
+    ///
+    /// Use this to check for out-of-vocabulary words. Like all lookups, [TokenClasses] and the
+    /// [OovFallback] chain apply.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// assert!(model.contains_word("have"));
+    /// assert!(!model.contains_word("toast"));
+    /// ```
+    pub fn contains_word(&self, word: &str) -> bool {
+        self.resolve_word(word).1 != ResolvedBy::Unknown
+    }
+
+    /// Whether `index` belongs to a word of the vocab other than `<unk>`
+    ///
+    /// This is about KenLM's `<unk>`, a word [SpecialTokens::unk] is set to is still contained.
+    pub fn contains_index(&self, index: WordIdx) -> bool {
+        index != self.not_found_idx() && (*index as usize) < self.vocab_size()
+    }
+
     /// Get the index of a word in the language model
     ///
     /// returns None if the vocab does not contain the word.
     pub fn get_word_idx_opt(&self, word: &str) -> Option<WordIdx> {
-        let vocab = self.inner.BaseVocabulary();
-        cxx::let_cxx_string!(input = &word);
-        let idx = vocab.Index1(&input);
-        //vocab.NotFound() is the unknown word index in the c++ vocab
-        if idx == vocab.NotFound() {
-            return None;
+        match self.resolve_word(word) {
+            (_, ResolvedBy::Unknown) => None,
+            (idx, _) => Some(idx),
         }
-        Some(WordIdx(idx))
     }
 
     /// Get the index of a word in the language model
     ///
-    /// returns vocab.NotFound() if the vocab does not contain the word.
+    /// returns `unk_idx` if the vocab does not contain the word. With the `unicode` feature, the
+    /// word is first normalized if a normalization form is set. If [TokenClasses] are set,
+    /// the word is replaced by the placeholder of its class before the lookup. The word is then
+    /// resolved through the [OovFallback] chain of the model.
     pub fn get_word_idx(&self, word: &str) -> WordIdx {
+        self.resolve_word(word).0
+    }
+
+    /// Get the indices of `words` in the language model, like `get_word_idx` for each word
+    ///
+    /// Use this for pipelines which tokenize whole documents before scoring. After
+    /// `build_lookup_cache`, the lookups of all words contained in the vocab stay in Rust.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let indices = model.get_word_indices("i have toast".split(' '));
+    /// assert_eq!(indices[0], model.get_word_idx("i"));
+    /// assert_eq!(indices[2], model.not_found_idx());
+    /// ```
+    pub fn get_word_indices(
+        &self,
+        words: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Vec<WordIdx> {
+        words
+            .into_iter()
+            .map(|word| self.get_word_idx(word.as_ref()))
+            .collect()
+    }
+
+    /// Get the index of a word and the [FallbackRule] which resolved it
+    ///
+    /// Tries the rules of the [OovFallback] chain in order and returns the index of the first
+    /// candidate contained in the vocabulary. Returns `unk_idx` and [ResolvedBy::Unknown] if no
+    /// rule matched.
+    pub fn resolve_word(&self, word: &str) -> (WordIdx, ResolvedBy) {
+        #[cfg(feature = "unicode")]
+        let normalized = match self.normalization {
+            Some(form) => form.apply(word),
+            None => std::borrow::Cow::Borrowed(word),
+        };
+        #[cfg(feature = "unicode")]
+        let word = normalized.as_ref();
+        let word = match self.token_classes.as_ref() {
+            Some(classes) => classes.classify(word),
+            None => word,
+        };
+        // the token itself always comes first, rules only rewrite tokens which aren't found
+        let idx = self.vocab_index(word);
+        if idx != self.not_found_idx() {
+            return (idx, ResolvedBy::Exact);
+        }
+        for rule in self.oov_fallback.rules() {
+            if matches!(rule, FallbackRule::Exact) {
+                continue;
+            }
+            let candidate = match rule.apply(word) {
+                Some(candidate) => candidate,
+                None => continue,
+            };
+            let idx = self.vocab_index(&candidate);
+            if idx != self.not_found_idx() {
+                return (idx, rule.resolved_by());
+            }
+        }
+        (self.unk_idx(), ResolvedBy::Unknown)
+    }
+
+    /// Index of the beginning of sentence token, `<s>` unless changed with `set_special_tokens`
+    pub fn bos_idx(&self) -> WordIdx {
+        self.bos_idx
+    }
+
+    /// Index of the end of sentence token, `</s>` unless changed with `set_special_tokens`
+    pub fn eos_idx(&self) -> WordIdx {
+        self.eos_idx
+    }
+
+    /// Index of the unknown word token, `<unk>` unless changed with `set_special_tokens`
+    ///
+    /// Out-of-vocabulary words are mapped to this index. KenLM maps them to `<unk>`, so unless
+    /// the special tokens were changed, this is the same as `not_found_idx`.
+    pub fn unk_idx(&self) -> WordIdx {
+        self.unk_idx
+    }
+
+    /// Sets the spellings of the sentence markers and the unknown word token
+    ///
+    /// KenLM only recognizes `<s>`, `</s>` and `<unk>`. For models built by toolkits with other
+    /// conventions, this makes sentence scoring use the given tokens instead, e.g. `bos` scoring
+    /// starts in the context of [SpecialTokens::bos] and out-of-vocabulary words are scored as
+    /// [SpecialTokens::unk]. Fails with [Error::InvalidConfig] if a token is not in the vocab.
+    /// ```
+    /// use kenlm_rs::{Model, SpecialTokens};
+    /// let mut model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// model.set_special_tokens(&SpecialTokens::new().unk("a")).unwrap();
+    /// assert_eq!(model.get_word_idx("toast"), model.get_word_idx("a"));
+    /// ```
+    pub fn set_special_tokens(&mut self, tokens: &SpecialTokens) -> Result<(), Error> {
+        let lookup = |token: &str| {
+            let idx = self.vocab_index(token);
+            // `<unk>` itself is where KenLM maps unknown words to
+            if idx == self.not_found_idx() && token != "<unk>" {
+                return Err(Error::InvalidConfig(format!(
+                    "the special token {token} is not in the vocab"
+                )));
+            }
+            Ok(idx)
+        };
+        let bos_idx = lookup(&tokens.bos)?;
+        let eos_idx = lookup(&tokens.eos)?;
+        let unk_idx = lookup(&tokens.unk)?;
+        self.bos_idx = bos_idx;
+        self.eos_idx = eos_idx;
+        self.unk_idx = unk_idx;
+        self.bos_state = self.state_from_indices(&[bos_idx]);
+        Ok(())
+    }
+
+    /// Index returned by lookups of words which are not in the vocabulary
+    pub fn not_found_idx(&self) -> WordIdx {
+        self.not_found_idx
+    }
+
+    /// Looks up `word` in the C++ vocabulary, bypassing classes and the fallback chain
+    ///
+    /// With the lookup cache, words are resolved without calling into C++. It holds the whole
+    /// vocabulary, so words missing from it are not found by KenLM either.
+    fn vocab_index(&self, word: &str) -> WordIdx {
+        if let Some(cache) = &self.lookup_cache {
+            return cache.get(word).copied().unwrap_or(self.not_found_idx);
+        }
         let vocab = self.inner.BaseVocabulary();
         cxx::let_cxx_string!(input = &word);
-        let idx = vocab.Index1(&input);
-        WordIdx(idx)
+        WordIdx(vocab.Index1(&input))
+    }
+
+    /// Sets the chain of rules used to resolve out-of-vocabulary words
+    ///
+    /// The chain applies to all lookups and scoring functions taking words, see [OovFallback].
+    pub fn set_oov_fallback(&mut self, oov_fallback: OovFallback) {
+        self.oov_fallback = oov_fallback;
+    }
+
+    /// Sets the placeholder classes applied to words before they are looked up
+    ///
+    /// Use this if the model was trained on text in which e.g. numbers were replaced by `<num>`,
+    /// see [TokenClasses]. The classes apply to all lookups and scoring functions taking words.
+    pub fn set_token_classes(&mut self, token_classes: TokenClasses) {
+        self.token_classes = Some(token_classes);
+    }
+
+    /// Sets the Unicode normalization applied to words before they are looked up
+    ///
+    /// The normalization applies to all lookups and scoring functions taking words, before
+    /// [TokenClasses] and the [OovFallback] chain. `None` disables it, which is the default.
+    /// ```
+    /// use kenlm_rs::{Model, NormalizationForm};
+    /// let mut model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// // fullwidth i
+    /// assert_eq!(model.get_word_idx_opt("\u{ff49}"), None);
+    /// model.set_unicode_normalization(Some(NormalizationForm::Nfkc));
+    /// assert_eq!(model.get_word_idx("\u{ff49}"), model.get_word_idx("i"));
+    /// ```
+    #[cfg(feature = "unicode")]
+    pub fn set_unicode_normalization(&mut self, normalization: Option<NormalizationForm>) {
+        self.normalization = normalization;
     }
 
     /// Score a word (suffix) given a state (prefix).
@@ -140,10 +663,8 @@ impl Model {
         out_state: &mut State,
         word: &str,
     ) -> f32 {
-        let vocab = self.inner.BaseVocabulary();
-        cxx::let_cxx_string!(input = &word);
-        let index = vocab.IndexString(&input);
-        self.score_index_given_state(in_state, out_state, WordIdx(index))
+        let index = self.get_word_idx(word);
+        self.score_index_given_state(in_state, out_state, index)
     }
 
     /// Returns the conditional probability of `index` given `in_state` in log10-space
@@ -164,138 +685,1207 @@ impl Model {
         out_state: &mut State,
         index: WordIdx,
     ) -> f32 {
-        let in_state = in_state.0.pin_mut();
-        let s = std::pin::Pin::<&mut bridge::lm::ngram::State>::into_inner(in_state);
-        let ptr = s as *mut bridge::lm::ngram::State;
-        let raw1 = ptr as *mut autocxx::c_void;
-
-        let out_state = out_state.0.pin_mut();
-        let s2 = std::pin::Pin::<&mut bridge::lm::ngram::State>::into_inner(out_state);
-        let ptr2 = s2 as *mut bridge::lm::ngram::State;
-        let raw2 = ptr2 as *mut autocxx::c_void;
+        let raw1 = in_state.as_mut_ptr();
+        let raw2 = out_state.as_mut_ptr();
         unsafe { self.inner.BaseScore(raw1, index.0, raw2) }
     }
 
-    /// Returns the joint probability of `sentence` in log10-space
+    /// Returns the conditional probability of `word` given at most `max_context` words of `in_state`
     ///
-    /// Computes the joint probability of the given sentence given this model. It returns the probability
-    /// in log10-space.
-    pub fn score_sentence(&self, sentence: &[&str], bos: bool, eos: bool) -> f32 {
-        let vocab = self.inner.BaseVocabulary();
+    /// Behaves like `score_word_given_state` after truncating `in_state` to its `max_context`
+    /// most recent words, see [State::truncate]. `out_state` is truncated as well, so swapping
+    /// and repeating the call keeps the history bounded, e.g. `max_context = 1` emulates a
+    /// bigram model.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let mut in_state = model.state_from_context(&["<s>", "i"]);
+    /// let mut out_state = model.new_state();
+    /// let score = model.score_with_context_limit(&mut in_state, &mut out_state, "have", 1);
+    /// assert_eq!(score, model.score_ngram(&["i"], "have"));
+    /// assert!(out_state.len() <= 1);
+    /// ```
+    pub fn score_with_context_limit(
+        &self,
+        in_state: &mut State,
+        out_state: &mut State,
+        word: &str,
+        max_context: usize,
+    ) -> f32 {
+        in_state.truncate(max_context);
+        let score = self.score_word_given_state(in_state, out_state, word);
+        out_state.truncate(max_context);
+        score
+    }
 
-        let mut mem1 = self.new_state();
-        let mut mem2 = self.new_state();
-        if bos {
-            self.fill_state_with_bos_context(&mut mem1);
-        } else {
-            self.fill_state_with_null_context(&mut mem1);
+    /// Scores each word given the state it is paired with and advances the state past the word
+    ///
+    /// Returns the log10 probabilities in the order of `pairs`. Afterwards, every state holds its
+    /// out state, like `in_state` after `score_index_given_state` and swapping. The whole batch
+    /// is scored in a single call into C++, which saves the per-call overhead when expanding
+    /// many hypotheses at once, e.g. in beam search.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let mut pairs = ["i", "a"].map(|w| (model.bos_state(), model.get_word_idx(w)));
+    /// let scores = model.score_pairs(&mut pairs);
+    /// assert_eq!(scores[0], model.score_sentence(&["i"], true, false));
+    /// assert_eq!(scores[1], model.score_sentence(&["a"], true, false));
+    /// ```
+    pub fn score_pairs(&self, pairs: &mut [(State, WordIdx)]) -> Vec<f32> {
+        let mut states = pairs
+            .iter()
+            .map(|(state, _)| state.0.clone())
+            .collect::<Vec<_>>();
+        let words = pairs.iter().map(|(_, word)| word.0).collect::<Vec<_>>();
+        let mut scores = vec![0f32; pairs.len()];
+        unsafe {
+            bridge::lm::base::BaseScoreBatch(
+                &self.inner,
+                states.as_mut_ptr(),
+                words.as_ptr(),
+                scores.as_mut_ptr(),
+                pairs.len(),
+            )
         }
-
-        let mut score = 0f32;
-
-        for w in sentence {
-            let out = self.score_word_given_state(&mut mem1, &mut mem2, w);
-            std::mem::swap(&mut mem1, &mut mem2);
-            score += out;
+        for ((state, _), out_state) in pairs.iter_mut().zip(states) {
+            state.0 = out_state;
         }
+        scores
+    }
 
-        if eos {
-            let out =
-                self.score_index_given_state(&mut mem1, &mut mem2, WordIdx(vocab.EndSentence()));
-            score += out;
+    /// Returns the joint probability of `indices` given `in_state` in log10-space
+    ///
+    /// Scores the indices one after another like `score_index_given_state`, swapping the states
+    /// in between. Afterwards, `in_state` holds the state following the last index, so calls can be
+    /// chained to score a long sequence in chunks. `out_state` is used as scratch space.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let indices = ["i", "have"].map(|w| model.get_word_idx(w));
+    /// let mut in_state = model.bos_state();
+    /// let mut out_state = model.new_state();
+    /// let score = model.score_indices(&mut in_state, &mut out_state, &indices);
+    /// assert_eq!(score, model.score_sentence(&["i", "have"], true, false));
+    /// ```
+    pub fn score_indices(
+        &self,
+        in_state: &mut State,
+        out_state: &mut State,
+        indices: &[WordIdx],
+    ) -> f32 {
+        let mut score = 0f32;
+        for &index in indices {
+            score += self.score_index_given_state(in_state, out_state, index);
+            std::mem::swap(in_state, out_state);
         }
-
         score
     }
 
-    /// Constructs a new StateWrapper
-    pub fn new_state(&self) -> State {
-        let mut state = State::new_for_model(self);
-        // better safe than sorry i guess?
-        self.fill_state_with_null_context(&mut state);
-        state
+    /// Returns the conditional probability of `word` given the `context` words in log10-space
+    ///
+    /// `context` is in reading order, i.e. the word right before `word` comes last. The state is
+    /// built internally by feeding `context` to the model starting from the null context. Start
+    /// `context` with `<s>` to score at the beginning of a sentence.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let score = model.score_ngram(&["<s>", "i"], "have");
+    /// assert!((score - -0.16921267).abs() < 1e-6);
+    /// ```
+    pub fn score_ngram(
+        &self,
+        context: impl IntoIterator<Item = impl AsRef<str>>,
+        word: &str,
+    ) -> f32 {
+        let state = self.state_from_context(context);
+        self.score_final_word(&state, word)
     }
 
-    /// Get the string vocabulary
+    /// Returns the state after feeding the `context` words to the model
     ///
-    /// This will only be Some if the model has a vocabulary and you passed `store_vocab` to the constructor.
-    pub fn get_vocab(&self) -> Option<&[String]> {
-        self.vocab.as_deref()
+    /// `context` is in reading order and fed starting from the null context, the scores are
+    /// discarded. Start `context` with `<s>` to seed a state at the beginning of a sentence.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let state = model.state_from_context(&["<s>", "i"]);
+    /// assert_eq!(
+    ///     model.score_final_word(&state, "have"),
+    ///     model.score_ngram(&["<s>", "i"], "have")
+    /// );
+    /// ```
+    pub fn state_from_context(&self, context: impl IntoIterator<Item = impl AsRef<str>>) -> State {
+        let indices = self.get_word_indices(context);
+        self.state_from_indices(&indices)
     }
 
-    /// Return the order of this ngram model
-    pub fn get_order(&self) -> u8 {
-        self.inner.Order()
-    }
-    /// Initializes `state` to the `<s>` (beginning of sentence) context
+    /// Returns the state after feeding the `context` indices to the model
     ///
-    /// Use this if you want to take the beginning of sentences into account.
-    pub fn fill_state_with_bos_context(&self, state: &mut State) {
-        let in_state = state.0.pin_mut();
-        let s = std::pin::Pin::<&mut bridge::lm::ngram::State>::into_inner(in_state);
-        let ptr = s as *mut bridge::lm::ngram::State;
-        let raw = ptr as *mut autocxx::c_void;
-        unsafe { self.inner.BeginSentenceWrite(raw) }
+    /// See `state_from_context`.
+    pub fn state_from_indices(&self, context: &[WordIdx]) -> State {
+        let mut state = self.null_state();
+        let mut out_state = self.new_state();
+        self.score_indices(&mut state, &mut out_state, context);
+        state
     }
 
-    /// Initializes `state` to an empty context.
+    /// Returns the conditional probability of `word` given `state` without producing an out-state
     ///
-    /// Use this function if you want to score without `<s>` (beginning of sentence) or discard context
-    pub fn fill_state_with_null_context(&self, state: &mut State) {
-        let in_state = state.0.pin_mut();
-        let s = std::pin::Pin::<&mut bridge::lm::ngram::State>::into_inner(in_state);
-        let ptr = s as *mut bridge::lm::ngram::State;
-        let raw = ptr as *mut autocxx::c_void;
-        unsafe { self.inner.NullContextWrite(raw) }
+    /// Use this when the state following `word` is not needed anymore, e.g. for the last word of a
+    /// hypothesis. It saves allocating an out-state and swapping it in Rust. KenLM has no scoring
+    /// path which skips the state, it is still written to a temporary on the C++ stack.
+    pub fn score_final_word(&self, state: &State, word: &str) -> f32 {
+        let index = self.get_word_idx(word);
+        self.score_final_index(state, index)
     }
 
-    fn state_size(&self) -> usize {
-        self.inner.StateSize()
+    /// Returns the conditional probability of `index` given `state` without producing an out-state
+    ///
+    /// See `score_final_word`.
+    pub fn score_final_index(&self, state: &State, index: WordIdx) -> f32 {
+        let raw = state.as_ptr();
+        unsafe { bridge::lm::base::BaseScoreForgetState(&self.inner, raw, index.0) }
     }
-}
-
-/// Index into the vocabulary of a [Model]
-///
-/// `WordIdx` is a wrapper around the vocabulary index type [autocxx::c_uint].
-/// A [autocxx::c_uint] as a newtype wrapper around a [core::ffi::c_uint].
-/// It seems to be the case that this is almost always a [u32].
-#[derive(Debug, Clone, Copy)]
-pub struct WordIdx(c_uint);
 
-impl Deref for WordIdx {
-    type Target = u32;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0 .0
+    /// Scores `word` given `in_state` like `score_word_given_state`, mirroring KenLM's `FullScore`
+    ///
+    /// Next to the probability, the returned [FullScore] tells how long the matched n-gram was and
+    /// whether `word` is out of vocabulary. States are handled like in `score_word_given_state`.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let mut in_state = model.bos_state();
+    /// let mut out_state = model.new_state();
+    /// let full = model.full_score_word_given_state(&mut in_state, &mut out_state, "i");
+    /// assert_eq!(full.ngram_length, 2);
+    /// assert!(!full.oov);
+    /// ```
+    pub fn full_score_word_given_state(
+        &self,
+        in_state: &mut State,
+        out_state: &mut State,
+        word: &str,
+    ) -> FullScore {
+        let (index, resolved_by) = self.resolve_word(word);
+        let full = self.raw_full_score(in_state, out_state, index);
+        FullScore {
+            log_prob: full.prob,
+            ngram_length: full.ngram_length,
+            oov: resolved_by == ResolvedBy::Unknown,
+        }
     }
-}
-
-/// The `State` is the prefix storage
-///
-/// `State` is a wrapper around the C++ pod-struct `lm::ngram::State`.
-/// It tracks the words in the prefix along backoff and currently active length.
-#[derive(Debug)]
-pub struct State(UniquePtr<bridge::lm::ngram::State>);
 
-impl State {
-    fn new_for_model(model: &Model) -> Self {
-        let size = std::mem::size_of::<bridge::lm::ngram::State>();
-        let model_size = model.state_size();
-        assert_eq!(size, model_size, "size of bridge::lm::ngram::State: {size} does not match size returned by StateSize: {model_size}");
-        let state = bridge::lm::ngram::State::new().within_unique_ptr();
-        Self(state)
+    /// Scores `index` given `in_state` like `score_index_given_state`, mirroring KenLM's `FullScore`
+    ///
+    /// See `full_score_word_given_state`. Without the word, only [Model::not_found_idx] is
+    /// reported as out of vocabulary.
+    pub fn full_score_index_given_state(
+        &self,
+        in_state: &mut State,
+        out_state: &mut State,
+        index: WordIdx,
+    ) -> FullScore {
+        let full = self.raw_full_score(in_state, out_state, index);
+        FullScore {
+            log_prob: full.prob,
+            ngram_length: full.ngram_length,
+            oov: index == self.not_found_idx(),
+        }
     }
 
-    /// Fetches the words currently stored in this [State]
-    pub fn words(&self) -> Vec<WordIdx> {
+    fn raw_full_score(
+        &self,
+        in_state: &mut State,
+        out_state: &mut State,
+        index: WordIdx,
+    ) -> bridge::lm::FullScoreReturn {
+        let raw1 = in_state.as_mut_ptr();
+        let raw2 = out_state.as_mut_ptr();
+        unsafe { self.inner.BaseFullScore(raw1, index.0, raw2) }
+    }
+
+    /// Scores `word` given `in_state` and returns every intermediate value, a verbose `FullScore`
+    ///
+    /// `in_state` is not modified. The returned [ScoreTrace] holds which n-gram order matched,
+    /// the backoff weights which were applied on top of the n-gram probability, the remaining
+    /// fields of KenLM's `FullScoreReturn` and the state after `word`.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let state = model.state_from_context(&["<s>", "i"]);
+    /// let trace = model.explain_score(&state, "have");
+    /// assert_eq!(trace.ngram_length as usize, trace.ngram.len());
+    /// let backoffs = trace.backoffs.iter().map(|b| b.backoff).sum::<f32>();
+    /// assert!((trace.ngram_log_prob + backoffs - trace.log_prob).abs() < 1e-6);
+    /// assert_eq!(trace.log_prob, model.score_final_word(&state, "have"));
+    /// ```
+    pub fn explain_score(&self, in_state: &State, word: &str) -> ScoreTrace {
+        let (index, resolved_by) = self.resolve_word(word);
+        let mut state = in_state.clone();
+        let mut out_state = self.new_state();
+        let full = self.raw_full_score(&mut state, &mut out_state, index);
+        let oov = resolved_by == ResolvedBy::Unknown;
+        ScoreTrace::new(in_state, index, oov, &full, out_state)
+    }
+
+    /// Returns the joint probability of `sentence` in log10-space
+    ///
+    /// Computes the joint probability of the given sentence given this model. It returns the probability
+    /// in log10-space. The words can be anything that derefs to `str`, e.g. a `&[&str]`, a
+    /// `Vec<String>` or an iterator over the tokens of a tokenizer.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let score = model.score_sentence(&["i", "have", "a", "good", "deal"], true, true);
+    /// let owned = vec!["i".to_string(), "have".to_string(), "a".to_string()];
+    /// let score = model.score_sentence(&owned, true, false);
+    /// let score = model.score_sentence("i have a good deal".split(' '), true, true);
+    /// ```
+    pub fn score_sentence(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        bos: bool,
+        eos: bool,
+    ) -> f32 {
+        self.score_sentence_with_options(sentence, ScoreOptions::new(bos, eos))
+    }
+
+    /// Returns the joint probability of `sentence` in log10-space along with the final state
+    ///
+    /// Behaves like `score_sentence`, the returned state is the state after the last scored token,
+    /// i.e. after `</s>` if `eos` is set. Pass it to `continue_sentence` to carry the context over
+    /// to the next call.
+    pub fn score_sentence_with_state(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        bos: bool,
+        eos: bool,
+    ) -> (f32, State) {
+        let mut state = if bos {
+            self.bos_state()
+        } else {
+            self.null_state()
+        };
+        let score = self.continue_sentence(&mut state, sentence, eos);
+        (score, state)
+    }
+
+    /// Returns the joint probability of `sentence` in log10-space given the context in `state`
+    ///
+    /// Scores `sentence` starting from `state` and leaves the state after the last scored token
+    /// in `state`, so streaming text can be scored chunk by chunk. If `eos` is set, `</s>` is
+    /// scored after the last word.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let mut state = model.bos_state();
+    /// let mut score = model.continue_sentence(&mut state, &["i", "have"], false);
+    /// score += model.continue_sentence(&mut state, &["a", "good", "deal"], true);
+    /// let expected = model.score_sentence(&["i", "have", "a", "good", "deal"], true, true);
+    /// assert!((score - expected).abs() < 1e-6);
+    /// ```
+    pub fn continue_sentence(
+        &self,
+        state: &mut State,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        eos: bool,
+    ) -> f32 {
+        let mut out_state = self.new_state();
+        let mut score = 0f32;
+        for w in sentence {
+            score += self.score_word_given_state(state, &mut out_state, w.as_ref());
+            std::mem::swap(state, &mut out_state);
+        }
+        if eos {
+            score += self.score_index_given_state(state, &mut out_state, self.eos_idx());
+            std::mem::swap(state, &mut out_state);
+        }
+        score
+    }
+
+    /// Returns the perplexity of `sentence`
+    ///
+    /// The perplexity is `10^(-log_prob / n)` where `log_prob` is the log10 probability of the
+    /// sentence and `n` the number of scored tokens, which includes `</s>` if `eos` is set.
+    /// Returns `None` if no token was scored, i.e. for an empty sentence without `eos`.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let perplexity = model.perplexity(["i", "have", "a", "good", "deal"], true, true);
+    /// assert!(perplexity.unwrap() > 1.);
+    /// assert!(model.perplexity(Vec::<String>::new(), true, false).is_none());
+    /// ```
+    pub fn perplexity(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        bos: bool,
+        eos: bool,
+    ) -> Option<f64> {
+        self.corpus_perplexity([sentence], bos, eos)
+    }
+
+    /// Returns the perplexity of all `sentences` taken together
+    ///
+    /// Log probabilities and token counts are summed over all sentences before computing the
+    /// perplexity, so this is not the mean of the per-sentence perplexities. Returns `None` if no
+    /// token was scored. See `perplexity`.
+    pub fn corpus_perplexity<S>(
+        &self,
+        sentences: impl IntoIterator<Item = S>,
+        bos: bool,
+        eos: bool,
+    ) -> Option<f64>
+    where
+        S: IntoIterator,
+        S::Item: AsRef<str>,
+    {
+        let mut log_prob = 0f64;
+        let mut tokens = 0usize;
+        for sentence in sentences {
+            let mut words = 0usize;
+            let sentence = sentence.into_iter().inspect(|_| words += 1);
+            log_prob += f64::from(self.score_sentence(sentence, bos, eos));
+            tokens += words + usize::from(eos);
+        }
+        if tokens == 0 {
+            return None;
+        }
+        Some(10f64.powf(-log_prob / tokens as f64))
+    }
+
+    /// Returns the joint probability of the sentence of `indices` in log10-space
+    ///
+    /// Behaves like `score_sentence` for sentences which were already mapped to vocabulary indices,
+    /// e.g. through `get_word_idx`.
+    pub fn score_sentence_indices(&self, indices: &[WordIdx], bos: bool, eos: bool) -> f32 {
+        let mut mem1 = if bos {
+            self.bos_state()
+        } else {
+            self.null_state()
+        };
+        let mut mem2 = self.new_state();
+        let mut score = self.score_indices(&mut mem1, &mut mem2, indices);
+        if eos {
+            score += self.score_final_index(&mem1, self.eos_idx());
+        }
+        score
+    }
+
+    /// Returns the joint probability of `sentence` in log10-space according to `options`
+    ///
+    /// Behaves like `score_sentence`, additionally, if [ScoreOptions::exclude_oov] is set, the
+    /// scores of out-of-vocabulary words are not added to the total. The state is still advanced
+    /// over them, so the following words are scored in the same context as without the option.
+    /// ```
+    /// use kenlm_rs::{Model, ScoreOptions};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let options = ScoreOptions::new(true, true).exclude_oov(true);
+    /// let score = model.score_sentence_with_options(&["what", "a", "lovely", "sentence"], options);
+    /// eprintln!("{score:?}");
+    /// ```
+    pub fn score_sentence_with_options(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+    ) -> f32 {
+        let mut mem1 = self.new_state();
+        let mut mem2 = self.new_state();
+        self.score_sentence_reusing_states(sentence, options, &mut mem1, &mut mem2)
+    }
+
+    /// Returns the joint probabilities of all `sentences` in log10-space according to `options`
+    ///
+    /// Scores every sentence like `score_sentence_with_options`. The states are allocated once
+    /// and reused for all sentences, which makes this faster than scoring the sentences one by one.
+    /// ```
+    /// use kenlm_rs::{Model, ScoreOptions};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let sentences = vec![
+    ///     vec!["i", "have", "a", "good", "deal"],
+    ///     vec!["what", "a", "lovely", "sentence"],
+    /// ];
+    /// let scores = model.score_sentences(&sentences, ScoreOptions::new(true, true));
+    /// assert_eq!(scores.len(), 2);
+    /// ```
+    pub fn score_sentences<S>(
+        &self,
+        sentences: impl IntoIterator<Item = S>,
+        options: ScoreOptions,
+    ) -> Vec<f32>
+    where
+        S: IntoIterator,
+        S::Item: AsRef<str>,
+    {
+        let mut mem1 = self.new_state();
+        let mut mem2 = self.new_state();
+        sentences
+            .into_iter()
+            .map(|sentence| {
+                self.score_sentence_reusing_states(sentence, options, &mut mem1, &mut mem2)
+            })
+            .collect()
+    }
+
+    /// Returns the joint probability of all `sentences` in log10-space, accumulated in `f64`
+    ///
+    /// Scores every sentence like `score_sentence_with_options`. The per-token scores are summed
+    /// up in `f64`, summing many thousands of `f32` scores loses precision otherwise.
+    /// ```
+    /// use kenlm_rs::{Model, ScoreOptions};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let document = "i have a good deal\nwhat a lovely sentence";
+    /// let sentences = document.lines().map(|line| line.split_ascii_whitespace());
+    /// let score = model.score_document(sentences, ScoreOptions::new(true, true));
+    /// ```
+    pub fn score_document(
+        &self,
+        sentences: impl IntoIterator<Item = impl IntoIterator<Item = impl AsRef<str>>>,
+        options: ScoreOptions,
+    ) -> f64 {
+        let mut mem1 = self.new_state();
+        let mut mem2 = self.new_state();
+        let mut score = 0f64;
+        for sentence in sentences {
+            self.for_each_score(sentence, options, &mut mem1, &mut mem2, |out| {
+                score += f64::from(out)
+            });
+        }
+        score
+    }
+
+    fn score_sentence_reusing_states(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+        mem1: &mut State,
+        mem2: &mut State,
+    ) -> f32 {
+        let mut score = 0f32;
+        self.for_each_score(sentence, options, mem1, mem2, |out| score += out);
+        score
+    }
+
+    /// Calls `add` with every score counting towards the total of `sentence` according to `options`
+    fn for_each_score(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+        mem1: &mut State,
+        mem2: &mut State,
+        mut add: impl FnMut(f32),
+    ) {
+        if options.bos {
+            mem1.copy_from(&self.bos_state);
+        } else {
+            mem1.copy_from(&self.null_state);
+        }
+
+        for w in sentence {
+            let (index, resolved_by) = self.resolve_word(w.as_ref());
+            let out = self.score_index_given_state(mem1, mem2, index);
+            std::mem::swap(mem1, mem2);
+            if options.exclude_oov && resolved_by == ResolvedBy::Unknown {
+                continue;
+            }
+            add(out);
+        }
+
+        if options.eos {
+            add(self.score_final_index(mem1, self.eos_idx()));
+        }
+    }
+
+    /// Returns the joint probability of `sentence` along with the positions of its OOV words
+    ///
+    /// Scores `sentence` like `score_sentence_with_options`, see [OovScore]. Positions index into
+    /// `sentence`, `<s>` and `</s>` are never out of vocabulary.
+    /// ```
+    /// use kenlm_rs::{Model, ScoreOptions};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let score = model.score_sentence_with_oov(&["i", "have", "toast"], ScoreOptions::new(true, true));
+    /// assert_eq!(score.oov_positions, &[2]);
+    /// assert_eq!(score.oov_count(), 1);
+    /// ```
+    pub fn score_sentence_with_oov(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+    ) -> OovScore {
+        let mut mem1 = if options.bos {
+            self.bos_state()
+        } else {
+            self.null_state()
+        };
+        let mut mem2 = self.new_state();
+
+        let mut log_prob = 0f32;
+        let mut oov_positions = vec![];
+        for (position, w) in sentence.into_iter().enumerate() {
+            let (index, resolved_by) = self.resolve_word(w.as_ref());
+            let out = self.score_index_given_state(&mut mem1, &mut mem2, index);
+            std::mem::swap(&mut mem1, &mut mem2);
+            if resolved_by == ResolvedBy::Unknown {
+                oov_positions.push(position);
+                if options.exclude_oov {
+                    continue;
+                }
+            }
+            log_prob += out;
+        }
+
+        if options.eos {
+            log_prob += self.score_final_index(&mem1, self.eos_idx());
+        }
+
+        OovScore {
+            log_prob,
+            oov_positions,
+        }
+    }
+
+    /// Scores whitespace tokenized sentences received from `rx` on `workers` threads and sends
+    /// them to `tx`
+    ///
+    /// Runs until either all senders of `rx` are dropped or the receiver of `tx` hung up. The
+    /// workers take turns receiving, so with more than one worker the scores may be sent in a
+    /// different order than the sentences were received, [SentenceScore] carries its sentence.
+    /// Memory stays bounded by the capacities of the channels since `tx` is a
+    /// [std::sync::mpsc::sync_channel] and producers can be throttled the same way.
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use std::sync::mpsc::sync_channel;
+    /// use kenlm_rs::{Model, ScoreOptions};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let (sentence_tx, sentence_rx) = sync_channel(16);
+    /// let (score_tx, score_rx) = sync_channel(16);
+    /// let producer = std::thread::spawn(move || {
+    ///     sentence_tx.send("i have a good deal".to_string()).unwrap();
+    /// });
+    /// let consumer = std::thread::spawn(move || score_rx.iter().collect::<Vec<_>>());
+    /// let workers = NonZeroUsize::new(4).unwrap();
+    /// model.score_channel(sentence_rx, score_tx, ScoreOptions::new(true, true), workers);
+    /// producer.join().unwrap();
+    /// assert_eq!(consumer.join().unwrap().len(), 1);
+    /// ```
+    pub fn score_channel(
+        &self,
+        rx: Receiver<String>,
+        tx: SyncSender<SentenceScore>,
+        options: ScoreOptions,
+        workers: NonZeroUsize,
+    ) {
+        let rx = Mutex::new(rx);
+        std::thread::scope(|scope| {
+            for _ in 0..workers.get() {
+                let tx = tx.clone();
+                let rx = &rx;
+                scope.spawn(move || loop {
+                    // the lock is released before scoring, so the workers score in parallel
+                    let received = rx.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    let sentence = match received {
+                        Ok(sentence) => sentence,
+                        Err(_) => return,
+                    };
+                    let log_prob = self
+                        .score_sentence_with_options(sentence.split_ascii_whitespace(), options);
+                    if tx.send(SentenceScore { sentence, log_prob }).is_err() {
+                        return;
+                    }
+                });
+            }
+        });
+    }
+
+    /// Returns the score of every word in `sentence` along with the length of the matched n-gram
+    ///
+    /// Scores `sentence` like `score_sentence_with_options` but instead of summing up, returns a
+    /// [TokenScore] per word. If [ScoreOptions::eos] is set, the last entry is the score of `</s>`.
+    /// [ScoreOptions::exclude_oov] has no effect here, out-of-vocabulary words are marked instead.
+    ///
+    /// [TokenScore::ngram_length] tells which order matched for a token, e.g. a trigram model
+    /// reports `3` if the full trigram was found and `1` if it backed off to the unigram.
+    pub fn score_sentence_detailed(
+        &self,
+        sentence: impl IntoIterator<Item = impl AsRef<str>>,
+        options: ScoreOptions,
+    ) -> Vec<TokenScore> {
+        let mut mem1 = self.new_state();
+        let mut mem2 = self.new_state();
+        if options.bos {
+            self.fill_state_with_bos_context(&mut mem1);
+        } else {
+            self.fill_state_with_null_context(&mut mem1);
+        }
+
+        let sentence = sentence.into_iter();
+        let mut scores = Vec::with_capacity(sentence.size_hint().0 + usize::from(options.eos));
+        for w in sentence {
+            let (index, resolved_by) = self.resolve_word(w.as_ref());
+            let full = self.raw_full_score(&mut mem1, &mut mem2, index);
+            std::mem::swap(&mut mem1, &mut mem2);
+            scores.push(TokenScore {
+                word_idx: index,
+                log_prob: full.prob,
+                ngram_length: full.ngram_length,
+                oov: resolved_by == ResolvedBy::Unknown,
+                resolved_by,
+            });
+        }
+
+        if options.eos {
+            let index = self.eos_idx();
+            let full = self.raw_full_score(&mut mem1, &mut mem2, index);
+            scores.push(TokenScore {
+                word_idx: index,
+                log_prob: full.prob,
+                ngram_length: full.ngram_length,
+                oov: false,
+                resolved_by: ResolvedBy::Exact,
+            });
+        }
+
+        scores
+    }
+
+    /// Constructs a new StateWrapper
+    pub fn new_state(&self) -> State {
+        self.null_state()
+    }
+
+    /// Returns a copy of the `<s>` (beginning of sentence) state
+    ///
+    /// The state is computed once when the model is loaded, copying it does not call into C++.
+    pub fn bos_state(&self) -> State {
+        self.bos_state.clone()
+    }
+
+    /// Returns a copy of the empty context state
+    ///
+    /// The state is computed once when the model is loaded, copying it does not call into C++.
+    pub fn null_state(&self) -> State {
+        self.null_state.clone()
+    }
+
+    /// Get the string vocabulary
+    ///
+    /// This will only be Some if the model has a vocabulary and you passed `store_vocab` to the constructor.
+    pub fn get_vocab(&self) -> Option<&[String]> {
+        self.vocab.as_deref()
+    }
+
+    /// Get the indices of the words in the string vocabulary
+    ///
+    /// Parallel to `get_vocab`, the i-th element is the index KenLM enumerated the i-th word with.
+    /// KenLM enumerates in the order of the indices, so unless the vocab was sorted, see
+    /// `new_with_sorted_vocab`, the position of a word is its index.
+    pub fn get_vocab_indices(&self) -> Option<&[WordIdx]> {
+        self.vocab_indices.as_deref()
+    }
+
+    /// Get the stored vocab paired with the index of each word
+    ///
+    /// Iterates in the order of `get_vocab`. Unlike the position in `get_vocab`, the paired index
+    /// is always the one KenLM scores the word with, also if the vocab was sorted.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new_with_sorted_vocab("test_data/test.bin").unwrap();
+    ///
+    /// for (index, word) in model.get_vocab_with_indices().unwrap() {
+    ///     assert_eq!(model.get_word_idx(word), index);
+    /// }
+    /// ```
+    pub fn get_vocab_with_indices(&self) -> Option<impl Iterator<Item = (WordIdx, &str)> + '_> {
+        let vocab = self.vocab.as_ref()?;
+        let indices = self.vocab_indices.as_ref()?;
+        Some(
+            indices
+                .iter()
+                .copied()
+                .zip(vocab.iter().map(String::as_str)),
+        )
+    }
+
+    /// Builds the index used by `suggest` for candidates up to `max_edits` edits
+    ///
+    /// The index is built over the stored vocabulary, returns [Error::ModelHasNoVocab] if the
+    /// model was loaded without `store_vocab`. It stores all strings derived from the vocabulary
+    /// by deleting up to `max_edits` characters, so keep `max_edits` small for large vocabularies.
+    pub fn build_suggestion_index(&mut self, max_edits: usize) -> Result<(), Error> {
+        let vocab = self.vocab.as_deref().ok_or(Error::ModelHasNoVocab)?;
+        self.suggestion_index = Some(SuggestionIndex::new(vocab, max_edits));
+        Ok(())
+    }
+
+    /// Returns in-vocabulary words within `max_edits` edits of `word`
+    ///
+    /// The suggestions are ranked by their unigram probability, ties are broken by edit distance.
+    /// `max_edits` is capped by the value passed to `build_suggestion_index`, returns None if the
+    /// index was not built.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let mut model = Model::new("test_data/test.bin", true).unwrap();
+    /// model.build_suggestion_index(2).unwrap();
+    ///
+    /// let suggestions = model.suggest("hav", 1).unwrap();
+    /// assert_eq!(suggestions[0].word, "have");
+    /// ```
+    pub fn suggest(&self, word: &str, max_edits: usize) -> Option<Vec<Suggestion<'_>>> {
+        let index = self.suggestion_index.as_ref()?;
+        let vocab = self.vocab.as_deref()?;
+        let mut suggestions = index
+            .candidates(vocab, word, max_edits)
+            .into_iter()
+            .map(|(position, distance)| {
+                let word = vocab[position].as_str();
+                let word_idx = self.vocab_index(word);
+                Suggestion {
+                    word,
+                    word_idx,
+                    distance,
+                    log_prob: self.score_final_index(&self.null_state, word_idx),
+                }
+            })
+            .collect::<Vec<_>>();
+        suggestions.sort_by(|s1, s2| {
+            s2.log_prob
+                .total_cmp(&s1.log_prob)
+                .then_with(|| s1.distance.cmp(&s2.distance))
+        });
+        Some(suggestions)
+    }
+
+    /// Writes the stored vocab to `path`, one word per line in the order of `get_vocab`
+    ///
+    /// If `with_indices` is set, each line is the word followed by a tab and its index. Returns
+    /// [Error::ModelHasNoVocab] if the model was loaded without `store_vocab`.
+    /// ```no_run
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", true).unwrap();
+    ///
+    /// model.write_vocab("vocab.txt", false).unwrap();
+    /// ```
+    pub fn write_vocab(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        with_indices: bool,
+    ) -> Result<(), Error> {
+        use std::io::Write;
+
+        let vocab = self
+            .get_vocab_with_indices()
+            .ok_or(Error::ModelHasNoVocab)?;
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        for (idx, word) in vocab {
+            if with_indices {
+                writeln!(out, "{}\t{}", word, *idx)?;
+            } else {
+                writeln!(out, "{}", word)?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Builds a Rust-side cache of the vocabulary used by all word lookups
+    ///
+    /// Without the cache, every lookup copies the word into a C++ string and calls into KenLM.
+    /// With it, words are resolved by a [HashMap] over the whole vocabulary, words missing from it
+    /// get [Model::not_found_idx] without calling into C++. The cache is built over the stored
+    /// vocabulary, returns [Error::ModelHasNoVocab] if the model was loaded without `store_vocab`.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let mut model = Model::new("test_data/test.bin", true).unwrap();
+    /// let expected = model.score_sentence(&["i", "have", "a", "good", "deal"], true, true);
+    /// model.build_lookup_cache().unwrap();
+    ///
+    /// let score = model.score_sentence(&["i", "have", "a", "good", "deal"], true, true);
+    /// assert_eq!(score, expected);
+    /// ```
+    pub fn build_lookup_cache(&mut self) -> Result<(), Error> {
+        let cache = self
+            .get_vocab_with_indices()
+            .ok_or(Error::ModelHasNoVocab)?
+            .map(|(idx, word)| (word.to_string(), idx))
+            .collect();
+        self.lookup_cache = Some(cache);
+        Ok(())
+    }
+
+    /// Builds the index used by `complete`
+    ///
+    /// The index is built over the stored vocabulary, returns [Error::ModelHasNoVocab] if the
+    /// model was loaded without `store_vocab`.
+    pub fn build_completion_index(&mut self) -> Result<(), Error> {
+        let vocab = self.vocab.as_deref().ok_or(Error::ModelHasNoVocab)?;
+        let entries = vocab
+            .iter()
+            .map(|word| (word.as_str(), *self.vocab_index(word)))
+            .collect::<Vec<_>>();
+        self.completion_index = Some(CompletionIndex::new(entries)?);
+        Ok(())
+    }
+
+    /// Returns the indices of all words starting with `prefix`
+    ///
+    /// The words are visited in lexicographic order, returns None if `build_completion_index`
+    /// was not called.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let mut model = Model::new("test_data/test.bin", true).unwrap();
+    /// model.build_completion_index().unwrap();
+    ///
+    /// let completions = model.complete("s").unwrap().count();
+    /// assert_eq!(completions, 3);
+    /// ```
+    pub fn complete(&self, prefix: &str) -> Option<impl Iterator<Item = WordIdx>> {
+        let index = self.completion_index.as_ref()?;
+        Some(
+            index
+                .complete(prefix)
+                .into_iter()
+                .map(|idx| WordIdx(c_uint(idx))),
+        )
+    }
+
+    /// Get the word of `index`, the reverse of `get_word_idx`
+    ///
+    /// The word is looked up in the stored vocab, returns None if the model was loaded without
+    /// `store_vocab` or `index` is not part of the vocab.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", true).unwrap();
+    ///
+    /// let index = model.get_word_idx("have");
+    /// assert_eq!(model.get_word(index), Some("have"));
+    /// assert_eq!(model.get_word(model.eos_idx()), Some("</s>"));
+    /// ```
+    pub fn get_word(&self, index: WordIdx) -> Option<&str> {
+        let vocab = self.vocab.as_ref()?;
+        let position = match &self.vocab_positions {
+            Some(positions) => *positions.get(*index as usize)? as usize,
+            None => *index as usize,
+        };
+        vocab.get(position).map(String::as_str)
+    }
+
+    /// Return the order of this ngram model
+    pub fn get_order(&self) -> u8 {
+        self.inner.Order()
+    }
+    /// Initializes `state` to the `<s>` (beginning of sentence) context
+    ///
+    /// Use this if you want to take the beginning of sentences into account.
+    pub fn fill_state_with_bos_context(&self, state: &mut State) {
+        state.copy_from(&self.bos_state);
+    }
+
+    /// Initializes `state` to an empty context.
+    ///
+    /// Use this function if you want to score without `<s>` (beginning of sentence) or discard context
+    pub fn fill_state_with_null_context(&self, state: &mut State) {
+        state.copy_from(&self.null_state);
+    }
+}
+
+/// Position of each index in `indices`, `None` if every index is at its own position
+///
+/// Indices which no word was enumerated with map to `u32::MAX`, which is past every position.
+fn vocab_positions(indices: &[WordIdx]) -> Option<Vec<u32>> {
+    if indices
+        .iter()
+        .enumerate()
+        .all(|(position, index)| **index as usize == position)
+    {
+        return None;
+    }
+    let len = indices.iter().map(|index| **index as usize + 1).max()?;
+    let mut positions = vec![u32::MAX; len];
+    for (position, index) in indices.iter().enumerate() {
+        positions[**index as usize] = position as u32;
+    }
+    Some(positions)
+}
+
+/// Spelling suggestion returned by [Model::suggest]
+#[derive(Debug, Clone, Copy)]
+pub struct Suggestion<'a> {
+    pub word: &'a str,
+    pub word_idx: WordIdx,
+    /// Edit distance between the query and `word`
+    pub distance: usize,
+    /// Unigram probability of `word` in log10-space
+    pub log_prob: f32,
+}
+
+/// Index into the vocabulary of a [Model]
+///
+/// `WordIdx` is a wrapper around the vocabulary index type [autocxx::c_uint].
+/// A [autocxx::c_uint] as a newtype wrapper around a [core::ffi::c_uint].
+/// It seems to be the case that this is almost always a [u32].
+///
+/// Indices compare and hash like the underlying integer, so they can be used as map keys. With
+/// the `serde` feature, they are serialized as plain integers.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "u32", into = "u32")
+)]
+pub struct WordIdx(c_uint);
+
+impl WordIdx {
+    /// Wraps a raw vocabulary index
+    ///
+    /// The index is not checked against any vocabulary, indices not known to the model are
+    /// scored like `<unk>`.
+    pub fn from_u32(index: u32) -> Self {
+        WordIdx(c_uint(index))
+    }
+
+    /// The raw vocabulary index
+    pub fn as_u32(&self) -> u32 {
+        self.0 .0
+    }
+}
+
+impl From<u32> for WordIdx {
+    fn from(index: u32) -> Self {
+        WordIdx::from_u32(index)
+    }
+}
+
+impl From<WordIdx> for u32 {
+    fn from(index: WordIdx) -> Self {
+        index.as_u32()
+    }
+}
+
+impl PartialEq for WordIdx {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_u32() == other.as_u32()
+    }
+}
+
+impl Eq for WordIdx {}
+
+impl PartialOrd for WordIdx {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WordIdx {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_u32().cmp(&other.as_u32())
+    }
+}
+
+impl std::hash::Hash for WordIdx {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_u32().hash(state);
+    }
+}
+
+impl Deref for WordIdx {
+    type Target = u32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0 .0
+    }
+}
+
+/// The `State` is the prefix storage
+///
+/// `State` is a wrapper around the C++ pod-struct `lm::ngram::State`.
+/// It tracks the words in the prefix along backoff and currently active length. The struct is
+/// held by value, so cloning is a plain copy which cannot fail and creating or dropping states
+/// never touches the allocator.
+#[derive(Debug, Clone)]
+pub struct State(bridge::lm::ngram::State);
+
+// SAFETY: `lm::ngram::State` is a plain struct of words, backoffs and a length without any
+// pointers, mutation requires `&mut State`.
+unsafe impl Send for State {}
+unsafe impl Sync for State {}
+
+impl State {
+    fn new_for_model(model: &CxxModel) -> Self {
+        let size = std::mem::size_of::<bridge::lm::ngram::State>();
+        let model_size = model.StateSize();
+        assert_eq!(size, model_size, "size of bridge::lm::ngram::State: {size} does not match size returned by StateSize: {model_size}");
+        // SAFETY: `lm::ngram::State` only holds words, backoffs and a length, for which all zero
+        // bytes are valid, it is the empty state KenLM fills in
+        Self(unsafe { std::mem::zeroed() })
+    }
+
+    /// Constructs the empty context state of `model`
+    ///
+    /// Same as [Model::null_state], use [Model::fill_state_with_null_context] to reset an
+    /// existing state in place.
+    pub fn null(model: &Model) -> Self {
+        model.null_state()
+    }
+
+    /// Constructs the `<s>` (beginning of sentence) state of `model`
+    ///
+    /// Same as [Model::bos_state], use [Model::fill_state_with_bos_context] to reset an existing
+    /// state in place.
+    /// ```
+    /// use kenlm_rs::{Model, State};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let mut in_state = State::bos(&model);
+    /// let mut out_state = State::null(&model);
+    /// let score = model.score_word_given_state(&mut in_state, &mut out_state, "i");
+    /// assert_eq!(score, model.score_sentence(&["i"], true, false));
+    /// ```
+    pub fn bos(model: &Model) -> Self {
+        model.bos_state()
+    }
+
+    /// Copies this state, never fails
+    ///
+    /// Kept for callers which handle clone failures, [Clone] is infallible as well.
+    pub fn try_clone(&self) -> Result<State, Error> {
+        Ok(self.clone())
+    }
+
+    /// Constructs a state for `model` holding `context`, most recent word first
+    fn from_context(model: &Model, context: &[WordIdx], backoffs: &[f32]) -> Self {
+        let mut state = model.new_state();
+        let s = &mut state.0;
+        for (i, (word, backoff)) in context.iter().zip(backoffs).enumerate() {
+            s.words[i] = word.0;
+            s.backoff[i] = *backoff;
+        }
+        s.length = context.len() as u8;
+        state
+    }
+
+    /// Overwrites this state with a copy of `other`
+    fn copy_from(&mut self, other: &State) {
+        self.0.clone_from(&other.0);
+    }
+
+    /// Untyped pointer to the C++ state as expected by the virtual model interface
+    fn as_ptr(&self) -> *const autocxx::c_void {
+        &self.0 as *const bridge::lm::ngram::State as *const autocxx::c_void
+    }
+
+    /// Untyped mutable pointer to the C++ state as expected by the virtual model interface
+    fn as_mut_ptr(&mut self) -> *mut autocxx::c_void {
+        &mut self.0 as *mut bridge::lm::ngram::State as *mut autocxx::c_void
+    }
+
+    /// Fetches the words currently stored in this [State]
+    ///
+    /// Only the first `len()` words, most recent word first, are part of the active context.
+    pub fn words(&self) -> Vec<WordIdx> {
         self.0.words.iter().map(|c| WordIdx(*c)).collect::<Vec<_>>()
     }
+
+    /// Number of words in the active context
+    pub fn len(&self) -> usize {
+        self.0.Length() as usize
+    }
+
+    /// Whether the context is empty, e.g. for the null context
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Backoff weights of the words in the active context in log10-space, most recent word first
+    ///
+    /// These are the weights applied if the next word's n-gram is not found with the full context.
+    pub fn backoffs(&self) -> &[f32] {
+        &self.0.backoff[..self.len()]
+    }
+
+    /// Shortens the context to the `max_len` most recent words
+    ///
+    /// Words further back are forgotten, states longer than `max_len` are left unchanged. The
+    /// backoffs of the remaining words stay valid since each belongs to the context ending in
+    /// its word.
+    pub fn truncate(&mut self, max_len: usize) {
+        if max_len < self.len() {
+            self.0.length = max_len as u8;
+        }
+    }
+
+    /// Displays the context words of this state in reading order
+    ///
+    /// Words are resolved through the vocab stored in `model`, indices which cannot be resolved,
+    /// e.g. because the model was loaded without `store_vocab`, are printed as `#<index>`.
+    /// ```
+    /// use kenlm_rs::Model;
+    /// let model = Model::new("test_data/test.bin", true).unwrap();
+    ///
+    /// let state = model.state_from_context(&["<s>", "i"]);
+    /// assert_eq!(state.display(&model).to_string(), "<s> i");
+    /// ```
+    pub fn display<'a>(&'a self, model: &'a Model) -> impl std::fmt::Display + 'a {
+        StateDisplay { state: self, model }
+    }
+}
+
+struct StateDisplay<'a> {
+    state: &'a State,
+    model: &'a Model,
 }
 
-/// Panics if Self::0 contains a null-pointer
-impl Clone for State {
-    fn clone(&self) -> Self {
-        Self(self.0.as_ref().unwrap().clone().within_unique_ptr())
+impl std::fmt::Display for StateDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let context = &self.state.0.words[..self.state.len()];
+        for (i, &index) in context.iter().rev().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            match self.model.get_word(WordIdx(index)) {
+                Some(word) => f.write_str(word)?,
+                None => write!(f, "#{}", index.0)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// States are equal if they hold the same context words, like `operator==` of KenLM's State
+///
+/// Backoffs are not compared, they are determined by the words. This allows to recombine
+/// hypotheses with identical states in beam search.
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        let length = self.len();
+        length == other.len()
+            && self.0.words[..length]
+                .iter()
+                .zip(&other.0.words[..length])
+                .all(|(w1, w2)| w1.0 == w2.0)
+    }
+}
+
+impl Eq for State {}
+
+/// Hashes the context words with KenLM's `hash_value`, consistent with [PartialEq]
+impl std::hash::Hash for State {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(bridge::lm::base::StateHash(&self.0));
     }
 }
 
@@ -311,7 +1901,12 @@ impl std::fmt::Debug for bridge::lm::ngram::State {
 
 #[cfg(test)]
 mod test {
-    use super::{Error, Model};
+    use std::num::NonZeroUsize;
+
+    use super::{
+        ConfigBuilder, Error, FallbackRule, LoadMethod, Model, ModelType, OovFallback, ResolvedBy,
+        ScoreOptions, SpecialTokens, State, TokenClasses, TokenScore, WordIdx,
+    };
     pub const TEST_SENTENCE: &[&str] = &[
         "i", "have", "a", "good", "deal", "of", "will", "you", "remember", "and", "what", "i",
         "have", "set", "my", "mind", "upon", "no", "doubt", "i", "shall", "some", "day", "achieve",
@@ -450,32 +2045,208 @@ mod test {
     }
 
     #[test]
-    fn score_longer_sentence_with_oov_works() {
-        let model = Model::new("test_data/test.bin", false).expect("should exist");
-        let score = model.score_sentence(TEST_WITH_OOV, false, false);
-        approx::assert_abs_diff_eq!(-7.1395426f32, score, epsilon = f32::EPSILON);
+    fn score_longer_sentence_with_oov_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let score = model.score_sentence(TEST_WITH_OOV, false, false);
+        approx::assert_abs_diff_eq!(-7.1395426f32, score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn score_longer_sentence_bos_with_oov_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let score = model.score_sentence(TEST_WITH_OOV, true, false);
+        approx::assert_abs_diff_eq!(-6.0499362f32, score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn score_sentence_bos_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let score = model.score_sentence(&["some"], true, false);
+        approx::assert_abs_diff_eq!(-1.6719012f32, score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn score_sentence_bos_eos_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let score = model.score_sentence(&["some"], true, true);
+        approx::assert_abs_diff_eq!(-3.3438025f32, score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn score_sentence_excluding_oov_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let options = ScoreOptions::new(false, false).exclude_oov(true);
+        let score = model.score_sentence_with_options(TEST_WITH_OOV, options);
+        let without_oov = model.score_sentence(TEST_SENTENCE, false, false);
+        approx::assert_abs_diff_eq!(without_oov, score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn score_sentence_bos_eos_excluding_oov_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let options = ScoreOptions::new(true, true).exclude_oov(true);
+        let score = model.score_sentence_with_options(TEST_WITH_OOV, options);
+        approx::assert_abs_diff_eq!(-5.155989f32, score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn score_sentence_detailed_reports_ngram_lengths() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let scores =
+            model.score_sentence_detailed(&["i", "have", "toast"], ScoreOptions::new(true, true));
+        let lengths = scores.iter().map(|s| s.ngram_length).collect::<Vec<_>>();
+        assert_eq!(lengths, &[2, 3, 1, 1]);
+        let oovs = scores.iter().map(|s| s.oov).collect::<Vec<_>>();
+        assert_eq!(oovs, &[false, false, true, false]);
+        let total = scores
+            .iter()
+            .map(|TokenScore { log_prob, .. }| log_prob)
+            .sum::<f32>();
+        approx::assert_abs_diff_eq!(
+            model.score_sentence(&["i", "have", "toast"], true, true),
+            total,
+            epsilon = f32::EPSILON
+        );
+    }
+
+    #[test]
+    fn token_classes_apply_before_lookup() {
+        let mut model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert!(model.get_word_idx_opt("42").is_none());
+        model.set_token_classes(TokenClasses::new().add(r"\d+", "some").unwrap());
+        assert_eq!(*model.get_word_idx("42"), *model.get_word_idx("some"));
+        approx::assert_abs_diff_eq!(
+            model.score_sentence(&["some"], true, true),
+            model.score_sentence(&["42"], true, true),
+            epsilon = f32::EPSILON
+        );
+    }
+
+    #[test]
+    fn score_final_word_matches_score_word_given_state() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut in_state = model.new_state();
+        let mut out_state = model.new_state();
+        model.fill_state_with_bos_context(&mut in_state);
+        model.score_word_given_state(&mut in_state, &mut out_state, "i");
+        std::mem::swap(&mut in_state, &mut out_state);
+        let score = model.score_word_given_state(&mut in_state, &mut out_state, "have");
+        let final_score = model.score_final_word(&in_state, "have");
+        approx::assert_abs_diff_eq!(-0.16921267f32, final_score, epsilon = f32::EPSILON);
+        approx::assert_abs_diff_eq!(score, final_score, epsilon = f32::EPSILON);
+    }
+
+    #[test]
+    fn cached_states_match_filled_states() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let bos = model.bos_state();
+        assert_eq!(bos.0.Length(), 1);
+        assert_eq!(bos.words()[0].0 .0, 1);
+        assert_eq!(model.null_state().0.Length(), 0);
+
+        let mut in_state = model.bos_state();
+        let mut out_state = model.new_state();
+        let score = model.score_word_given_state(&mut in_state, &mut out_state, "some");
+        approx::assert_abs_diff_eq!(
+            model.score_sentence(&["some"], true, false),
+            score,
+            epsilon = f32::EPSILON
+        );
+    }
+
+    #[test]
+    fn oov_fallback_chain_resolves_words() {
+        let mut model = Model::new("test_data/test.bin", false).expect("should exist");
+        let some = model.get_word_idx("some");
+        assert_eq!(model.resolve_word("Some!").1, ResolvedBy::Unknown);
+
+        model.set_oov_fallback(OovFallback::new(vec![
+            FallbackRule::Exact,
+            FallbackRule::Lowercase,
+            FallbackRule::StripPunctuation,
+            FallbackRule::Placeholder(TokenClasses::new().add(r"\d+", "day").unwrap()),
+        ]));
+        let (idx, resolved_by) = model.resolve_word("some");
+        assert_eq!((*idx, resolved_by), (*some, ResolvedBy::Exact));
+        let (idx, resolved_by) = model.resolve_word("Some");
+        assert_eq!((*idx, resolved_by), (*some, ResolvedBy::Lowercase));
+        let (idx, resolved_by) = model.resolve_word("some!");
+        assert_eq!((*idx, resolved_by), (*some, ResolvedBy::StripPunctuation));
+        let (idx, resolved_by) = model.resolve_word("42");
+        assert_eq!((*idx, resolved_by), (7, ResolvedBy::Placeholder));
+        let (idx, resolved_by) = model.resolve_word("Some!");
+        assert_eq!((*idx, resolved_by), (0, ResolvedBy::Unknown));
+
+        let scores = model.score_sentence_detailed(&["Some", "toast"], ScoreOptions::default());
+        assert_eq!(scores[0].resolved_by, ResolvedBy::Lowercase);
+        assert_eq!(scores[1].resolved_by, ResolvedBy::Unknown);
+
+        // in-vocab words resolve to themselves without FallbackRule::Exact in the chain
+        model.set_oov_fallback(OovFallback::new(vec![FallbackRule::Lowercase]));
+        let (idx, resolved_by) = model.resolve_word("some");
+        assert_eq!((*idx, resolved_by), (*some, ResolvedBy::Exact));
+        let (idx, resolved_by) = model.resolve_word("Some");
+        assert_eq!((*idx, resolved_by), (*some, ResolvedBy::Lowercase));
+    }
+
+    #[test]
+    fn suggests_in_vocab_words() {
+        let mut model = Model::new("test_data/test.bin", true).expect("should exist");
+        assert!(model.suggest("hav", 1).is_none());
+        model.build_suggestion_index(1).unwrap();
+        let suggestions = model.suggest("hav", 1).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].word, "have");
+        assert_eq!(*suggestions[0].word_idx, *model.get_word_idx("have"));
+        assert_eq!(suggestions[0].distance, 1);
+        let suggestions = model.suggest("mo", 1).unwrap();
+        let words = suggestions.iter().map(|s| s.word).collect::<Vec<_>>();
+        assert_eq!(words, &["no", "my"]);
     }
 
     #[test]
-    fn score_longer_sentence_bos_with_oov_works() {
-        let model = Model::new("test_data/test.bin", false).expect("should exist");
-        let score = model.score_sentence(TEST_WITH_OOV, true, false);
-        approx::assert_abs_diff_eq!(-6.0499362f32, score, epsilon = f32::EPSILON);
+    fn suggestion_index_requires_vocab() {
+        let mut model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert!(matches!(
+            model.build_suggestion_index(1),
+            Err(Error::ModelHasNoVocab)
+        ));
     }
 
     #[test]
-    fn score_sentence_bos_works() {
-        let model = Model::new("test_data/test.bin", false).expect("should exist");
-        let score = model.score_sentence(&["some"], true, false);
-        approx::assert_abs_diff_eq!(-1.6719012f32, score, epsilon = f32::EPSILON);
+    fn completes_prefixes() {
+        let mut model = Model::new("test_data/test.bin", true).expect("should exist");
+        assert!(model.complete("s").is_none());
+        model.build_completion_index().unwrap();
+        let completions = model
+            .complete("s")
+            .unwrap()
+            .map(|idx| *idx)
+            .collect::<Vec<_>>();
+        let expected = ["set", "shall", "some"].map(|w| *model.get_word_idx(w));
+        assert_eq!(completions, expected);
+        assert_eq!(model.complete("<").unwrap().count(), 3);
+        assert_eq!(model.complete("toast").unwrap().count(), 0);
     }
 
     #[test]
-    fn score_sentence_bos_eos_works() {
-        let model = Model::new("test_data/test.bin", false).expect("should exist");
-        let score = model.score_sentence(&["some"], true, true);
-        approx::assert_abs_diff_eq!(-3.3438025f32, score, epsilon = f32::EPSILON);
+    fn loads_with_sorted_vocab() {
+        let model = Model::new_with_sorted_vocab("test_data/test.bin").expect("should exist");
+        let vocab = model.get_vocab().unwrap();
+        let mut expected = vocab.to_vec();
+        expected.sort();
+        assert_eq!(vocab, expected);
+        assert_eq!(&vocab[..3], &["</s>", "<s>", "<unk>"]);
+        let indices = model.get_vocab_indices().unwrap();
+        assert_eq!(vocab.len(), indices.len());
+        for (word, idx) in vocab.iter().zip(indices) {
+            assert_eq!(**idx, *model.get_word_idx(word));
+        }
+        let probing = Model::new_with_sorted_vocab("test_data/carol_probing_bigram.bin").unwrap();
+        let trie = Model::new_with_sorted_vocab("test_data/carol.bin").unwrap();
+        assert_eq!(probing.get_vocab(), trie.get_vocab());
     }
+
     struct Example {
         input_word: &'static str,
         word_idx: u32,
@@ -591,4 +2362,861 @@ mod test {
             approx::assert_abs_diff_eq!(expected_score, score, epsilon = f32::EPSILON);
         }
     }
+
+    #[test]
+    fn score_channel_scores_on_workers() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let (sentence_tx, sentence_rx) = std::sync::mpsc::sync_channel(1);
+        let (score_tx, score_rx) = std::sync::mpsc::sync_channel(1);
+        let producer = std::thread::spawn(move || {
+            for sentence in [TEST_SENTENCE, TEST_WITH_OOV] {
+                sentence_tx.send(sentence.join(" ")).unwrap();
+            }
+        });
+        let consumer = std::thread::spawn(move || score_rx.iter().collect::<Vec<_>>());
+        let workers = NonZeroUsize::new(2).unwrap();
+        model.score_channel(
+            sentence_rx,
+            score_tx,
+            ScoreOptions::new(true, true),
+            workers,
+        );
+        producer.join().unwrap();
+        let mut scores = consumer.join().unwrap();
+        scores.sort_by_key(|score| score.sentence != TEST_SENTENCE.join(" "));
+
+        assert_eq!(scores.len(), 2);
+        for (score, sentence) in scores.iter().zip([TEST_SENTENCE, TEST_WITH_OOV]) {
+            assert_eq!(score.sentence, sentence.join(" "));
+            approx::assert_abs_diff_eq!(
+                score.log_prob,
+                model.score_sentence(sentence, true, true),
+                epsilon = f32::EPSILON
+            );
+        }
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn explain_reports_backoffs() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut state = model.bos_state();
+        let mut out_state = model.new_state();
+        for word in ["i", "have"] {
+            let trace = model.explain_score(&state, word);
+            assert!(trace.backoffs.is_empty());
+            model.score_word_given_state(&mut state, &mut out_state, word);
+            std::mem::swap(&mut state, &mut out_state);
+        }
+        let trace = model.explain_score(&model.bos_state(), "i");
+        assert_eq!(
+            trace.ngram.iter().map(|w| **w).collect::<Vec<_>>(),
+            &[1, 20]
+        );
+
+        let trace = model.explain_score(&state, "toast");
+        assert!(trace.oov);
+        assert_eq!(trace.ngram.iter().map(|w| **w).collect::<Vec<_>>(), &[0]);
+        let contexts = trace
+            .backoffs
+            .iter()
+            .map(|b| b.context.iter().map(|w| **w).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(contexts, vec![vec![13], vec![20, 13]]);
+        for backoff in &trace.backoffs {
+            approx::assert_abs_diff_eq!(backoff.backoff, -0.30103, epsilon = 1e-6);
+        }
+        approx::assert_abs_diff_eq!(trace.log_prob, -2.264818, epsilon = f32::EPSILON);
+        approx::assert_abs_diff_eq!(trace.ngram_log_prob, -1.6627579, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn memory_footprint_matches_model_type() {
+        let model = Model::new("test_data/carol.bin", false).expect("should exist");
+        let footprint = model.memory_footprint().unwrap();
+        assert_eq!(footprint.orders.len(), 3);
+        assert_eq!(footprint.total(), 455620);
+
+        let model = Model::new("test_data/carol_probing_bigram.bin", false).expect("should exist");
+        assert_eq!(model.memory_footprint().unwrap().total(), 445076);
+
+        // ARPA files are loaded with the probing multiplier of the config
+        let model = Model::builder("test_data/arpa/lm_small.arpa")
+            .config(ConfigBuilder::new().probing_multiplier(2.))
+            .build()
+            .expect("should load");
+        assert_eq!(
+            model.memory_footprint().unwrap().total(),
+            model.memory_usage().model
+        );
+    }
+
+    #[test]
+    fn estimate_memory_matches_footprint() {
+        let model = Model::new("test_data/carol.bin", false).expect("should exist");
+        let estimate = Model::builder("test_data/carol.bin")
+            .estimate_memory(model.model_type())
+            .unwrap();
+        assert_eq!(estimate, model.memory_footprint().unwrap().total());
+        assert!(matches!(
+            Model::builder("test_data/missing.bin").estimate_memory(ModelType::Probing),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn memory_usage_covers_model_and_vocab() {
+        let model = Model::new("test_data/carol.bin", false).expect("should exist");
+        let usage = model.memory_usage();
+        assert_eq!(usage.model, 455620);
+        assert_eq!(usage.vocab, 0);
+
+        let model = Model::new("test_data/carol.bin", true).expect("should exist");
+        let usage = model.memory_usage();
+        assert_eq!(usage.model, 455620);
+        assert!(usage.vocab > 0);
+        assert_eq!(usage.total(), usage.model + usage.vocab);
+    }
+
+    #[test]
+    fn memory_budget_guards_loading() {
+        for file_name in ["test_data/carol.bin", "test_data/arpa/lm_small.arpa"] {
+            let required = Model::new(file_name, false)
+                .expect("should exist")
+                .memory_usage()
+                .model;
+            assert!(Model::builder(file_name)
+                .max_memory_bytes(required)
+                .build()
+                .is_ok());
+            assert!(matches!(
+                Model::builder(file_name)
+                    .max_memory_bytes(required - 1)
+                    .build(),
+                Err(Error::MemoryBudgetExceeded { budget, .. }) if budget == required - 1
+            ));
+            // the vocab copy counts towards the budget as well
+            assert!(matches!(
+                Model::builder(file_name)
+                    .store_vocab(true)
+                    .max_memory_bytes(required)
+                    .build(),
+                Err(Error::MemoryBudgetExceeded { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn kenlm_exceptions_become_errors() {
+        let result = Model::builder("test_data/arpa/lm_truncated.arpa")
+            .config(crate::ConfigBuilder::new().messages(false))
+            .build();
+        assert!(matches!(result, Err(Error::KenLMError(message)) if !message.is_empty()));
+    }
+
+    #[test]
+    fn unk_score_and_vocab_size() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        approx::assert_abs_diff_eq!(model.unk_score(), -1.6627579, epsilon = f32::EPSILON);
+        assert_eq!(model.vocab_size(), 24);
+
+        let model = Model::new("test_data/carol.bin", false).expect("should exist");
+        assert_eq!(model.vocab_size(), 4415);
+    }
+
+    #[test]
+    fn full_score_reports_ngram_length_and_oov() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut in_state = model.bos_state();
+        let mut out_state = model.new_state();
+        let mut expected_state = model.new_state();
+        let mut total = 0.;
+        for (word, detailed) in TEST_WITH_OOV
+            .iter()
+            .zip(model.score_sentence_detailed(TEST_WITH_OOV, ScoreOptions::new(true, false)))
+        {
+            let full = model.full_score_word_given_state(&mut in_state, &mut out_state, word);
+            let score = model.score_word_given_state(&mut in_state, &mut expected_state, word);
+            std::mem::swap(&mut in_state, &mut out_state);
+            approx::assert_abs_diff_eq!(full.log_prob, score, epsilon = f32::EPSILON);
+            assert_eq!(full.ngram_length, detailed.ngram_length);
+            assert_eq!(full.oov, detailed.oov);
+            total += full.log_prob;
+        }
+        approx::assert_abs_diff_eq!(
+            total,
+            model.score_sentence(TEST_WITH_OOV, true, false),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn perplexity_works() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let sentence = &["i", "have", "a", "good", "deal"];
+        let expected = 10f64.powf(3.0595014 / 6.);
+        approx::assert_abs_diff_eq!(
+            model.perplexity(sentence, true, true).unwrap(),
+            expected,
+            epsilon = 1e-4
+        );
+
+        let corpus: [&[&str]; 2] = [sentence, TEST_WITH_OOV];
+        let log_prob = model.score_sentence(sentence, true, true)
+            + model.score_sentence(TEST_WITH_OOV, true, true);
+        let tokens = sentence.len() + TEST_WITH_OOV.len() + 2;
+        approx::assert_abs_diff_eq!(
+            model.corpus_perplexity(corpus, true, true).unwrap(),
+            10f64.powf(-f64::from(log_prob) / tokens as f64),
+            epsilon = 1e-3
+        );
+
+        let empty: [&[&str]; 1] = [&[]];
+        assert_eq!(model.perplexity(empty[0], true, false), None);
+        assert_eq!(model.corpus_perplexity(empty, true, false), None);
+        assert_eq!(
+            model.corpus_perplexity(Vec::<Vec<String>>::new(), true, true),
+            None
+        );
+        assert!(model.corpus_perplexity(empty, true, true).is_some());
+    }
+
+    #[test]
+    fn score_sentences_matches_score_sentence() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let sentences: [&[&str]; 4] = [TEST_SENTENCE, TEST_WITH_OOV, &[], TEST_SENTENCE];
+        for options in [
+            ScoreOptions::new(true, true),
+            ScoreOptions::new(false, true).exclude_oov(true),
+        ] {
+            let scores = model.score_sentences(sentences, options);
+            assert_eq!(scores.len(), sentences.len());
+            let owned = sentences
+                .iter()
+                .map(|sentence| sentence.iter().map(|word| word.to_string()).collect())
+                .collect::<Vec<Vec<String>>>();
+            assert_eq!(model.score_sentences(&owned, options), scores);
+            for (score, sentence) in scores.iter().zip(sentences) {
+                approx::assert_abs_diff_eq!(
+                    *score,
+                    model.score_sentence_with_options(sentence, options),
+                    epsilon = f32::EPSILON
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn score_sentence_with_oov_reports_positions() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        for options in [
+            ScoreOptions::new(true, true),
+            ScoreOptions::new(false, false).exclude_oov(true),
+        ] {
+            let score = model.score_sentence_with_oov(TEST_WITH_OOV, options);
+            assert_eq!(score.oov_positions, &[TEST_WITH_OOV.len() - 1]);
+            approx::assert_abs_diff_eq!(
+                score.log_prob,
+                model.score_sentence_with_options(TEST_WITH_OOV, options),
+                epsilon = f32::EPSILON
+            );
+        }
+        let score = model.score_sentence_with_oov(TEST_SENTENCE, ScoreOptions::new(true, true));
+        assert_eq!(score.oov_count(), 0);
+    }
+
+    #[test]
+    fn score_indices_matches_score_sentence() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let indices = TEST_WITH_OOV
+            .iter()
+            .map(|w| model.get_word_idx(w))
+            .collect::<Vec<_>>();
+        for (bos, eos) in [(true, true), (false, true), (true, false), (false, false)] {
+            approx::assert_abs_diff_eq!(
+                model.score_sentence_indices(&indices, bos, eos),
+                model.score_sentence(TEST_WITH_OOV, bos, eos),
+                epsilon = f32::EPSILON
+            );
+        }
+
+        // scoring in chunks carries the state over
+        let mut in_state = model.bos_state();
+        let mut out_state = model.new_state();
+        let (head, tail) = indices.split_at(7);
+        let score = model.score_indices(&mut in_state, &mut out_state, head)
+            + model.score_indices(&mut in_state, &mut out_state, tail);
+        approx::assert_abs_diff_eq!(
+            score,
+            model.score_sentence(TEST_WITH_OOV, true, false),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn score_ngram_matches_score_sentence() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        approx::assert_abs_diff_eq!(
+            model.score_ngram(&["<s>", "i"], "have"),
+            -0.16921267,
+            epsilon = f32::EPSILON
+        );
+        let sentence = &["i", "have", "a", "good", "deal"];
+        let total = model.score_ngram(&["<s>"], "i")
+            + model.score_ngram(&["<s>", "i"], "have")
+            + model.score_ngram(&["i", "have"], "a")
+            + model.score_ngram(&["have", "a"], "good")
+            + model.score_ngram(&["a", "good"], "deal")
+            + model.score_ngram(&["good", "deal"], "</s>");
+        approx::assert_abs_diff_eq!(
+            total,
+            model.score_sentence(sentence, true, true),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn special_token_indices() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert_eq!(*model.bos_idx(), *model.get_word_idx("<s>"));
+        assert_eq!(*model.eos_idx(), *model.get_word_idx("</s>"));
+        assert_eq!(*model.unk_idx(), *model.get_word_idx("<unk>"));
+        assert_eq!(*model.not_found_idx(), *model.get_word_idx("toast"));
+        assert_eq!(*model.bos_state().words()[0], *model.bos_idx());
+    }
+
+    #[test]
+    fn model_type_matches_header() {
+        let model = Model::new("test_data/carol.bin", false).expect("should exist");
+        assert_eq!(model.model_type(), ModelType::Trie);
+        let model = Model::new("test_data/carol_probing_bigram.bin", false).expect("should exist");
+        assert_eq!(model.model_type(), ModelType::Probing);
+        let model = Model::new("test_data/arpa/lm_small.arpa", false).expect("should exist");
+        assert_eq!(model.model_type(), ModelType::Probing);
+    }
+
+    #[test]
+    fn builder_combines_options() {
+        let model = Model::builder("test_data/test.bin")
+            .with_load_method(LoadMethod::Read)
+            .store_vocab(true)
+            .sort_vocab(true)
+            .build()
+            .expect("should exist");
+        let vocab = model.get_vocab().unwrap();
+        assert!(vocab.windows(2).all(|w| w[0] <= w[1]));
+        approx::assert_abs_diff_eq!(
+            model.score_sentence(TEST_SENTENCE, true, true),
+            Model::new("test_data/test.bin", false)
+                .unwrap()
+                .score_sentence(TEST_SENTENCE, true, true),
+            epsilon = f32::EPSILON
+        );
+        assert!(matches!(
+            Model::builder("test_data/test_no_vocab.bin")
+                .store_vocab(true)
+                .build(),
+            Err(Error::ModelHasNoVocab)
+        ));
+    }
+
+    #[test]
+    fn score_sentence_with_state_carries_context() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let (head, tail) = TEST_WITH_OOV.split_at(12);
+        let (head_score, mut state) = model.score_sentence_with_state(head, true, false);
+        let tail_score = model.continue_sentence(&mut state, tail, true);
+        approx::assert_abs_diff_eq!(
+            head_score + tail_score,
+            model.score_sentence(TEST_WITH_OOV, true, true),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn model_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Model>();
+        assert_send_sync::<super::State>();
+
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let expected = model.score_sentence(TEST_WITH_OOV, true, true);
+        std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..100)
+                            .map(|_| model.score_sentence(TEST_WITH_OOV, true, true))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                for score in handle.join().unwrap() {
+                    assert_eq!(score, expected);
+                }
+            }
+        });
+
+        let model = std::sync::Arc::new(model);
+        let shared = std::sync::Arc::clone(&model);
+        let score = std::thread::spawn(move || shared.score_sentence(TEST_SENTENCE, true, true))
+            .join()
+            .unwrap();
+        assert_eq!(score, model.score_sentence(TEST_SENTENCE, true, true));
+    }
+
+    #[test]
+    fn score_sentence_accepts_any_tokens() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let expected = model.score_sentence(TEST_WITH_OOV, true, true);
+        let owned = TEST_WITH_OOV
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(model.score_sentence(&owned, true, true), expected);
+        assert_eq!(model.score_sentence(owned.clone(), true, true), expected);
+        let joined = TEST_WITH_OOV.join(" ");
+        assert_eq!(
+            model.score_sentence(joined.split_ascii_whitespace(), true, true),
+            expected
+        );
+        assert_eq!(
+            model
+                .score_sentence_detailed(owned.iter(), ScoreOptions::new(true, true))
+                .len(),
+            TEST_WITH_OOV.len() + 1
+        );
+    }
+
+    #[test]
+    fn score_document_accumulates_in_f64() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let options = ScoreOptions::new(true, true);
+        let sentences = [TEST_SENTENCE, TEST_WITH_OOV].repeat(500);
+        let expected = sentences
+            .iter()
+            .flat_map(|sentence| model.score_sentence_detailed(*sentence, options))
+            .map(|score| f64::from(score.log_prob))
+            .sum::<f64>();
+        let score = model.score_document(sentences.iter().copied(), options);
+        approx::assert_abs_diff_eq!(score, expected, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn states_with_same_context_are_equal() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut out_state = model.new_state();
+
+        let mut first = model.bos_state();
+        model.score_word_given_state(&mut first, &mut out_state, "i");
+        std::mem::swap(&mut first, &mut out_state);
+        let mut second = first.clone();
+        assert_eq!(first, second);
+
+        model.score_word_given_state(&mut second, &mut out_state, "have");
+        std::mem::swap(&mut second, &mut out_state);
+        assert_ne!(first, second);
+        assert_ne!(model.bos_state(), model.null_state());
+
+        let mut states = std::collections::HashSet::new();
+        states.insert(first.clone());
+        states.insert(second.clone());
+        states.insert(first);
+        assert_eq!(states.len(), 2);
+        assert!(states.contains(&second));
+    }
+
+    #[test]
+    fn state_accessors() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let null = model.null_state();
+        assert!(null.is_empty());
+        assert!(null.backoffs().is_empty());
+
+        let bos = model.bos_state();
+        assert_eq!(bos.len(), 1);
+        assert!(!bos.is_empty());
+        assert_eq!(bos.backoffs().len(), 1);
+
+        let mut state = model.bos_state();
+        let mut out_state = model.new_state();
+        model.score_word_given_state(&mut state, &mut out_state, "i");
+        assert!(out_state.len() <= 2);
+        assert_eq!(out_state.backoffs().len(), out_state.len());
+    }
+
+    #[test]
+    fn state_from_context() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let state = model.state_from_context(&["<s>"]);
+        assert_eq!(state, model.bos_state());
+
+        let (_, expected) = model.score_sentence_with_state(&["i", "have"], true, false);
+        assert_eq!(model.state_from_context(&["<s>", "i", "have"]), expected);
+        let indices = ["<s>", "i", "have"].map(|w| model.get_word_idx(w));
+        assert_eq!(model.state_from_indices(&indices), expected);
+        assert!(model.state_from_indices(&[]).is_empty());
+    }
+
+    #[test]
+    fn state_constructors() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert_eq!(State::bos(&model), model.bos_state());
+        assert_eq!(State::null(&model), model.null_state());
+        assert!(State::null(&model).is_empty());
+        assert_eq!(State::bos(&model).len(), 1);
+    }
+
+    #[test]
+    fn state_clones_are_independent() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let bos = model.bos_state();
+        let mut copy = bos.try_clone().expect("should not fail");
+        assert_eq!(copy, bos);
+
+        let mut out_state = model.new_state();
+        model.score_word_given_state(&mut copy, &mut out_state, "i");
+        model.fill_state_with_null_context(&mut copy);
+        assert!(copy.is_empty());
+        assert_eq!(bos, model.bos_state());
+    }
+
+    #[test]
+    fn state_display() {
+        let model = Model::new("test_data/test.bin", true).expect("should exist");
+        assert_eq!(model.null_state().display(&model).to_string(), "");
+        assert_eq!(model.bos_state().display(&model).to_string(), "<s>");
+        let state = model.state_from_context(&["<s>", "i", "have"]);
+        assert!(state.display(&model).to_string().ends_with("i have"));
+
+        let sorted = Model::new_with_sorted_vocab("test_data/test.bin").expect("should exist");
+        let state = sorted.state_from_context(&["<s>", "i"]);
+        assert_eq!(state.display(&sorted).to_string(), "<s> i");
+
+        let without_vocab = Model::new("test_data/test.bin", false).expect("should exist");
+        let bos = without_vocab
+            .bos_state()
+            .display(&without_vocab)
+            .to_string();
+        assert_eq!(bos, format!("#{}", *without_vocab.bos_idx()));
+    }
+
+    #[test]
+    fn explain_score_matches_full_score() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut state = model.bos_state();
+        let mut out_state = model.new_state();
+        for word in TEST_WITH_OOV {
+            let trace = model.explain_score(&state, word);
+            let full = model.full_score_word_given_state(&mut state, &mut out_state, word);
+            assert_eq!(trace.log_prob, full.log_prob);
+            assert_eq!(trace.ngram_length, full.ngram_length);
+            assert_eq!(trace.oov, full.oov);
+            assert_eq!(trace.out_state, out_state);
+            std::mem::swap(&mut state, &mut out_state);
+        }
+    }
+
+    #[test]
+    fn truncated_context() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut state = model.state_from_context(&["<s>", "i", "have"]);
+        let length = state.len();
+        state.truncate(length + 1);
+        assert_eq!(state.len(), length);
+        state.truncate(1);
+        assert_eq!(state, model.state_from_context(&["have"]));
+        state.truncate(0);
+        assert!(state.is_empty());
+
+        let mut in_state = model.bos_state();
+        let mut out_state = model.new_state();
+        let mut score = 0.;
+        for word in TEST_SENTENCE {
+            score += model.score_with_context_limit(&mut in_state, &mut out_state, word, 0);
+            std::mem::swap(&mut in_state, &mut out_state);
+        }
+        let unigrams = TEST_SENTENCE
+            .iter()
+            .map(|word| model.score_ngram(&[] as &[&str], word))
+            .sum::<f32>();
+        approx::assert_abs_diff_eq!(score, unigrams, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn score_pairs_matches_single_scores() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let context = model.state_from_context(&["<s>", "i"]);
+        let mut pairs = TEST_WITH_OOV
+            .iter()
+            .map(|w| (context.clone(), model.get_word_idx(w)))
+            .collect::<Vec<_>>();
+        let scores = model.score_pairs(&mut pairs);
+        assert_eq!(scores.len(), TEST_WITH_OOV.len());
+        for ((state, _), (word, score)) in pairs.iter().zip(TEST_WITH_OOV.iter().zip(scores)) {
+            let mut in_state = context.clone();
+            let mut out_state = model.new_state();
+            let expected = model.score_word_given_state(&mut in_state, &mut out_state, word);
+            assert_eq!(score, expected);
+            assert_eq!(*state, out_state);
+        }
+        assert!(model.score_pairs(&mut []).is_empty());
+    }
+
+    #[test]
+    fn word_idx_as_key() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let i = model.get_word_idx("i");
+        assert_eq!(WordIdx::from_u32(i.as_u32()), i);
+        assert_eq!(u32::from(i), *i);
+        assert!(model.unk_idx() < i);
+
+        let mut counts = std::collections::HashMap::new();
+        for word in TEST_SENTENCE {
+            *counts.entry(model.get_word_idx(word)).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&i], 3);
+    }
+
+    #[test]
+    fn vocab_callback_streams_indexed_words() {
+        let mut words = vec![];
+        let model = Model::builder("test_data/test.bin")
+            .store_vocab(true)
+            .build_with_vocab_callback(|index, word| words.push((index, word.to_string())))
+            .expect("should exist");
+        assert_eq!(words.len(), model.vocab_size());
+        for (index, word) in words.iter() {
+            assert_eq!(model.get_word_idx(word), *index);
+        }
+        assert_eq!(model.get_vocab().unwrap().len(), words.len());
+
+        let mut reloaded = 0;
+        model
+            .reload_with_vocab_callback(|_, _| reloaded += 1)
+            .expect("should exist");
+        assert_eq!(reloaded, words.len());
+
+        assert!(matches!(
+            Model::builder("test_data/test_no_vocab.bin").build_with_vocab_callback(|_, _| {}),
+            Err(Error::ModelHasNoVocab)
+        ));
+
+        // the panic is resumed once KenLM returned instead of unwinding through it
+        let panicked = std::panic::catch_unwind(|| {
+            Model::builder("test_data/test.bin").build_with_vocab_callback(|_, _| panic!("on_word"))
+        });
+        assert!(panicked.is_err());
+    }
+
+    #[test]
+    fn vocab_with_indices() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert!(model.get_vocab_with_indices().is_none());
+
+        for model in [
+            Model::new("test_data/test.bin", true).expect("should exist"),
+            Model::new_with_sorted_vocab("test_data/test.bin").expect("should exist"),
+        ] {
+            let pairs = model.get_vocab_with_indices().unwrap().collect::<Vec<_>>();
+            assert_eq!(pairs.len(), model.vocab_size());
+            for (index, word) in pairs {
+                assert_eq!(model.get_word_idx(word), index);
+            }
+        }
+    }
+
+    #[test]
+    fn vocab_positions_follow_the_enumerated_indices() {
+        let indices = [2, 0, 3].map(WordIdx::from_u32);
+        assert_eq!(
+            super::vocab_positions(&indices),
+            Some(vec![1, u32::MAX, 0, 2])
+        );
+        assert_eq!(super::vocab_positions(&[0, 1].map(WordIdx::from_u32)), None);
+    }
+
+    #[test]
+    fn lookup_cache_matches_cxx_lookup() {
+        let mut model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert!(matches!(
+            model.build_lookup_cache(),
+            Err(Error::ModelHasNoVocab)
+        ));
+
+        let mut model = Model::new_with_sorted_vocab("test_data/test.bin").expect("should exist");
+        let expected = TEST_WITH_OOV
+            .iter()
+            .map(|word| model.get_word_idx(word))
+            .collect::<Vec<_>>();
+        let score = model.score_sentence(TEST_WITH_OOV, true, true);
+        model.build_lookup_cache().unwrap();
+        let cached = TEST_WITH_OOV
+            .iter()
+            .map(|word| model.get_word_idx(word))
+            .collect::<Vec<_>>();
+        assert_eq!(cached, expected);
+        assert_eq!(model.score_sentence(TEST_WITH_OOV, true, true), score);
+        assert_eq!(model.get_word_idx_opt("toast"), None);
+    }
+
+    #[test]
+    fn batch_word_lookup() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let indices = model.get_word_indices(TEST_WITH_OOV);
+        assert_eq!(indices.len(), TEST_WITH_OOV.len());
+        for (word, index) in TEST_WITH_OOV.iter().zip(&indices) {
+            assert_eq!(model.get_word_idx(word), *index);
+        }
+        approx::assert_abs_diff_eq!(
+            model.score_sentence_indices(&indices, true, true),
+            model.score_sentence(TEST_WITH_OOV, true, true),
+            epsilon = 1e-5
+        );
+        assert!(model.get_word_indices(Vec::<String>::new()).is_empty());
+    }
+
+    #[test]
+    fn reverse_lookup() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert_eq!(model.get_word(model.bos_idx()), None);
+
+        for model in [
+            Model::new("test_data/test.bin", true).expect("should exist"),
+            Model::new_with_sorted_vocab("test_data/test.bin").expect("should exist"),
+        ] {
+            for word in TEST_SENTENCE {
+                assert_eq!(model.get_word(model.get_word_idx(word)), Some(*word));
+            }
+            assert_eq!(model.get_word(model.unk_idx()), Some("<unk>"));
+            for (index, word) in model.get_vocab_with_indices().unwrap() {
+                assert_eq!(model.get_word(index), Some(word));
+            }
+            let past_vocab = WordIdx::from_u32(model.vocab_size() as u32);
+            assert_eq!(model.get_word(past_vocab), None);
+        }
+    }
+
+    #[test]
+    fn writes_vocab() {
+        let model = Model::new("test_data/test.bin", true).expect("should exist");
+        let path = std::env::temp_dir().join(format!("kenlm-rs-vocab-{}.txt", std::process::id()));
+
+        model.write_vocab(&path, false).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            written.lines().collect::<Vec<_>>(),
+            model.get_vocab().unwrap()
+        );
+
+        model.write_vocab(&path, true).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        for line in written.lines() {
+            let (word, idx) = line.split_once('\t').unwrap();
+            assert_eq!(*model.get_word_idx(word), idx.parse::<u32>().unwrap());
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        assert!(matches!(
+            model.write_vocab(&path, false),
+            Err(Error::ModelHasNoVocab)
+        ));
+    }
+
+    #[test]
+    fn vocab_size_without_stored_vocab() {
+        let stored = Model::new("test_data/test.bin", true).expect("should exist");
+        for file_name in ["test_data/test.bin", "test_data/test_no_vocab.bin"] {
+            let model = Model::new(file_name, false).expect("should exist");
+            assert!(model.get_vocab().is_none());
+            assert_eq!(model.vocab_size(), stored.get_vocab().unwrap().len());
+        }
+
+        let arpa = Model::new("test_data/arpa/lm_small.arpa", true).expect("should exist");
+        let arpa_without_vocab =
+            Model::new("test_data/arpa/lm_small.arpa", false).expect("should exist");
+        assert_eq!(
+            arpa_without_vocab.vocab_size(),
+            arpa.get_vocab().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn contains_words_and_indices() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        for word in TEST_SENTENCE {
+            assert!(model.contains_word(word));
+        }
+        assert!(!model.contains_word("toast"));
+        assert!(model.contains_word("<s>"));
+        assert!(model.contains_index(model.eos_idx()));
+        assert!(!model.contains_index(model.unk_idx()));
+        assert!(!model.contains_index(WordIdx::from_u32(model.vocab_size() as u32)));
+    }
+
+    #[test]
+    fn special_tokens_replace_sentence_markers() {
+        let mut model = Model::new("test_data/test.bin", false).expect("should exist");
+        let score = model.score_sentence(TEST_WITH_OOV, true, true);
+        model.set_special_tokens(&SpecialTokens::new()).unwrap();
+        assert_eq!(model.score_sentence(TEST_WITH_OOV, true, true), score);
+
+        let with_deal = [TEST_SENTENCE, &["deal"]].concat();
+        let expected = model.score_sentence(&with_deal, true, false);
+        model
+            .set_special_tokens(&SpecialTokens::new().eos("deal"))
+            .unwrap();
+        assert_eq!(model.eos_idx(), model.get_word_idx("deal"));
+        approx::assert_abs_diff_eq!(
+            model.score_sentence(TEST_SENTENCE, true, true),
+            expected,
+            epsilon = 1e-5
+        );
+
+        assert!(matches!(
+            model.set_special_tokens(&SpecialTokens::new().unk("[UNK]")),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert_eq!(model.eos_idx(), model.get_word_idx("deal"));
+    }
+
+    #[test]
+    fn remapped_unk_word_is_not_oov() {
+        let mut model = Model::new("test_data/test.bin", false).expect("should exist");
+        model
+            .set_special_tokens(&SpecialTokens::new().unk("a"))
+            .unwrap();
+        assert_eq!(model.get_word_idx("toast"), model.get_word_idx("a"));
+        assert!(model.contains_word("a"));
+        assert!(!model.contains_word("toast"));
+        assert_eq!(model.get_word_idx_opt("a"), Some(model.unk_idx()));
+        assert_eq!(model.get_word_idx_opt("toast"), None);
+
+        let options = ScoreOptions::new(true, true).exclude_oov(true);
+        let scores = model.score_sentence_detailed(["i", "have", "a", "toast"], options);
+        assert_eq!(
+            scores.iter().map(|score| score.oov).collect::<Vec<_>>(),
+            &[false, false, false, true, false]
+        );
+        let score = model.score_sentence_with_oov(["i", "have", "a", "toast"], options);
+        assert_eq!(score.oov_positions, &[3]);
+        // the score of the remapped word counts, only the one of the unknown word is dropped
+        let expected = scores
+            .iter()
+            .filter(|score| !score.oov)
+            .map(|score| score.log_prob)
+            .sum::<f32>();
+        approx::assert_abs_diff_eq!(
+            model.score_sentence_with_options(["i", "have", "a", "toast"], options),
+            expected,
+            epsilon = 1e-5
+        );
+        let mut out_state = model.new_state();
+        let full = model.full_score_word_given_state(&mut model.bos_state(), &mut out_state, "a");
+        assert!(!full.oov);
+        assert!(!model.explain_score(&model.bos_state(), "a").oov);
+        assert!(model.explain_score(&model.bos_state(), "toast").oov);
+    }
 }
@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+
+use unicode_normalization::{is_nfc_quick, is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+/// Unicode normalization form applied to words before they are looked up
+///
+/// Composed and decomposed spellings of the same word, e.g. `é` as one code point or as `e`
+/// followed by a combining accent, are different strings to KenLM. If the model was trained on
+/// normalized text, normalizing the queries the same way keeps them from ending up as `<unk>`.
+/// Requires the `unicode` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// Canonical composition
+    Nfc,
+    /// Compatibility composition, additionally folds e.g. ligatures and fullwidth forms
+    Nfkc,
+}
+
+impl NormalizationForm {
+    pub(crate) fn apply<'a>(&self, token: &'a str) -> Cow<'a, str> {
+        let quick_check = match self {
+            NormalizationForm::Nfc => is_nfc_quick(token.chars()),
+            NormalizationForm::Nfkc => is_nfkc_quick(token.chars()),
+        };
+        if quick_check == IsNormalized::Yes {
+            return Cow::Borrowed(token);
+        }
+        match self {
+            NormalizationForm::Nfc => Cow::Owned(token.nfc().collect()),
+            NormalizationForm::Nfkc => Cow::Owned(token.nfkc().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NormalizationForm;
+
+    #[test]
+    fn normalizes_tokens() {
+        assert_eq!(
+            NormalizationForm::Nfc.apply("caf\u{65}\u{301}"),
+            "caf\u{e9}"
+        );
+        assert_eq!(NormalizationForm::Nfc.apply("\u{fb01}ne"), "\u{fb01}ne");
+        assert_eq!(NormalizationForm::Nfkc.apply("\u{fb01}ne"), "fine");
+        assert_eq!(NormalizationForm::Nfkc.apply("word"), "word");
+    }
+}
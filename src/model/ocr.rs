@@ -0,0 +1,196 @@
+use super::{Model, State};
+
+/// A candidate reading of one or more OCR tokens
+///
+/// `text` may contain whitespace to split a token the OCR engine merged, e.g. `"a good"` for
+/// `"agood"`. A `span` larger than `1` merges tokens the OCR engine split, e.g. `"deal"` spanning
+/// the tokens `"de"` and `"al"`. The alternative is attached to the first of the tokens it spans.
+#[derive(Debug, Clone)]
+pub struct OcrAlternative {
+    pub text: String,
+    /// Confidence of the OCR engine in `(0, 1]`
+    pub confidence: f32,
+    /// Number of input tokens this alternative replaces
+    pub span: usize,
+}
+
+impl OcrAlternative {
+    /// Constructs an alternative replacing a single token
+    pub fn new(text: impl Into<String>, confidence: f32) -> Self {
+        OcrAlternative {
+            text: text.into(),
+            confidence,
+            span: 1,
+        }
+    }
+
+    /// Sets the number of input tokens this alternative replaces
+    pub fn spanning(mut self, span: usize) -> Self {
+        self.span = span.max(1);
+        self
+    }
+}
+
+/// Best correction found by [OcrCorrector::correct]
+#[derive(Debug, Clone)]
+pub struct OcrCorrection {
+    /// The corrected words
+    pub words: Vec<String>,
+    /// Weighted sum of the language model score and the log10 confidences
+    pub score: f32,
+}
+
+/// Beam search reranking OCR alternatives with a [Model]
+///
+/// Each hypothesis is scored as `lm_weight * lm_log_prob + confidence_weight * log10(confidence)`
+/// summed over the chosen alternatives. Sentences are scored with `<s>` and `</s>`.
+/// ```
+/// use kenlm_rs::{Model, OcrAlternative, OcrCorrector};
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let tokens = vec![
+///     vec![OcrAlternative::new("i", 1.)],
+///     vec![OcrAlternative::new("hove", 0.6), OcrAlternative::new("have", 0.4)],
+/// ];
+/// let correction = OcrCorrector::new().correct(&model, &tokens).unwrap();
+/// assert_eq!(correction.words, &["i", "have"]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct OcrCorrector {
+    beam_width: usize,
+    lm_weight: f32,
+    confidence_weight: f32,
+}
+
+impl Default for OcrCorrector {
+    fn default() -> Self {
+        OcrCorrector {
+            beam_width: 16,
+            lm_weight: 1.,
+            confidence_weight: 1.,
+        }
+    }
+}
+
+struct Hypothesis {
+    state: State,
+    words: Vec<String>,
+    score: f32,
+}
+
+impl OcrCorrector {
+    /// Constructs a corrector with a beam width of `16` and equal weights
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of hypotheses kept per position
+    pub fn beam_width(mut self, beam_width: usize) -> Self {
+        self.beam_width = beam_width.max(1);
+        self
+    }
+
+    /// Sets the weights of the language model score and the OCR confidences
+    pub fn weights(mut self, lm_weight: f32, confidence_weight: f32) -> Self {
+        self.lm_weight = lm_weight;
+        self.confidence_weight = confidence_weight;
+        self
+    }
+
+    /// Returns the best correction of `tokens`, each entry holds the alternatives of one token
+    ///
+    /// Returns `None` if no sequence of alternatives covers all tokens, e.g. because an
+    /// alternative spans past the end or a token has no alternatives.
+    pub fn correct(&self, model: &Model, tokens: &[Vec<OcrAlternative>]) -> Option<OcrCorrection> {
+        // beams[i] holds the hypotheses which covered the first i tokens
+        let mut beams = (0..=tokens.len()).map(|_| vec![]).collect::<Vec<_>>();
+        beams[0].push(Hypothesis {
+            state: model.bos_state(),
+            words: vec![],
+            score: 0.,
+        });
+
+        let mut out_state = model.new_state();
+        for (position, alternatives) in tokens.iter().enumerate() {
+            let mut hypotheses = std::mem::take(&mut beams[position]);
+            self.prune(&mut hypotheses);
+            for hypothesis in &hypotheses {
+                for alternative in alternatives {
+                    let end = position + alternative.span;
+                    if end > tokens.len() {
+                        continue;
+                    }
+                    let mut state = hypothesis.state.clone();
+                    let mut words = hypothesis.words.clone();
+                    let mut lm_score = 0.;
+                    for word in alternative.text.split_ascii_whitespace() {
+                        lm_score += model.score_word_given_state(&mut state, &mut out_state, word);
+                        std::mem::swap(&mut state, &mut out_state);
+                        words.push(word.to_string());
+                    }
+                    let score = hypothesis.score
+                        + self.lm_weight * lm_score
+                        + self.confidence_weight * alternative.confidence.log10();
+                    beams[end].push(Hypothesis {
+                        state,
+                        words,
+                        score,
+                    });
+                }
+            }
+        }
+
+        beams
+            .pop()?
+            .into_iter()
+            .map(|hypothesis| OcrCorrection {
+                score: hypothesis.score
+                    + self.lm_weight * model.score_final_word(&hypothesis.state, "</s>"),
+                words: hypothesis.words,
+            })
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+    }
+
+    fn prune(&self, hypotheses: &mut Vec<Hypothesis>) {
+        hypotheses.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hypotheses.truncate(self.beam_width);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OcrAlternative, OcrCorrector};
+    use crate::Model;
+
+    #[test]
+    fn corrects_split_and_merged_tokens() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let tokens = vec![
+            vec![OcrAlternative::new("i", 1.)],
+            vec![
+                OcrAlternative::new("hove", 0.6),
+                OcrAlternative::new("have", 0.4),
+            ],
+            vec![
+                OcrAlternative::new("agood", 0.5),
+                OcrAlternative::new("a good", 0.5),
+            ],
+            vec![
+                OcrAlternative::new("de", 0.5),
+                OcrAlternative::new("deal", 0.5).spanning(2),
+            ],
+            vec![OcrAlternative::new("al", 1.)],
+        ];
+        let correction = OcrCorrector::new().correct(&model, &tokens).unwrap();
+        assert_eq!(correction.words, &["i", "have", "a", "good", "deal"]);
+        let expected = -3.0595014 + (0.4f32 * 0.5 * 0.5).log10();
+        approx::assert_abs_diff_eq!(correction.score, expected, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn returns_none_if_tokens_are_not_covered() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let tokens = vec![vec![OcrAlternative::new("i", 1.).spanning(2)]];
+        assert!(OcrCorrector::new().correct(&model, &tokens).is_none());
+    }
+}
@@ -0,0 +1,29 @@
+/// Options for scoring a sentence
+///
+/// `bos` and `eos` control whether the sentence is scored in the context of `<s>` and whether
+/// `</s>` is appended. Setting `exclude_oov` mirrors the convention of the Python kenlm module
+/// of skipping the scores of out-of-vocabulary words. The context is still advanced over them,
+/// only their scores are not part of the total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScoreOptions {
+    pub bos: bool,
+    pub eos: bool,
+    pub exclude_oov: bool,
+}
+
+impl ScoreOptions {
+    /// Constructs options scoring all words, with or without sentence boundaries
+    pub fn new(bos: bool, eos: bool) -> Self {
+        Self {
+            bos,
+            eos,
+            exclude_oov: false,
+        }
+    }
+
+    /// Sets whether scores of out-of-vocabulary words are left out of the total
+    pub fn exclude_oov(mut self, exclude_oov: bool) -> Self {
+        self.exclude_oov = exclude_oov;
+        self
+    }
+}
@@ -0,0 +1,150 @@
+use std::io::{self, Write};
+
+use super::{Model, ScoreOptions, TokenScore};
+
+/// Printer replicating the output of KenLM's `query` binary
+///
+/// Each scored line is printed as `word=idx ngram_length log_prob` entries separated by tabs,
+/// followed by `Total: <log_prob> OOV: <count>`. [QueryPrinter::summary] prints the
+/// perplexity block `query` ends its output with. Harnesses diffing against the output of
+/// the C++ tool can switch to this crate without changing their comparisons.
+pub struct QueryPrinter<W> {
+    out: W,
+    sentence_context: bool,
+    print_word: bool,
+    print_line: bool,
+    corpus_total: f64,
+    corpus_total_oov_only: f64,
+    corpus_oov: u64,
+    corpus_tokens: u64,
+}
+
+impl<W: Write> QueryPrinter<W> {
+    /// Constructs a printer writing to `out`
+    ///
+    /// Like `query`, sentences are scored with `<s>` as context and `</s>` appended by
+    /// default, see [QueryPrinter::sentence_context].
+    pub fn new(out: W) -> Self {
+        QueryPrinter {
+            out,
+            sentence_context: true,
+            print_word: true,
+            print_line: true,
+            corpus_total: 0.,
+            corpus_total_oov_only: 0.,
+            corpus_oov: 0,
+            corpus_tokens: 0,
+        }
+    }
+
+    /// Whether to score with sentence context, equivalent to `query -n` if `false`
+    pub fn sentence_context(mut self, sentence_context: bool) -> Self {
+        self.sentence_context = sentence_context;
+        self
+    }
+
+    /// Whether to print the per-word scores
+    pub fn print_word(mut self, print_word: bool) -> Self {
+        self.print_word = print_word;
+        self
+    }
+
+    /// Whether to print the per-line totals
+    pub fn print_line(mut self, print_line: bool) -> Self {
+        self.print_line = print_line;
+        self
+    }
+
+    /// Scores a whitespace tokenized line and prints it
+    pub fn query_line(&mut self, model: &Model, line: &str) -> io::Result<()> {
+        let sentence = line.split_ascii_whitespace().collect::<Vec<_>>();
+        let options = ScoreOptions::new(self.sentence_context, self.sentence_context);
+        let scores = model.score_sentence_detailed(&sentence, options);
+
+        let mut total = 0f32;
+        let mut oov = 0u64;
+        let surfaces = sentence.iter().copied().chain(std::iter::once("</s>"));
+        for (surface, score) in surfaces.zip(&scores) {
+            let TokenScore {
+                word_idx,
+                log_prob,
+                ngram_length,
+                oov: is_oov,
+                ..
+            } = score;
+            if *is_oov {
+                oov += 1;
+                self.corpus_total_oov_only += f64::from(*log_prob);
+            }
+            total += log_prob;
+            self.corpus_tokens += 1;
+            if self.print_word {
+                write!(
+                    self.out,
+                    "{}={} {} {}\t",
+                    surface, **word_idx, ngram_length, log_prob
+                )?;
+            }
+        }
+        if self.print_line {
+            writeln!(self.out, "Total: {} OOV: {}", total, oov)?;
+        }
+        self.corpus_total += f64::from(total);
+        self.corpus_oov += oov;
+        Ok(())
+    }
+
+    /// Prints the perplexity summary over all lines queried so far
+    pub fn summary(&mut self) -> io::Result<()> {
+        let ppl_including_oov = 10f64.powf(-(self.corpus_total / self.corpus_tokens as f64));
+        let ppl_excluding_oov = 10f64.powf(
+            -((self.corpus_total - self.corpus_total_oov_only)
+                / (self.corpus_tokens - self.corpus_oov) as f64),
+        );
+        writeln!(
+            self.out,
+            "Perplexity including OOVs:\t{}",
+            ppl_including_oov
+        )?;
+        writeln!(
+            self.out,
+            "Perplexity excluding OOVs:\t{}",
+            ppl_excluding_oov
+        )?;
+        writeln!(self.out, "OOVs:\t{}", self.corpus_oov)?;
+        writeln!(self.out, "Tokens:\t{}", self.corpus_tokens)?;
+        self.out.flush()
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::QueryPrinter;
+    use crate::Model;
+
+    #[test]
+    fn matches_kenlm_query_output() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut printer = QueryPrinter::new(vec![]);
+        printer.query_line(&model, "i have toast").unwrap();
+        printer.query_line(&model, "have a good deal").unwrap();
+        printer.summary().unwrap();
+        let out = String::from_utf8(printer.into_inner()).unwrap();
+        assert_eq!(
+            out,
+            "i=20 2 -0.2659049\thave=13 3 -0.16921267\ttoast=0 1 -2.264818\t</s>=10 1 -1.3708712\t\
+             Total: -4.0708065 OOV: 1\n\
+             have=13 1 -1.6719012\ta=2 2 -0.5665723\tgood=23 3 -0.11881906\tdeal=11 3 -0.11881906\t\
+             </s>=10 1 -1.9729313\tTotal: -4.449043 OOV: 0\n\
+             Perplexity including OOVs:\t8.844024199835276\n\
+             Perplexity excluding OOVs:\t6.051721288394032\n\
+             OOVs:\t1\n\
+             Tokens:\t9\n"
+        );
+    }
+}
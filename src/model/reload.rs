@@ -0,0 +1,161 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use super::{Model, ModelBuilder};
+use crate::Error;
+
+/// A [Model] that is reloaded whenever its file changes
+///
+/// A background thread polls the size and modification time of the model file every
+/// `interval`. When they change, the model is loaded again with the options of the builder and
+/// atomically swapped in. Readers holding a model from [HotReloadingModel::current] keep using
+/// the old one until they ask again, it is dropped once the last of them is done. If a reload
+/// fails, the current model is kept and the error can be retrieved with
+/// [HotReloadingModel::take_error]. Remote models are not watched.
+///
+/// Replace model files atomically, e.g. by renaming a fully written file over the old one, a
+/// partially written file may be picked up otherwise.
+/// ```
+/// use std::time::Duration;
+/// use kenlm_rs::{HotReloadingModel, Model};
+///
+/// let model = HotReloadingModel::new(Model::builder("test_data/test.bin"), Duration::from_secs(1))
+///     .unwrap();
+/// let current = model.current();
+/// assert!(current.get_word_idx_opt("i").is_some());
+/// ```
+pub struct HotReloadingModel {
+    shared: Arc<Shared>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    builder: ModelBuilder,
+    model: RwLock<Arc<Model>>,
+    error: Mutex<Option<Error>>,
+    stop: AtomicBool,
+}
+
+impl HotReloadingModel {
+    /// Loads the model from `builder` and watches its file every `interval`
+    pub fn new(builder: ModelBuilder, interval: Duration) -> Result<Self, Error> {
+        let mut modified = file_version(builder.file_name());
+        let model = builder.clone().build()?;
+        let shared = Arc::new(Shared {
+            builder,
+            model: RwLock::new(Arc::new(model)),
+            error: Mutex::new(None),
+            stop: AtomicBool::new(false),
+        });
+        let watched = shared.clone();
+        let watcher = std::thread::spawn(move || {
+            while !watched.stop.load(Ordering::Acquire) {
+                std::thread::park_timeout(interval);
+                let version = file_version(watched.builder.file_name());
+                if version.is_some() && version != modified {
+                    modified = version;
+                    // a failed reload is not retried until the file changes again
+                    if let Err(err) = watched.reload() {
+                        *watched.error.lock().unwrap() = Some(err);
+                    }
+                }
+            }
+        });
+        Ok(HotReloadingModel {
+            shared,
+            watcher: Some(watcher),
+        })
+    }
+
+    /// Returns the current model
+    pub fn current(&self) -> Arc<Model> {
+        self.shared.model.read().unwrap().clone()
+    }
+
+    /// Reloads the model now, regardless of whether its file changed
+    pub fn reload(&self) -> Result<(), Error> {
+        self.shared.reload()
+    }
+
+    /// Takes the error of the last failed reload in the background, if any
+    pub fn take_error(&self) -> Option<Error> {
+        self.shared.error.lock().unwrap().take()
+    }
+}
+
+impl Shared {
+    /// Loads the model and swaps it in
+    fn reload(&self) -> Result<(), Error> {
+        let model = self.builder.clone().build()?;
+        *self.model.write().unwrap() = Arc::new(model);
+        Ok(())
+    }
+}
+
+impl Drop for HotReloadingModel {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        if let Some(watcher) = self.watcher.take() {
+            watcher.thread().unpark();
+            let _ = watcher.join();
+        }
+    }
+}
+
+/// Size and modification time of `file_name`, `None` if they can't be read
+fn file_version(file_name: &str) -> Option<(u64, SystemTime)> {
+    let metadata = std::fs::metadata(file_name).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::HotReloadingModel;
+    use crate::model::test::TEST_SENTENCE;
+    use crate::{Error, Model};
+
+    #[test]
+    fn reload_keeps_scores() {
+        let model = Model::new("test_data/test.bin", false).expect("should load");
+        let reloaded = model.reload().expect("should reload");
+        assert_eq!(
+            model.score_sentence(TEST_SENTENCE, true, true),
+            reloaded.score_sentence(TEST_SENTENCE, true, true)
+        );
+    }
+
+    #[test]
+    fn swaps_in_changed_model() {
+        let path = std::env::temp_dir().join(format!("kenlm-rs-reload-{}.bin", std::process::id()));
+        let file_name = path.to_str().unwrap();
+        std::fs::copy("test_data/test.bin", &path).unwrap();
+        let model = HotReloadingModel::new(Model::builder(file_name), Duration::from_millis(10))
+            .expect("should load");
+        let before = model.current();
+        assert_eq!(before.vocab_size(), 24);
+
+        // replace the file atomically by a different model, the modification time may be too
+        // coarse to notice the change, so the size differs as well
+        let replacement = path.with_extension("part");
+        std::fs::copy("test_data/carol.bin", &replacement).unwrap();
+        std::fs::rename(&replacement, &path).unwrap();
+        let mut current = model.current();
+        for _ in 0..500 {
+            if !std::sync::Arc::ptr_eq(&before, &current) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+            current = model.current();
+        }
+        assert_ne!(current.vocab_size(), before.vocab_size());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(model.reload(), Err(Error::FileNotFound(_))));
+        assert!(model.take_error().is_none());
+        assert_eq!(model.current().vocab_size(), current.vocab_size());
+    }
+}
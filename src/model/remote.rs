@@ -0,0 +1,157 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use reqwest::blocking::RequestBuilder;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+
+use super::cache::cache_dir;
+use super::checksum::verify_digest;
+use crate::Error;
+
+const CHECKSUM_FRAGMENT: &str = "#sha256=";
+
+/// Returns whether `file_name` should be downloaded before loading
+pub(crate) fn is_remote(file_name: &str) -> bool {
+    file_name.starts_with("http://") || file_name.starts_with("https://")
+}
+
+/// Downloads `url` into the cache directory unless it is cached already, returns the local path
+///
+/// A `#sha256=<hex digest>` fragment is stripped from the url and the downloaded file is
+/// verified against it, cached files are verified again before they are reused. Cached files
+/// without a checksum are revalidated with the `ETag` and `Last-Modified` headers of their
+/// download and downloaded again if the server reports a change. Files whose server sent
+/// neither header are reused as they are, as are cached files if the server can't be reached.
+pub(crate) fn fetch(url: &str) -> Result<PathBuf, Error> {
+    let (url, checksum) = split_checksum(url);
+    let path = cache_dir()?.join(format!("{:x}", Sha256::digest(url.as_bytes())));
+    let validators = path.with_extension("validators");
+    let mut request = reqwest::blocking::Client::new().get(url);
+    if path.exists() {
+        if checksum.is_some() {
+            // a file matching its checksum can't be outdated
+            if verify_checksum(&path, checksum).is_ok() {
+                return Ok(path);
+            }
+        } else {
+            match conditional(request, &validators) {
+                Some(conditional) => request = conditional,
+                None => return Ok(path),
+            }
+        }
+    }
+
+    let response = match request.send() {
+        Ok(response) => response,
+        Err(_) if path.exists() && checksum.is_none() => return Ok(path),
+        Err(err) => return Err(err.into()),
+    };
+    if response.status() == StatusCode::NOT_MODIFIED && path.exists() {
+        return Ok(path);
+    }
+    let mut response = response.error_for_status()?;
+    let partial = path.with_extension("part");
+    response.copy_to(&mut File::create(&partial)?)?;
+    if let Err(err) = verify_checksum(&partial, checksum) {
+        std::fs::remove_file(&partial)?;
+        return Err(err);
+    }
+    std::fs::rename(&partial, &path)?;
+    std::fs::write(&validators, format_validators(response.headers()))?;
+    Ok(path)
+}
+
+/// Adds the validators stored for a cached file to `request`, `None` if there are none
+fn conditional(mut request: RequestBuilder, validators: &Path) -> Option<RequestBuilder> {
+    let validators = std::fs::read_to_string(validators).ok()?;
+    let mut any = false;
+    for line in validators.lines() {
+        if let Some((name, value)) = line.split_once(": ") {
+            request = request.header(name, value);
+            any = true;
+        }
+    }
+    any.then_some(request)
+}
+
+/// Turns the `ETag` and `Last-Modified` headers of a response into the headers of a request
+/// revalidating it, one `name: value` per line
+fn format_validators(headers: &HeaderMap) -> String {
+    [(ETAG, IF_NONE_MATCH), (LAST_MODIFIED, IF_MODIFIED_SINCE)]
+        .into_iter()
+        .filter_map(|(response, request)| {
+            let value = headers.get(response)?.to_str().ok()?;
+            Some(format!("{}: {}\n", request, value))
+        })
+        .collect()
+}
+
+/// Returns whether `url` carries a `#sha256=<hex digest>` fragment
+pub(crate) fn has_checksum(url: &str) -> bool {
+    split_checksum(url).1.is_some()
+}
+
+fn split_checksum(url: &str) -> (&str, Option<&str>) {
+    match url.rsplit_once(CHECKSUM_FRAGMENT) {
+        Some((url, checksum)) => (url, Some(checksum)),
+        None => (url, None),
+    }
+}
+
+fn verify_checksum(path: &Path, expected: Option<&str>) -> Result<(), Error> {
+    match expected {
+        Some(expected) => verify_digest(path, expected),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+
+    use super::{format_validators, has_checksum, is_remote, split_checksum, verify_checksum};
+    use crate::Error;
+
+    #[test]
+    fn formats_validators() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(format_validators(&headers), "");
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            format_validators(&headers),
+            "if-none-match: \"abc\"\nif-modified-since: Wed, 21 Oct 2015 07:28:00 GMT\n"
+        );
+    }
+
+    #[test]
+    fn splits_checksum_fragment() {
+        assert_eq!(
+            split_checksum("https://example.com/lm.bin#sha256=abc"),
+            ("https://example.com/lm.bin", Some("abc"))
+        );
+        assert_eq!(
+            split_checksum("https://example.com/lm.bin"),
+            ("https://example.com/lm.bin", None)
+        );
+        assert!(has_checksum("https://example.com/lm.bin#sha256=abc"));
+        assert!(!has_checksum("https://example.com/lm.bin"));
+        assert!(is_remote("https://example.com/lm.bin"));
+        assert!(!is_remote("test_data/test.bin"));
+    }
+
+    #[test]
+    fn verifies_checksum() {
+        let path = std::path::Path::new("test_data/arpa/lm_small.arpa");
+        assert!(verify_checksum(path, None).is_ok());
+        assert!(matches!(
+            verify_checksum(path, Some("00")),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+    }
+}
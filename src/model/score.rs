@@ -0,0 +1,125 @@
+use super::{ResolvedBy, WordIdx};
+
+/// Score of a single token within a sentence
+///
+/// Returned by [crate::Model::score_sentence_detailed]. Next to the log10 probability, it
+/// carries the length of the n-gram that matched when scoring the token, as reported by
+/// KenLM's `FullScore`.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenScore {
+    /// The index the token was mapped to, `<unk>` for out-of-vocabulary tokens
+    pub word_idx: WordIdx,
+    /// Conditional probability of the token given its context in log10-space
+    pub log_prob: f32,
+    /// Length of the matched n-gram, `1` means the model backed off to the unigram
+    pub ngram_length: u8,
+    /// Whether the token is out of vocabulary
+    pub oov: bool,
+    /// The rule of the model's [crate::OovFallback] chain which resolved the token
+    pub resolved_by: ResolvedBy,
+}
+
+/// Score of a single word as returned by KenLM's `FullScore`
+///
+/// Returned by [crate::Model::full_score_word_given_state].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FullScore {
+    /// Conditional probability of the word given its context in log10-space
+    pub log_prob: f32,
+    /// Length of the matched n-gram, `1` means the model backed off to the unigram
+    pub ngram_length: u8,
+    /// Whether the word is out of vocabulary
+    pub oov: bool,
+}
+
+/// Score of a sentence along with its out-of-vocabulary words
+///
+/// Returned by [crate::Model::score_sentence_with_oov].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OovScore {
+    /// Joint probability of the sentence in log10-space
+    pub log_prob: f32,
+    /// Positions of the out-of-vocabulary words in the sentence
+    pub oov_positions: Vec<usize>,
+}
+
+impl OovScore {
+    /// Number of out-of-vocabulary words in the sentence
+    pub fn oov_count(&self) -> usize {
+        self.oov_positions.len()
+    }
+}
+
+/// Score of a whole sentence
+///
+/// Sent by [crate::Model::score_channel] for every sentence it receives.
+#[derive(Debug, Clone)]
+pub struct SentenceScore {
+    /// The sentence as it was received
+    pub sentence: String,
+    /// Joint probability of the sentence in log10-space
+    pub log_prob: f32,
+}
+
+/// A log10 probability as returned by the scoring functions of [crate::Model]
+///
+/// Converts scores to other bases for systems which don't work in log10-space.
+/// ```
+/// use kenlm_rs::{Model, Score};
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let score = Score::from(model.score_sentence(&["i", "have"], true, false));
+/// assert!((score.ln() - score.log10() * std::f32::consts::LN_10).abs() < 1e-6);
+/// assert!(score.probability() < 1.);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Score(pub f32);
+
+impl Score {
+    /// The score in log10-space, as returned by KenLM
+    pub fn log10(self) -> f32 {
+        self.0
+    }
+
+    /// The score as natural logarithm
+    pub fn ln(self) -> f32 {
+        self.0 * std::f32::consts::LN_10
+    }
+
+    /// The score in log2-space, i.e. in bits
+    pub fn log2(self) -> f32 {
+        self.0 * std::f32::consts::LOG2_10
+    }
+
+    /// The linear probability, may underflow to `0` for long sentences
+    pub fn probability(self) -> f32 {
+        10f32.powf(self.0)
+    }
+}
+
+impl From<f32> for Score {
+    fn from(log10: f32) -> Self {
+        Score(log10)
+    }
+}
+
+impl From<Score> for f32 {
+    fn from(score: Score) -> Self {
+        score.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Score;
+
+    #[test]
+    fn converts_between_bases() {
+        let score = Score::from(-2.);
+        approx::assert_abs_diff_eq!(score.log10(), -2.);
+        approx::assert_abs_diff_eq!(score.ln(), (0.01f32).ln(), epsilon = 1e-6);
+        approx::assert_abs_diff_eq!(score.log2(), (0.01f32).log2(), epsilon = 1e-5);
+        approx::assert_abs_diff_eq!(score.probability(), 0.01, epsilon = 1e-7);
+        assert_eq!(f32::from(score), -2.);
+    }
+}
@@ -0,0 +1,127 @@
+use super::{Model, State, WordIdx};
+
+/// Left-to-right scorer which manages the states internally
+///
+/// Replaces the score-then-swap idiom of `score_word_given_state`, every pushed word is scored
+/// in the context of the words pushed before it.
+/// ```
+/// use kenlm_rs::{Model, Scorer};
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let mut scorer = Scorer::new(&model, true);
+/// for word in ["i", "have", "a", "good", "deal", "</s>"] {
+///     scorer.push_word(word);
+/// }
+/// let expected = model.score_sentence(&["i", "have", "a", "good", "deal"], true, true);
+/// assert!((scorer.total() - expected).abs() < 1e-6);
+/// ```
+pub struct Scorer<'m> {
+    model: &'m Model,
+    state: State,
+    scratch: State,
+    total: f32,
+}
+
+impl<'m> Scorer<'m> {
+    /// Constructs a scorer, in the context of `<s>` if `bos` is set
+    pub fn new(model: &'m Model, bos: bool) -> Self {
+        let state = if bos {
+            model.bos_state()
+        } else {
+            model.null_state()
+        };
+        Self::with_state(model, state)
+    }
+
+    /// Constructs a scorer continuing from `state`
+    pub fn with_state(model: &'m Model, state: State) -> Self {
+        Scorer {
+            model,
+            state,
+            scratch: model.new_state(),
+            total: 0.,
+        }
+    }
+
+    /// Scores `word` given the words pushed so far, returns its log10 probability
+    pub fn push_word(&mut self, word: &str) -> f32 {
+        self.push_index(self.model.get_word_idx(word))
+    }
+
+    /// Scores `index` given the words pushed so far, returns its log10 probability
+    pub fn push_index(&mut self, index: WordIdx) -> f32 {
+        let log_prob =
+            self.model
+                .score_index_given_state(&mut self.state, &mut self.scratch, index);
+        std::mem::swap(&mut self.state, &mut self.scratch);
+        self.total += log_prob;
+        log_prob
+    }
+
+    /// Sum of the log10 probabilities of all words pushed since construction or the last reset
+    pub fn total(&self) -> f32 {
+        self.total
+    }
+
+    /// Clears the total and the context, starts in the context of `<s>` if `bos` is set
+    pub fn reset(&mut self, bos: bool) {
+        if bos {
+            self.state.copy_from(&self.model.bos_state);
+        } else {
+            self.state.copy_from(&self.model.null_state);
+        }
+        self.total = 0.;
+    }
+
+    /// The state after the last pushed word
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub(crate) fn model(&self) -> &'m Model {
+        self.model
+    }
+
+    pub(crate) fn set_total(&mut self, total: f32) {
+        self.total = total;
+    }
+
+    /// Continues from `state` with `total` as the accumulated score
+    pub(crate) fn rewind(&mut self, state: &State, total: f32) {
+        self.state.copy_from(state);
+        self.total = total;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Scorer;
+    use crate::model::test::TEST_WITH_OOV;
+    use crate::Model;
+
+    #[test]
+    fn scorer_matches_score_sentence() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut scorer = Scorer::new(&model, false);
+        for word in TEST_WITH_OOV {
+            scorer.push_word(word);
+        }
+        approx::assert_abs_diff_eq!(
+            scorer.total(),
+            model.score_sentence(TEST_WITH_OOV, false, false),
+            epsilon = f32::EPSILON
+        );
+
+        scorer.reset(true);
+        assert_eq!(scorer.total(), 0.);
+        for word in TEST_WITH_OOV {
+            scorer.push_word(word);
+        }
+        scorer.push_index(model.eos_idx());
+        approx::assert_abs_diff_eq!(
+            scorer.total(),
+            model.score_sentence(TEST_WITH_OOV, true, true),
+            epsilon = f32::EPSILON
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{Model, Scorer, State, WordIdx};
+use crate::Error;
+
+/// Incremental scoring of a sentence, one word at a time
+///
+/// Like a [Scorer], the session keeps the state and the accumulated score, additionally it counts
+/// the pushed words. [ScoreSession::snapshot] captures everything needed to continue scoring later,
+/// e.g. to persist the context of a user across requests or process restarts.
+///
+/// Pushed words can be undone with [ScoreSession::pop], or all at once back to a
+/// [ScoreSession::checkpoint] with [ScoreSession::rollback]. For this, the session keeps the
+/// states before the most recently pushed words, up to the limit set with
+/// [ScoreSession::with_history]. By default no history is kept, so sessions can be long-lived
+/// without growing. Words pushed before a restore can't be undone.
+/// ```
+/// use kenlm_rs::{Model, ScoreSession};
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+///
+/// let mut session = ScoreSession::new(&model, true);
+/// session.push("i");
+/// let snapshot = session.snapshot();
+///
+/// let mut restored = ScoreSession::restore(&model, &snapshot).unwrap();
+/// restored.push("have");
+/// session.push("have");
+/// assert_eq!(restored.log_prob(), session.log_prob());
+/// ```
+pub struct ScoreSession<'m> {
+    scorer: Scorer<'m>,
+    tokens: usize,
+    /// Identifies the checkpoints of this session
+    id: u64,
+    /// Identifies the current state, pushing a word gives it a new id
+    state_id: u64,
+    /// Number of words pushed since the session started, the source of new state ids
+    pushed: u64,
+    /// The states before the most recently pushed words, oldest first, with score and id
+    history: VecDeque<(State, f32, u64)>,
+    history_limit: usize,
+}
+
+/// Position in a [ScoreSession] to roll back to, see [ScoreSession::checkpoint]
+///
+/// A checkpoint belongs to the session and the sequence of pushed words it was taken from, it
+/// is rejected by other sessions and once the words it marks were undone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    session: u64,
+    state: u64,
+    tokens: usize,
+}
+
+fn next_session_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Serializable snapshot of a [ScoreSession]
+///
+/// `context` and `backoffs` hold the context of the state, most recent word first. With the
+/// `serde` feature, this implements `Serialize` and `Deserialize`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSnapshot {
+    pub context: Vec<u32>,
+    pub backoffs: Vec<f32>,
+    pub log_prob: f32,
+    pub tokens: usize,
+}
+
+impl<'m> ScoreSession<'m> {
+    /// Starts a session, in the context of `<s>` if `bos` is set
+    pub fn new(model: &'m Model, bos: bool) -> Self {
+        Self::with_scorer(Scorer::new(model, bos), 0)
+    }
+
+    fn with_scorer(scorer: Scorer<'m>, tokens: usize) -> Self {
+        ScoreSession {
+            scorer,
+            tokens,
+            id: next_session_id(),
+            state_id: 0,
+            pushed: 0,
+            history: VecDeque::new(),
+            history_limit: 0,
+        }
+    }
+
+    /// Keeps the states before the last `limit` pushed words, so they can be undone
+    ///
+    /// Older states are dropped, words pushed before them can't be undone anymore.
+    /// ```
+    /// use kenlm_rs::{Model, ScoreSession};
+    /// let model = Model::new("test_data/test.bin", false).unwrap();
+    ///
+    /// let mut session = ScoreSession::new(&model, true).with_history(1);
+    /// session.push("i");
+    /// session.push("have");
+    /// assert!(session.pop().is_some());
+    /// assert!(session.pop().is_none());
+    /// ```
+    pub fn with_history(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+        self
+    }
+
+    /// Restores a session from `snapshot`
+    ///
+    /// Fails with [Error::InvalidSnapshot] if the context of the snapshot is longer than the
+    /// model's order allows or its words are not part of the model's vocabulary.
+    pub fn restore(model: &'m Model, snapshot: &SessionSnapshot) -> Result<Self, Error> {
+        let max_context = usize::from(model.get_order()) - 1;
+        if snapshot.context.len() != snapshot.backoffs.len()
+            || snapshot.context.len() > max_context
+            || snapshot
+                .context
+                .iter()
+                .any(|&word| word as usize >= model.vocab_size())
+        {
+            return Err(Error::InvalidSnapshot);
+        }
+        let context = snapshot
+            .context
+            .iter()
+            .map(|&word| WordIdx::from_u32(word))
+            .collect::<Vec<_>>();
+        let state = State::from_context(model, &context, &snapshot.backoffs);
+        let mut scorer = Scorer::with_state(model, state);
+        scorer.set_total(snapshot.log_prob);
+        Ok(Self::with_scorer(scorer, snapshot.tokens))
+    }
+
+    /// Scores `word` given the words pushed so far, returns its log10 probability
+    pub fn push(&mut self, word: &str) -> f32 {
+        self.push_index(self.scorer.model().get_word_idx(word))
+    }
+
+    /// Scores `index` given the words pushed so far, returns its log10 probability
+    pub fn push_index(&mut self, index: WordIdx) -> f32 {
+        if self.history_limit > 0 {
+            if self.history.len() == self.history_limit {
+                self.history.pop_front();
+            }
+            self.history.push_back((
+                self.scorer.state().clone(),
+                self.scorer.total(),
+                self.state_id,
+            ));
+        }
+        self.pushed += 1;
+        self.state_id = self.pushed;
+        self.tokens += 1;
+        self.scorer.push_index(index)
+    }
+
+    /// Undoes the last pushed word, returns its log10 probability
+    ///
+    /// Returns `None` if there is no word to undo or its state is not in the history anymore.
+    pub fn pop(&mut self) -> Option<f32> {
+        let (state, total, state_id) = self.history.pop_back()?;
+        let log_prob = self.scorer.total() - total;
+        self.scorer.rewind(&state, total);
+        self.state_id = state_id;
+        self.tokens -= 1;
+        Some(log_prob)
+    }
+
+    /// Marks the current position to return to with [ScoreSession::rollback]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            session: self.id,
+            state: self.state_id,
+            tokens: self.tokens,
+        }
+    }
+
+    /// Undoes all words pushed after `checkpoint`, returns whether the session is at `checkpoint`
+    ///
+    /// Checkpoints of other sessions are rejected, as are checkpoints whose words were undone or
+    /// whose state is not in the history anymore. The session is unchanged then.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) -> bool {
+        if checkpoint.session != self.id || checkpoint.tokens > self.tokens {
+            return false;
+        }
+        if checkpoint.state == self.state_id {
+            return true;
+        }
+        let undo = self.tokens - checkpoint.tokens;
+        if undo == 0 || undo > self.history.len() {
+            return false;
+        }
+        let index = self.history.len() - undo;
+        let (state, total, state_id) = &self.history[index];
+        if *state_id != checkpoint.state {
+            return false;
+        }
+        self.scorer.rewind(state, *total);
+        self.state_id = checkpoint.state;
+        self.tokens = checkpoint.tokens;
+        self.history.truncate(index);
+        true
+    }
+
+    /// Scores `</s>` given the words pushed so far and returns the total log10 probability
+    pub fn finish(mut self) -> f32 {
+        self.push_index(self.scorer.model().eos_idx());
+        self.scorer.total()
+    }
+
+    /// Sum of the log10 probabilities of all words pushed so far
+    pub fn log_prob(&self) -> f32 {
+        self.scorer.total()
+    }
+
+    /// Number of words pushed so far
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
+    /// The current state
+    pub fn state(&self) -> &State {
+        self.scorer.state()
+    }
+
+    /// Captures the context and accumulated score of this session
+    pub fn snapshot(&self) -> SessionSnapshot {
+        let state = self.scorer.state();
+        SessionSnapshot {
+            context: state.words()[..state.len()].iter().map(|w| **w).collect(),
+            backoffs: state.backoffs().to_vec(),
+            log_prob: self.scorer.total(),
+            tokens: self.tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ScoreSession, SessionSnapshot};
+    use crate::model::test::TEST_WITH_OOV;
+    use crate::{Error, Model};
+
+    #[test]
+    fn restored_session_continues_scoring() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let (head, tail) = TEST_WITH_OOV.split_at(10);
+
+        let mut session = ScoreSession::new(&model, true);
+        for word in head {
+            session.push(word);
+        }
+        let snapshot = session.snapshot();
+        assert_eq!(snapshot.tokens, 10);
+        assert_eq!(snapshot.context.len(), 2);
+
+        let mut restored = ScoreSession::restore(&model, &snapshot).unwrap();
+        for word in tail {
+            restored.push(word);
+            session.push(word);
+        }
+        assert_eq!(restored.tokens(), TEST_WITH_OOV.len());
+        assert_eq!(restored.finish(), session.finish());
+        approx::assert_abs_diff_eq!(
+            ScoreSession::restore(&model, &snapshot).unwrap().log_prob(),
+            snapshot.log_prob
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_snapshots() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let too_long = SessionSnapshot {
+            context: vec![1, 2, 3],
+            backoffs: vec![0., 0., 0.],
+            log_prob: 0.,
+            tokens: 0,
+        };
+        assert!(matches!(
+            ScoreSession::restore(&model, &too_long),
+            Err(Error::InvalidSnapshot)
+        ));
+        let unknown_word = SessionSnapshot {
+            context: vec![1000],
+            backoffs: vec![0.],
+            log_prob: 0.,
+            tokens: 0,
+        };
+        assert!(matches!(
+            ScoreSession::restore(&model, &unknown_word),
+            Err(Error::InvalidSnapshot)
+        ));
+    }
+
+    #[test]
+    fn pop_and_rollback_undo_words() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut session = ScoreSession::new(&model, true).with_history(64);
+        assert_eq!(session.pop(), None);
+
+        session.push("i");
+        let checkpoint = session.checkpoint();
+        let log_prob = session.log_prob();
+        let state = session.state().clone();
+        let have = session.push("have");
+        approx::assert_abs_diff_eq!(session.pop().unwrap(), have, epsilon = 1e-6);
+        assert_eq!(session.log_prob(), log_prob);
+        assert_eq!(*session.state(), state);
+
+        for word in TEST_WITH_OOV {
+            session.push(word);
+        }
+        assert!(session.rollback(checkpoint));
+        assert_eq!(session.tokens(), 1);
+        assert_eq!(session.log_prob(), log_prob);
+        assert_eq!(*session.state(), state);
+        assert!(session.rollback(checkpoint));
+        assert_eq!(session.tokens(), 1);
+
+        session.pop();
+        assert_eq!(session.tokens(), 0);
+        assert_eq!(*session.state(), model.bos_state());
+        assert!(!session.rollback(checkpoint));
+        assert_eq!(session.tokens(), 0);
+    }
+
+    #[test]
+    fn rejects_foreign_and_undone_checkpoints() {
+        let model = Model::new("test_data/test.bin", false).expect("should exist");
+        let mut session = ScoreSession::new(&model, true).with_history(2);
+        let mut other = ScoreSession::new(&model, true).with_history(2);
+        let start = session.checkpoint();
+        assert!(!other.rollback(start));
+
+        // the checkpoint is on a branch which was undone
+        session.push("i");
+        let branch = session.checkpoint();
+        session.pop();
+        session.push("you");
+        assert_eq!(session.tokens(), 1);
+        assert!(!session.rollback(branch));
+        assert_eq!(session.tokens(), 1);
+
+        // the state of the checkpoint was dropped from the bounded history
+        session.push("have");
+        session.push("a");
+        assert!(!session.rollback(start));
+        assert_eq!(session.tokens(), 3);
+        assert!(session.pop().is_some());
+        assert!(session.pop().is_some());
+        assert_eq!(session.pop(), None);
+    }
+}
@@ -0,0 +1,46 @@
+/// Spellings of the sentence markers and the unknown word token
+///
+/// KenLM hard-wires `<s>`, `</s>` and `<unk>`, models built by other toolkits may use different
+/// spellings, e.g. `<S>` or `[UNK]`. Set on a model with `Model::set_special_tokens`, the
+/// default is KenLM's convention.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecialTokens {
+    pub bos: String,
+    pub eos: String,
+    pub unk: String,
+}
+
+impl Default for SpecialTokens {
+    fn default() -> Self {
+        Self {
+            bos: "<s>".into(),
+            eos: "</s>".into(),
+            unk: "<unk>".into(),
+        }
+    }
+}
+
+impl SpecialTokens {
+    /// Constructs KenLM's `<s>`, `</s>` and `<unk>`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the beginning of sentence token
+    pub fn bos(mut self, bos: &str) -> Self {
+        self.bos = bos.into();
+        self
+    }
+
+    /// Sets the end of sentence token
+    pub fn eos(mut self, eos: &str) -> Self {
+        self.eos = eos.into();
+        self
+    }
+
+    /// Sets the unknown word token
+    pub fn unk(mut self, unk: &str) -> Self {
+        self.unk = unk.into();
+        self
+    }
+}
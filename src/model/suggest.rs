@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+
+/// SymSpell-style deletion index over a vocabulary
+///
+/// Stores every string that can be derived from a vocabulary entry by deleting up to
+/// `max_edits` characters. Candidates for a query are found by looking up the deletions of the
+/// query, their actual edit distance is verified afterwards.
+#[derive(Debug, Clone)]
+pub(crate) struct SuggestionIndex {
+    max_edits: usize,
+    deletes: HashMap<String, Vec<usize>>,
+}
+
+impl SuggestionIndex {
+    pub(crate) fn new(vocab: &[String], max_edits: usize) -> Self {
+        let mut deletes: HashMap<String, Vec<usize>> = HashMap::new();
+        for (position, word) in vocab.iter().enumerate() {
+            for delete in deletions(word, max_edits) {
+                deletes.entry(delete).or_default().push(position);
+            }
+        }
+        Self { max_edits, deletes }
+    }
+
+    /// Returns the vocab positions and edit distances of all entries within `max_edits` of `query`
+    ///
+    /// `max_edits` is capped to the value the index was built with.
+    pub(crate) fn candidates(
+        &self,
+        vocab: &[String],
+        query: &str,
+        max_edits: usize,
+    ) -> Vec<(usize, usize)> {
+        let max_edits = max_edits.min(self.max_edits);
+        let mut seen = HashSet::new();
+        let mut candidates = vec![];
+        for delete in deletions(query, max_edits) {
+            for &position in self.deletes.get(&delete).into_iter().flatten() {
+                if !seen.insert(position) {
+                    continue;
+                }
+                let distance = levenshtein(query, &vocab[position]);
+                if distance <= max_edits {
+                    candidates.push((position, distance));
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// All strings obtained by deleting up to `max_edits` chars from `word`, including `word`
+fn deletions(word: &str, max_edits: usize) -> HashSet<String> {
+    let mut all = HashSet::new();
+    all.insert(word.to_string());
+    let mut frontier = vec![word.chars().collect::<Vec<_>>()];
+    for _ in 0..max_edits {
+        let mut next = vec![];
+        for chars in frontier {
+            for skip in 0..chars.len() {
+                let delete = chars
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| *idx != skip)
+                    .map(|(_, c)| *c)
+                    .collect::<Vec<_>>();
+                if all.insert(delete.iter().collect()) {
+                    next.push(delete);
+                }
+            }
+        }
+        frontier = next;
+    }
+    all
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::{levenshtein, SuggestionIndex};
+
+    #[test]
+    fn levenshtein_distance() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("have", "hav"), 1);
+        assert_eq!(levenshtein("hvae", "have"), 2);
+    }
+
+    #[test]
+    fn finds_candidates_within_distance() {
+        let vocab = ["have", "had", "shall", "hall"].map(String::from);
+        let index = SuggestionIndex::new(&vocab, 2);
+        let mut candidates = index.candidates(&vocab, "hal", 1);
+        candidates.sort();
+        assert_eq!(candidates, &[(1, 1), (3, 1)]);
+        let mut candidates = index.candidates(&vocab, "hal", 5);
+        candidates.sort();
+        assert_eq!(candidates, &[(0, 2), (1, 1), (2, 2), (3, 1)]);
+        assert!(index.candidates(&vocab, "xyz", 2).is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use super::{State, WordIdx};
+use crate::cxx::bridge;
+
+/// Explanation of how the score of a word was computed
+///
+/// Returned by [crate::Model::explain_score]. KenLM looks up the longest n-gram ending in the word that
+/// is stored in the model. For each longer context of the state, the backoff weight of that
+/// context is added to the probability of the matched n-gram:
+///
+/// `log_prob = ngram_log_prob + sum(backoffs)`
+#[derive(Debug, Clone)]
+pub struct ScoreTrace {
+    /// The index the word was mapped to
+    pub word_idx: WordIdx,
+    /// Whether the word is out of vocabulary
+    pub oov: bool,
+    /// The matched n-gram in sentence order, the last entry is `word_idx`
+    pub ngram: Vec<WordIdx>,
+    /// Probability of the matched n-gram as stored in the model, in log10-space
+    ///
+    /// Derived by subtracting the backoffs from [ScoreTrace::log_prob], so it may differ from
+    /// the stored value by float rounding.
+    pub ngram_log_prob: f32,
+    /// The backoff weights which were applied, from the shortest to the longest context
+    pub backoffs: Vec<AppliedBackoff>,
+    /// The final score of the word given the state, in log10-space
+    pub log_prob: f32,
+    /// Length of the matched n-gram as reported by KenLM, equals `ngram.len()`
+    pub ngram_length: u8,
+    /// Whether the score is independent of words further to the left, up to additional backoff
+    pub independent_left: bool,
+    /// Rest cost for extending to the left, equals `log_prob` unless the model stores rest costs
+    pub rest: f32,
+    /// The state after the word
+    pub out_state: State,
+}
+
+/// A backoff weight applied while scoring a word
+#[derive(Debug, Clone)]
+pub struct AppliedBackoff {
+    /// The context the backoff belongs to, in sentence order
+    pub context: Vec<WordIdx>,
+    /// The backoff weight of `context` in log10-space
+    pub backoff: f32,
+}
+
+impl ScoreTrace {
+    /// Builds the trace from the context of the input state and KenLM's `FullScoreReturn`
+    pub(crate) fn new(
+        in_state: &State,
+        word_idx: WordIdx,
+        oov: bool,
+        full: &bridge::lm::FullScoreReturn,
+        out_state: State,
+    ) -> Self {
+        let context = &in_state.words()[..in_state.len()];
+        let backoffs = in_state.backoffs();
+        let ngram_length = full.ngram_length as usize;
+        let log_prob = full.prob;
+        let in_sentence_order =
+            |len: usize| context[..len].iter().rev().copied().collect::<Vec<_>>();
+
+        let mut ngram = in_sentence_order(ngram_length - 1);
+        ngram.push(word_idx);
+
+        let backoffs = (ngram_length - 1..context.len())
+            .map(|len| AppliedBackoff {
+                context: in_sentence_order(len + 1),
+                backoff: backoffs[len],
+            })
+            .collect::<Vec<_>>();
+        let ngram_log_prob = log_prob - backoffs.iter().map(|b| b.backoff).sum::<f32>();
+
+        ScoreTrace {
+            word_idx,
+            oov,
+            ngram,
+            ngram_log_prob,
+            backoffs,
+            log_prob,
+            ngram_length: full.ngram_length,
+            independent_left: full.independent_left,
+            rest: full.rest,
+            out_state,
+        }
+    }
+}
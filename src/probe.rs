@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::io::{BufReader, Seek, SeekFrom};
+
+use crate::headers::{FixedParameters, ModelType, Sanity};
+use crate::reader::arpa::ArpaReader;
+use crate::Error;
+
+/// Kind of a model file, as determined by [probe]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FileKind {
+    /// A KenLM binary of the given type
+    Binary(ModelType),
+    /// An ARPA file
+    Arpa,
+    /// Neither, or a binary of an incompatible format version
+    Unknown,
+}
+
+/// Determines whether `file_name` is a KenLM binary, an ARPA file or neither
+///
+/// Only the headers are read. Binaries are recognized by their sanity header like KenLM's
+/// `RecognizeBinary` in src/cxx/lm/binary_format.cc does, ARPA files by their `\data\` section.
+/// Fails only if the file can't be opened.
+/// ```
+/// use kenlm_rs::{probe, FileKind, ModelType};
+///
+/// assert_eq!(probe("test_data/test.bin").unwrap(), FileKind::Binary(ModelType::Trie));
+/// assert_eq!(probe("test_data/arpa/lm_small.arpa").unwrap(), FileKind::Arpa);
+/// ```
+pub fn probe(file_name: &str) -> Result<FileKind, Error> {
+    let mut fd = File::open(file_name).map_err(|_| Error::FileNotFound(file_name.to_string()))?;
+    if let Some(model_type) = probe_binary(&mut fd) {
+        return Ok(FileKind::Binary(model_type));
+    }
+    fd.seek(SeekFrom::Start(0))?;
+    if ArpaReader::new(BufReader::new(&mut fd)).is_ok() {
+        return Ok(FileKind::Arpa);
+    }
+    Ok(FileKind::Unknown)
+}
+
+fn probe_binary(fd: &mut File) -> Option<ModelType> {
+    if Sanity::from_file(fd).ok()? != Sanity::REFERENCE {
+        return None;
+    }
+    FixedParameters::from_file(fd).ok()?.model_type().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{probe, FileKind};
+    use crate::headers::ModelType;
+    use crate::Error;
+
+    #[test]
+    fn recognizes_files() {
+        assert_eq!(
+            probe("test_data/carol_probing_bigram.bin").unwrap(),
+            FileKind::Binary(ModelType::Probing)
+        );
+        assert_eq!(probe("test_data/arpa/lm.arpa").unwrap(), FileKind::Arpa);
+        assert_eq!(
+            probe("test_data/arpa/arpa_no_data_header.arpa").unwrap(),
+            FileKind::Unknown
+        );
+        assert_eq!(probe("Cargo.toml").unwrap(), FileKind::Unknown);
+        assert!(matches!(
+            probe("test_data/missing.bin"),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs::File;
+#[cfg(any(feature = "gzip", feature = "bzip2", feature = "xz"))]
+use std::io::Read;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::{ArpaReadError, ArpaReader};
+
+/// Compression formats ARPA files are commonly shipped in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// Requires the `gzip` feature
+    Gzip,
+    /// Requires the `bzip2` feature
+    Bzip2,
+    /// Requires the `xz` feature
+    Xz,
+}
+
+impl Compression {
+    /// Detects the compression from the first bytes of a file
+    pub fn from_magic(magic: &[u8]) -> Self {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if magic.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+}
+
+impl ArpaReader<Box<dyn BufRead>> {
+    /// Opens the ARPA file at `path`, decompressing it if necessary
+    ///
+    /// The compression is detected from the magic bytes of the file rather than its extension.
+    /// Each format requires its feature, compressed files fail with
+    /// [ArpaReadError::UnsupportedCompression] otherwise.
+    /// ```
+    /// use kenlm_rs::reader::arpa::ArpaReader;
+    ///
+    /// let reader = ArpaReader::open("test_data/arpa/lm_small.arpa").unwrap();
+    /// assert_eq!(reader.order().get(), 3);
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArpaReadError> {
+        ArpaReader::new(open_decompressed(path.as_ref())?)
+    }
+}
+
+/// Opens `path` as a buffered reader over its decompressed contents
+pub fn open_decompressed(path: &Path) -> Result<Box<dyn BufRead>, ArpaReadError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let compression = Compression::from_magic(reader.fill_buf()?);
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(buffered(flate2::bufread::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Ok(buffered(bzip2::bufread::MultiBzDecoder::new(reader))),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(buffered(xz2::bufread::XzDecoder::new_multi_decoder(reader))),
+        #[allow(unreachable_patterns)]
+        compression => Err(ArpaReadError::UnsupportedCompression(compression)),
+    }
+}
+
+#[cfg(any(feature = "gzip", feature = "bzip2", feature = "xz"))]
+fn buffered(decoder: impl Read + 'static) -> Box<dyn BufRead> {
+    Box::new(BufReader::new(decoder))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Compression;
+    use crate::reader::arpa::{ArpaReadError, ArpaReader};
+
+    const COMPRESSED: [(&str, Compression, bool); 3] = [
+        (
+            "test_data/arpa/lm_small.arpa.gz",
+            Compression::Gzip,
+            cfg!(feature = "gzip"),
+        ),
+        (
+            "test_data/arpa/lm_small.arpa.bz2",
+            Compression::Bzip2,
+            cfg!(feature = "bzip2"),
+        ),
+        (
+            "test_data/arpa/lm_small.arpa.xz",
+            Compression::Xz,
+            cfg!(feature = "xz"),
+        ),
+    ];
+
+    #[test]
+    fn reads_compressed_like_plain() {
+        let plain = ArpaReader::open("test_data/arpa/lm_small.arpa")
+            .unwrap()
+            .into_arpa_sections()
+            .unwrap();
+        for (file_name, compression, enabled) in COMPRESSED {
+            let magic = std::fs::read(file_name).unwrap();
+            assert_eq!(Compression::from_magic(&magic), compression);
+            match ArpaReader::open(file_name) {
+                Ok(reader) => {
+                    assert!(enabled);
+                    let sections = reader.into_arpa_sections().unwrap();
+                    assert_eq!(sections.counts, plain.counts);
+                    assert_eq!(sections.no_backoff.len(), plain.no_backoff.len());
+                }
+                Err(err) => {
+                    assert!(!enabled);
+                    assert!(
+                        matches!(err, ArpaReadError::UnsupportedCompression(c) if c == compression)
+                    );
+                }
+            }
+        }
+    }
+}
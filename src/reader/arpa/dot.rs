@@ -0,0 +1,59 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use super::ArpaFileSections;
+use crate::reader::NGram;
+
+impl<F: Display> ArpaFileSections<F> {
+    /// Writes the n-gram structure in DOT (GraphViz) format
+    ///
+    /// Every n-gram becomes a node which is the context of the n-grams of the next order. Each
+    /// n-gram is connected to its context by an edge labelled with its last word and its log10
+    /// probability, unigrams hang off an empty root context. Nodes of n-grams with backoff
+    /// weights are labelled with them.
+    ///
+    /// This is meant for teaching and debugging with small models, the output of a real model
+    /// is too large to be rendered.
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "digraph arpa {{")?;
+        writeln!(writer, "  rankdir=LR;")?;
+        writeln!(writer, "  \"\" [label=\"ε\"];")?;
+        for section in &self.backoffs {
+            for entry in section {
+                let ngram = escape(&entry.ngram.0);
+                writeln!(
+                    writer,
+                    "  \"{ngram}\" [label=\"{ngram}\\nbackoff: {}\"];",
+                    entry.prob_backoff.backoff
+                )?;
+                write_edge(&mut writer, &entry.ngram, &entry.prob_backoff.log_prob)?;
+            }
+        }
+        for entry in &self.no_backoff {
+            write_edge(&mut writer, &entry.ngram, &entry.prob)?;
+        }
+        writeln!(writer, "}}")
+    }
+}
+
+fn write_edge<W: Write, F: Display>(
+    writer: &mut W,
+    ngram: &NGram,
+    log_prob: &F,
+) -> std::io::Result<()> {
+    let (context, word) = match ngram.0.rsplit_once(' ') {
+        Some((context, word)) => (context, word),
+        None => ("", ngram.0.as_str()),
+    };
+    writeln!(
+        writer,
+        "  \"{}\" -> \"{}\" [label=\"{} ({log_prob})\"];",
+        escape(context),
+        escape(&ngram.0),
+        escape(word)
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -1,11 +1,25 @@
 use itertools::Itertools;
-use std::str::SplitAsciiWhitespace;
+use std::marker::PhantomData;
+use std::str::FromStr;
 use std::{io::BufRead, num::NonZeroUsize};
 
 use crate::headers::{Counts, InvalidCounts, NGramCardinality};
 
 use super::{NGram, ProbBackoff, ProbBackoffNgram, ProbNgram};
 
+pub use compressed::{open_decompressed, Compression};
+pub use progress::ArpaProgress;
+#[cfg(feature = "mmap")]
+pub use slice::MappedArpa;
+pub use slice::{ArpaSliceEntries, ArpaSliceEntry};
+
+use sections::Sections;
+
+mod compressed;
+mod dot;
+mod progress;
+mod sections;
+mod slice;
 #[cfg(test)]
 mod test;
 
@@ -29,18 +43,65 @@ pub enum ArpaReadError {
     CountHeaderError(#[from] InvalidCounts),
     #[error("A boundary between sections is missing. An empty line is expected")]
     SectionBoundaryMissing,
+    #[error("The \\end\\ marker is missing after the last n-gram section.")]
+    EndMarkerMissing,
+    #[error("The file has no {0}-gram section")]
+    SectionMissing(NonZeroUsize),
     #[error("The no-backoff section is malformed.")]
     NoBackoffSectionError,
     #[error("An IO error occurred while reading the arpa file: {0}")]
     IoError(#[from] std::io::Error),
     #[error("Tried reading a section while being in the wrong state")]
     InvalidReaderState,
+    #[error("The file is {0:?}-compressed, enable the corresponding feature to read it")]
+    UnsupportedCompression(Compression),
+    #[error("{source} (line {line}: {content:?})")]
+    AtLine {
+        line: usize,
+        /// The offending line, truncated to [MAX_ERROR_CONTENT] bytes
+        content: String,
+        source: Box<ArpaReadError>,
+    },
+    #[error("Invalid value {value} on line {line}: {reason}")]
+    InvalidValue {
+        line: usize,
+        value: f64,
+        reason: &'static str,
+    },
+}
+
+impl ArpaReadError {
+    /// The line the error occurred on, if it is tied to one
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ArpaReadError::AtLine { line, .. } | ArpaReadError::InvalidValue { line, .. } => {
+                Some(*line)
+            }
+            _ => None,
+        }
+    }
 }
 
-pub struct ArpaFileSections {
+/// Maximum number of bytes of the offending line kept in [ArpaReadError::AtLine]
+pub const MAX_ERROR_CONTENT: usize = 80;
+
+/// Wraps `source` with the number and the truncated content of the line it occurred on
+fn at_line(line: usize, content: &str, source: ArpaReadError) -> ArpaReadError {
+    let mut end = content.len().min(MAX_ERROR_CONTENT);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    ArpaReadError::AtLine {
+        line,
+        content: content[..end].to_string(),
+        source: Box::new(source),
+    }
+}
+
+pub struct ArpaFileSections<F = f32> {
     pub counts: Counts,
-    pub backoffs: Vec<Vec<ProbBackoffNgram>>,
-    pub no_backoff: Vec<ProbNgram>,
+    pub backoffs: Vec<Vec<ProbBackoffNgram<F>>>,
+    pub no_backoff: Vec<ProbNgram<F>>,
 }
 
 /// Arpa reader
@@ -71,44 +132,166 @@ pub struct ArpaFileSections {
 /// to have two columns, `log_prob` and `ngram`. It is again split on whitespace,
 /// the first element is parsed to float, the rest is treated as a white-space
 /// separated n-gram.
-pub struct ArpaReader<B> {
+///
+/// The file ends with an `\end\` marker after the last n-gram section. Lines
+/// starting with `#` are comments and skipped wherever they occur.
+///
+/// Probabilities and backoffs are parsed into `F`, which is `f32` by default. Use
+/// [ArpaReader::with_precision] to parse them as `f64` where exactness matters.
+///
+/// In [strict](ArpaReader::strict) mode, values which would silently poison
+/// downstream scores are rejected together with the line they were found on.
+///
+/// Files written by SRILM are read as well: `\interpolated` markers before the
+/// `\data\` heading are skipped, lines of backoff sections with exactly as
+/// many words as the order of their section are given a backoff of zero. The
+/// placeholder log-probability [SRILM_ZERO_LOG_PROB] of `<s>` is kept, like KenLM
+/// does.
+pub struct ArpaReader<B, F = f32> {
     reader: B,
-    counts: Counts,
-    cur_section: NonZeroUsize,
+    sections: Sections,
+    /// Reused for every line read
+    buf: String,
+    precision: PhantomData<F>,
 }
 
+/// Largest absolute backoff accepted in strict mode
+///
+/// Backoffs are log10 weights, anything beyond this is far outside of what an
+/// estimator produces.
+pub const STRICT_MAX_ABS_BACKOFF: f64 = 100.0;
+
+/// Log-probability SRILM writes for `<s>` in place of `log10(0)`
+///
+/// `<s>` is never predicted. The value is read as is, mapping it to zero would give `<s>` a
+/// probability of one and un-normalise the unigram distribution.
+pub const SRILM_ZERO_LOG_PROB: f64 = -99.0;
+
 impl<B> ArpaReader<B>
 where
     B: BufRead,
 {
-    const ARPA_DATA_HEADER: &'_ str = "\\data\\";
-    const ARPA_NGRAM_KEY: &'_ str = "ngram ";
-
     /// Constructs the ArpaReader, parses the header
     ///
     /// Constructs the ArpaReader and validates it by parsing the count header
     /// describing the file.
     pub fn new(mut reader: B) -> Result<Self, ArpaReadError> {
-        let counts = Self::read_count_header(&mut reader)?;
+        let (counts, line) = read_count_header(&mut reader)?;
         Ok(Self {
-            counts,
             reader,
-            cur_section: NonZeroUsize::try_from(1).unwrap(),
+            sections: Sections::new(counts, line),
+            buf: String::new(),
+            precision: PhantomData,
         })
     }
+}
+
+impl<B, F> ArpaReader<B, F>
+where
+    B: BufRead,
+    F: FromStr + Copy + Default + Into<f64>,
+{
+    /// Switches the float type probabilities and backoffs are parsed into
+    ///
+    /// Some toolchains write probabilities with more digits than `f32` preserves:
+    /// ```
+    /// use std::io::BufReader;
+    /// use kenlm_rs::reader::arpa::ArpaReader;
+    ///
+    /// let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    /// let reader = ArpaReader::new(BufReader::new(fd)).unwrap().with_precision::<f64>();
+    /// let sections = reader.into_arpa_sections().unwrap();
+    /// let log_prob: f64 = sections.no_backoff[0].prob;
+    /// ```
+    pub fn with_precision<G: FromStr>(self) -> ArpaReader<B, G> {
+        let Self {
+            reader,
+            sections,
+            buf,
+            ..
+        } = self;
+        ArpaReader {
+            reader,
+            sections,
+            buf,
+            precision: PhantomData,
+        }
+    }
+
+    /// Toggles strict validation of probabilities and backoffs
+    ///
+    /// In strict mode NaN and infinite values, positive log-probabilities and
+    /// backoffs with an absolute value above [STRICT_MAX_ABS_BACKOFF] are
+    /// rejected with [ArpaReadError::InvalidValue], which carries the offending
+    /// line number.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.sections.set_strict(strict);
+        self
+    }
+
+    /// Reads only the sections up to `max_order`
+    ///
+    /// The section of `max_order` becomes the highest order section, its backoffs are dropped.
+    /// Sections of higher orders are not read at all, so lower order models can be built from
+    /// higher order files without pruning them first. Has no effect if `max_order` is not lower
+    /// than the order of the file.
+    /// ```
+    /// use std::io::BufReader;
+    /// use std::num::NonZeroUsize;
+    /// use kenlm_rs::reader::arpa::ArpaReader;
+    ///
+    /// let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    /// let reader = ArpaReader::new(BufReader::new(fd))
+    ///     .unwrap()
+    ///     .with_max_order(NonZeroUsize::new(2).unwrap());
+    /// assert_eq!(reader.order().get(), 2);
+    /// let sections = reader.into_arpa_sections().unwrap();
+    /// assert_eq!(sections.backoffs.len(), 1);
+    /// assert_eq!(sections.no_backoff.len(), 13);
+    /// ```
+    pub fn with_max_order(mut self, max_order: NonZeroUsize) -> Self {
+        self.sections.set_max_order(max_order);
+        self
+    }
+
+    /// Calls `callback` every `every` n-grams and after each section
+    ///
+    /// The callback gets the progress within the current section and the file, their totals
+    /// are taken from the count header:
+    /// ```
+    /// use std::io::BufReader;
+    /// use std::num::NonZeroUsize;
+    /// use kenlm_rs::reader::arpa::ArpaReader;
+    ///
+    /// let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    /// let reader = ArpaReader::new(BufReader::new(fd))
+    ///     .unwrap()
+    ///     .with_progress(NonZeroUsize::new(10).unwrap(), |progress| {
+    ///         eprintln!("{}-grams: {:.0}%", progress.order, progress.fraction() * 100.0)
+    ///     });
+    /// reader.into_arpa_sections().unwrap();
+    /// ```
+    pub fn with_progress(
+        mut self,
+        every: NonZeroUsize,
+        callback: impl FnMut(ArpaProgress) + Send + 'static,
+    ) -> Self {
+        self.sections.set_progress(every, Box::new(callback));
+        self
+    }
 
     /// Returns the order of the model
     ///
     /// Returns the order of the model described by the arpa file.
     pub fn order(&self) -> NonZeroUsize {
-        self.counts.order()
+        self.sections.order()
     }
 
     /// Returns the order of the model
     ///
     /// Returns the order of the model described by the arpa file.
     pub fn counts(&self) -> &Counts {
-        &self.counts
+        self.sections.counts()
     }
 
     /// Parse the n-gram sections
@@ -116,152 +299,276 @@ where
     /// Consumes the remainder of the reader and parses it according to the count-header of the file
     /// returns a tuple where the first element are the backoff sections in ascending ngram order,
     /// the second element is the highest order section which has no backoff values.
-    pub fn into_arpa_sections(mut self) -> Result<ArpaFileSections, ArpaReadError> {
-        let mut backoffs = vec![];
-        while let Some(backoff) = self.next_backoff_section()? {
-            backoffs.push(backoff)
+    pub fn into_arpa_sections(mut self) -> Result<ArpaFileSections<F>, ArpaReadError> {
+        let order = self.order();
+        let counts = self.counts().counts();
+        let mut backoffs = counts[..order.get() - 1]
+            .iter()
+            .map(|count| Vec::with_capacity(count.cardinality))
+            .collect::<Vec<_>>();
+        let mut no_backoff = Vec::with_capacity(counts[order.get() - 1].cardinality);
+        while !self.sections.is_done() {
+            if let Some(entry) = self.next_line(false)? {
+                let ngram = to_backoff_ngram(&entry);
+                if entry.order < order {
+                    backoffs[entry.order.get() - 1].push(ngram);
+                } else {
+                    no_backoff.push(ProbNgram {
+                        ngram: ngram.ngram,
+                        prob: ngram.prob_backoff.log_prob,
+                    });
+                }
+            }
         }
-        let no_backoff = self.read_no_backoff_section()?;
-        let Self { counts, .. } = self;
         Ok(ArpaFileSections {
-            counts,
+            counts: self.sections.into_counts(),
             backoffs,
             no_backoff,
         })
     }
 
-    fn read_count_header(reader: &mut B) -> Result<Counts, ArpaReadError> {
-        let mut reader = reader.lines();
-        match reader.next().transpose()?.as_deref() {
-            Some(Self::ARPA_DATA_HEADER) => {}
-            _ => {
-                return Err(ArpaReadError::DataHeaderMissing);
-            }
+    /// Parses only the sections of `orders`, skipping the others
+    ///
+    /// Returns the selected sections in ascending order. Lines of skipped sections are counted but
+    /// not parsed and reading stops after the highest selected section. Like with
+    /// [ArpaReader::into_entries], n-grams of the highest order have a backoff of zero.
+    /// ```
+    /// use std::io::BufReader;
+    /// use std::num::NonZeroUsize;
+    /// use kenlm_rs::reader::arpa::ArpaReader;
+    ///
+    /// let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    /// let reader = ArpaReader::new(BufReader::new(fd)).unwrap();
+    /// let sections = reader
+    ///     .into_selected_sections([NonZeroUsize::new(2).unwrap()])
+    ///     .unwrap();
+    /// assert_eq!(sections[0].0.get(), 2);
+    /// assert_eq!(sections[0].1.len(), 13);
+    /// ```
+    pub fn into_selected_sections(
+        mut self,
+        orders: impl IntoIterator<Item = NonZeroUsize>,
+    ) -> Result<Vec<(NonZeroUsize, Vec<ProbBackoffNgram<F>>)>, ArpaReadError> {
+        let mut orders = orders.into_iter().collect::<Vec<_>>();
+        orders.sort_unstable();
+        orders.dedup();
+        let last = match orders.last() {
+            Some(&last) => last,
+            None => return Ok(vec![]),
+        };
+        if last > self.order() {
+            return Err(ArpaReadError::SectionMissing(last));
         }
 
-        let mut counts = vec![];
-        while let Some(line) = reader.next().transpose()? {
-            if line.trim().is_empty() {
-                break;
-            }
-
-            if let Some(suffix) = line.strip_prefix(Self::ARPA_NGRAM_KEY) {
-                counts.push(NGramCardinality::try_from_ngram_line_suffix(suffix)?);
+        let mut sections = orders
+            .iter()
+            .map(|&order| {
+                let cardinality = self.counts().get(order).map_or(0, |c| c.cardinality);
+                (order, Vec::with_capacity(cardinality))
+            })
+            .collect::<Vec<_>>();
+        while !self.sections.is_done() && self.sections.section() <= last {
+            let selected = orders.binary_search(&self.sections.section());
+            if let Some(entry) = self.next_line(selected.is_err())? {
+                if let Ok(idx) = selected {
+                    sections[idx].1.push(to_backoff_ngram(&entry));
+                }
             }
         }
-        if counts.is_empty() {
-            return Err(ArpaReadError::NgramCountsMissing);
+        Ok(sections)
+    }
+
+    /// Parses only the unigram section, e.g. for the vocabulary and unigram priors
+    ///
+    /// See [ArpaReader::into_selected_sections].
+    pub fn into_unigrams(self) -> Result<Vec<ProbBackoffNgram<F>>, ArpaReadError> {
+        let unigrams = NonZeroUsize::new(1).unwrap();
+        Ok(self
+            .into_selected_sections([unigrams])?
+            .pop()
+            .map(|(_, ngrams)| ngrams)
+            .unwrap_or_default())
+    }
+
+    /// Streams the n-gram entries instead of collecting them into sections
+    ///
+    /// Yields every n-gram with its order, in the order of the file. Only the current line is
+    /// held in memory, so arbitrarily large files can be processed. Entries of the highest order
+    /// have no backoff in the file, they are yielded with a backoff of zero. The iterator ends
+    /// after the first error.
+    /// ```
+    /// use std::io::BufReader;
+    /// use kenlm_rs::reader::arpa::ArpaReader;
+    ///
+    /// let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    /// let reader = ArpaReader::new(BufReader::new(fd)).unwrap();
+    /// let bigrams = reader
+    ///     .into_entries()
+    ///     .map(Result::unwrap)
+    ///     .filter(|(order, _)| order.get() == 2)
+    ///     .count();
+    /// assert_eq!(bigrams, 13);
+    /// ```
+    pub fn into_entries(self) -> ArpaEntries<B, F> {
+        ArpaEntries {
+            reader: self,
+            done: false,
         }
-        let counts = counts.into_iter().collect();
-        Ok(Counts::from_count_vec(counts)?)
     }
 
-    fn next_backoff_section(&mut self) -> Result<Option<Vec<ProbBackoffNgram>>, ArpaReadError> {
-        if self.cur_section >= self.order() {
-            return Ok(None);
+    /// Reads the unigram section, returns the `words` it does not contain and the first unigram
+    /// with a positive log-probability
+    ///
+    /// Has to be called before any other section is read.
+    pub(crate) fn missing_unigrams<'w>(
+        &mut self,
+        words: &[&'w str],
+    ) -> Result<(Vec<&'w str>, Option<String>), ArpaReadError> {
+        let unigrams = NonZeroUsize::new(1).unwrap();
+        if self.sections.section() != unigrams {
+            return Err(ArpaReadError::InvalidReaderState);
         }
-        let count = if let Some(cnt) = self.counts.get(self.cur_section) {
-            cnt
-        } else {
-            return Ok(None);
-        };
+        let mut missing = words.to_vec();
+        let mut positive = None;
+        while !self.sections.is_done() && self.sections.section() == unigrams {
+            if let Some(entry) = self.next_line(false)? {
+                missing.retain(|&word| word != entry.ngram);
+                if positive.is_none() && entry.log_prob.into() > 0.0 {
+                    positive = Some(entry.ngram.to_string());
+                }
+            }
+        }
+        Ok((missing, positive))
+    }
 
-        let mut reader = (&mut self.reader).lines();
-        if let Some(next_line) = reader.next().transpose()? {
-            matches_ngram_section_header(&next_line, count.order)?
+    /// Reads the next line into the line buffer and feeds it to the section state machine
+    ///
+    /// The buffer is reused for every line, only n-grams which are kept are allocated.
+    fn next_line(&mut self, skip: bool) -> Result<Option<ArpaSliceEntry<'_, F>>, ArpaReadError> {
+        let line = if read_line(&mut self.reader, &mut self.buf)? {
+            Some(self.buf.as_str())
         } else {
-            return Err(ArpaReadError::NGramSectionHeaderMissing);
+            None
         };
+        self.sections.feed(line, skip)
+    }
+}
 
-        let prob_backoff_ngrams = (&mut reader)
-            .take(count.cardinality)
-            .map(|s| s.map_err(|_| ArpaReadError::BackOffSectionError))
-            .map(|s| ProbBackoffNgram::try_from_arpa_line(&s?))
-            .collect::<Result<Vec<ProbBackoffNgram>, ArpaReadError>>()?;
+/// Iterator over the n-gram entries of an ARPA file, see [ArpaReader::into_entries]
+pub struct ArpaEntries<B, F = f32> {
+    reader: ArpaReader<B, F>,
+    done: bool,
+}
 
-        if prob_backoff_ngrams.len() != count.cardinality {
-            return Err(ArpaReadError::NgramCountsMismatch);
-        }
-        if let Some(line) = reader.next().transpose()? {
-            if !line.trim().is_empty() {
-                return Err(ArpaReadError::SectionBoundaryMissing);
+impl<B, F> ArpaEntries<B, F>
+where
+    B: BufRead,
+    F: FromStr + Copy + Default + Into<f64>,
+{
+    fn next_entry(&mut self) -> Result<Option<(NonZeroUsize, ProbBackoffNgram<F>)>, ArpaReadError> {
+        while !self.reader.sections.is_done() {
+            if let Some(entry) = self.reader.next_line(false)? {
+                return Ok(Some((entry.order, to_backoff_ngram(&entry))));
             }
         }
-        self.cur_section = self.cur_section.saturating_add(1);
-        Ok(Some(prob_backoff_ngrams))
+        Ok(None)
     }
+}
 
-    fn read_no_backoff_section(&mut self) -> Result<Vec<ProbNgram>, ArpaReadError> {
-        if self.cur_section != self.order() {
-            return Err(ArpaReadError::InvalidReaderState);
-        }
-
-        let mut reader = (&mut self.reader).lines();
-        let counts = self.counts.highest_order_count();
+impl<B, F> Iterator for ArpaEntries<B, F>
+where
+    B: BufRead,
+    F: FromStr + Copy + Default + Into<f64>,
+{
+    type Item = Result<(NonZeroUsize, ProbBackoffNgram<F>), ArpaReadError>;
 
-        if let Some(line) = reader.next().transpose()? {
-            matches_ngram_section_header(&line, counts.order)?;
-        } else {
-            return Err(ArpaReadError::NGramSectionHeaderMissing);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
-        let prob_backoff_ngrams = (&mut reader)
-            .take(counts.cardinality)
-            .map(|s| s.map_err(|_| ArpaReadError::BackOffSectionError))
-            .map(|s| ProbNgram::try_from_arpa_line(&s?))
-            .collect::<Result<Vec<ProbNgram>, ArpaReadError>>()?;
-        if prob_backoff_ngrams.len() != counts.cardinality {
-            return Err(ArpaReadError::NgramCountsMismatch);
+        let entry = self.next_entry().transpose();
+        if !matches!(entry, Some(Ok(_))) {
+            self.done = true;
         }
-        if let Some(Ok(line)) = reader.next() {
-            if !line.trim().is_empty() {
-                return Err(ArpaReadError::SectionBoundaryMissing);
-            }
-        }
-        self.cur_section = self.cur_section.saturating_add(1);
-        Ok(prob_backoff_ngrams)
+        entry
     }
 }
 
-impl ProbNgram {
-    fn try_from_arpa_line(line: &str) -> Result<Self, ArpaReadError> {
-        let mut pieces = line.split_ascii_whitespace();
-        let log_prob = next_log_prob(&mut pieces)?;
+/// Copies a borrowed entry, the words of its n-gram are separated by single spaces
+fn to_backoff_ngram<F: Copy>(entry: &ArpaSliceEntry<'_, F>) -> ProbBackoffNgram<F> {
+    ProbBackoffNgram {
+        ngram: NGram(entry.words().join(" ")),
+        prob_backoff: ProbBackoff {
+            log_prob: entry.log_prob,
+            backoff: entry.backoff,
+        },
+    }
+}
 
-        let ngram = pieces.join(" ");
+const ARPA_DATA_HEADER: &str = "\\data\\";
+const ARPA_NGRAM_KEY: &str = "ngram ";
+const ARPA_INTERPOLATED: &str = "\\interpolated";
+const ARPA_END_MARKER: &str = "\\end\\";
 
-        Ok(Self {
-            ngram: NGram(ngram),
-            prob: log_prob,
-        })
-    }
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#')
 }
 
-fn next_log_prob(pieces: &mut SplitAsciiWhitespace) -> Result<f32, ArpaReadError> {
-    pieces
-        .next()
-        .map(str::parse::<f32>)
-        .ok_or(ArpaReadError::NoBackoffSectionError)?
-        .map_err(|_| ArpaReadError::NoBackoffSectionError)
+/// Reads a line into `line` without its line break, returns `false` at the end of `reader`
+fn read_line<B: BufRead>(reader: &mut B, line: &mut String) -> std::io::Result<bool> {
+    line.clear();
+    if reader.read_line(line)? == 0 {
+        return Ok(false);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(true)
 }
 
-impl ProbBackoffNgram {
-    fn try_from_arpa_line(line: &str) -> Result<Self, ArpaReadError> {
-        let mut pieces = line.split_ascii_whitespace();
-        let log_prob = next_log_prob(&mut pieces)?;
-        let mut pieces = pieces.rev();
-        let backoff = if let Some(Ok(backoff)) = pieces.next().map(str::parse::<f32>) {
-            backoff
-        } else {
-            return Err(ArpaReadError::BackOffSectionError);
-        };
+/// Parses the `\data\` section, returns the counts and the number of lines consumed.
+fn read_count_header<B: BufRead>(reader: &mut B) -> Result<(Counts, usize), ArpaReadError> {
+    let mut line = String::new();
+    let mut n_lines = 0;
+    loop {
+        n_lines += 1;
+        if !read_line(reader, &mut line)? {
+            return Err(ArpaReadError::DataHeaderMissing);
+        }
+        match line.as_str() {
+            ARPA_DATA_HEADER => break,
+            // SRILM marks interpolated models before the data section
+            line if line.trim() == ARPA_INTERPOLATED
+                || line.trim().is_empty()
+                || is_comment(line) => {}
+            _ => {
+                return Err(ArpaReadError::DataHeaderMissing);
+            }
+        }
+    }
 
-        let ngram = pieces.rev().join(" ");
+    let mut counts = vec![];
+    while read_line(reader, &mut line)? {
+        n_lines += 1;
+        if line.trim().is_empty() {
+            break;
+        }
 
-        Ok(Self {
-            ngram: NGram(ngram),
-            prob_backoff: ProbBackoff { log_prob, backoff },
-        })
+        if let Some(suffix) = line.strip_prefix(ARPA_NGRAM_KEY) {
+            counts.push(
+                NGramCardinality::try_from_ngram_line_suffix(suffix)
+                    .map_err(|err| at_line(n_lines, &line, err))?,
+            );
+        }
+    }
+    if counts.is_empty() {
+        return Err(ArpaReadError::NgramCountsMissing);
     }
+    let counts = counts.into_iter().collect();
+    Ok((Counts::from_count_vec(counts)?, n_lines))
 }
 
 fn matches_ngram_section_header(line: &str, order: NonZeroUsize) -> Result<(), ArpaReadError> {
@@ -0,0 +1,87 @@
+use std::num::NonZeroUsize;
+
+use crate::headers::Counts;
+
+/// Progress of parsing an ARPA file, see [super::ArpaReader::with_progress]
+///
+/// The totals are known from the count header, so they can be used to size a progress bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArpaProgress {
+    /// Order of the section which is being read
+    pub order: NonZeroUsize,
+    /// N-grams read from the current section
+    pub section_read: usize,
+    /// N-grams in the current section
+    pub section_total: usize,
+    /// N-grams read from the file
+    pub read: usize,
+    /// N-grams in the file
+    pub total: usize,
+}
+
+impl ArpaProgress {
+    /// Whether the current section was read completely
+    pub fn section_done(&self) -> bool {
+        self.section_read == self.section_total
+    }
+
+    /// Share of the n-grams of the file which were read, between 0 and 1
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.read as f64 / self.total as f64
+        }
+    }
+}
+
+fn total(counts: &Counts) -> usize {
+    counts.counts().iter().map(|count| count.cardinality).sum()
+}
+
+pub(super) struct Progress {
+    every: NonZeroUsize,
+    callback: Box<dyn FnMut(ArpaProgress) + Send>,
+    state: ArpaProgress,
+}
+
+impl Progress {
+    pub(super) fn new(
+        counts: &Counts,
+        every: NonZeroUsize,
+        callback: Box<dyn FnMut(ArpaProgress) + Send>,
+    ) -> Self {
+        let first = counts.counts()[0];
+        Progress {
+            every,
+            callback,
+            state: ArpaProgress {
+                order: first.order,
+                section_read: 0,
+                section_total: first.cardinality,
+                read: 0,
+                total: total(counts),
+            },
+        }
+    }
+
+    pub(super) fn set_total(&mut self, counts: &Counts) {
+        self.state.total = total(counts);
+    }
+
+    /// Starts a section of `order` with `total` n-grams
+    pub(super) fn section(&mut self, order: NonZeroUsize, total: usize) {
+        self.state.order = order;
+        self.state.section_read = 0;
+        self.state.section_total = total;
+    }
+
+    /// Counts a read n-gram, calls the callback every `every` n-grams and at the end of a section
+    pub(super) fn advance(&mut self) {
+        self.state.section_read += 1;
+        self.state.read += 1;
+        if self.state.read % self.every.get() == 0 || self.state.section_done() {
+            (self.callback)(self.state);
+        }
+    }
+}
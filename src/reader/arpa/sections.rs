@@ -0,0 +1,279 @@
+use std::{num::NonZeroUsize, str::FromStr};
+
+use crate::headers::Counts;
+
+use super::{
+    at_line, is_comment, matches_ngram_section_header, progress::Progress, ArpaProgress,
+    ArpaReadError, ArpaSliceEntry, ARPA_END_MARKER, STRICT_MAX_ABS_BACKOFF,
+};
+
+/// What the next line of the n-gram sections is expected to be
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Header,
+    /// N-grams left in the current section
+    Entries(usize),
+    Boundary,
+    End,
+    Done,
+}
+
+/// The line-level state machine of the n-gram sections of an ARPA file
+///
+/// The readers only differ in where their lines come from, they feed every line after the count
+/// header to [Sections::feed]. This tracks the current section and the line number, checks the
+/// headers, boundaries and the `\end\` marker, and parses and validates the n-grams.
+pub(super) struct Sections {
+    counts: Counts,
+    /// Order of the file, [Sections::order] is lower if it was capped
+    file_order: NonZeroUsize,
+    section: NonZeroUsize,
+    step: Step,
+    line: usize,
+    strict: bool,
+    progress: Option<Progress>,
+}
+
+impl Sections {
+    /// Starts before the first n-gram section, `line` is the number of lines of the count header
+    pub(super) fn new(counts: Counts, line: usize) -> Self {
+        Self {
+            file_order: counts.order(),
+            counts,
+            section: NonZeroUsize::new(1).unwrap(),
+            step: Step::Header,
+            line,
+            strict: false,
+            progress: None,
+        }
+    }
+
+    pub(super) fn counts(&self) -> &Counts {
+        &self.counts
+    }
+
+    pub(super) fn into_counts(self) -> Counts {
+        self.counts
+    }
+
+    pub(super) fn order(&self) -> NonZeroUsize {
+        self.counts.order()
+    }
+
+    /// Order of the section which is being read
+    pub(super) fn section(&self) -> NonZeroUsize {
+        self.section
+    }
+
+    /// Whether the last section, and the `\end\` marker unless the order is capped, were read
+    pub(super) fn is_done(&self) -> bool {
+        matches!(self.step, Step::Done)
+    }
+
+    pub(super) fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub(super) fn set_max_order(&mut self, max_order: NonZeroUsize) {
+        self.counts.truncate(max_order);
+        if let Some(progress) = &mut self.progress {
+            progress.set_total(&self.counts);
+        }
+    }
+
+    pub(super) fn set_progress(
+        &mut self,
+        every: NonZeroUsize,
+        callback: Box<dyn FnMut(ArpaProgress) + Send>,
+    ) {
+        self.progress = Some(Progress::new(&self.counts, every, callback));
+    }
+
+    /// Feeds the next line of the file, `None` at its end
+    ///
+    /// Returns the n-gram if `line` is one. With `skip`, n-gram lines are counted without being
+    /// parsed. Comments are skipped wherever they occur.
+    pub(super) fn feed<'l, F>(
+        &mut self,
+        line: Option<&'l str>,
+        skip: bool,
+    ) -> Result<Option<ArpaSliceEntry<'l, F>>, ArpaReadError>
+    where
+        F: FromStr + Copy + Default + Into<f64>,
+    {
+        if let Some(line) = line {
+            self.line += 1;
+            if is_comment(line) {
+                return Ok(None);
+            }
+        }
+        match (self.step, line) {
+            (Step::Done, _) => Err(ArpaReadError::InvalidReaderState),
+            (Step::Header, None) => Err(ArpaReadError::NGramSectionHeaderMissing),
+            (Step::Header, Some(line)) => {
+                matches_ngram_section_header(line, self.section)
+                    .map_err(|err| at_line(self.line, line, err))?;
+                let cardinality = self
+                    .counts
+                    .get(self.section)
+                    .map_or(0, |count| count.cardinality);
+                if let Some(progress) = &mut self.progress {
+                    progress.section(self.section, cardinality);
+                }
+                self.step = Self::entries(cardinality);
+                Ok(None)
+            }
+            (Step::Entries(_), None) => Err(ArpaReadError::NgramCountsMismatch),
+            (Step::Entries(remaining), Some(line)) => {
+                self.step = Self::entries(remaining - 1);
+                let entry = if skip {
+                    None
+                } else {
+                    Some(self.parse_entry(line)?)
+                };
+                if let Some(progress) = &mut self.progress {
+                    progress.advance();
+                }
+                Ok(entry)
+            }
+            (Step::Boundary, Some(line)) if !line.trim().is_empty() => Err(at_line(
+                self.line,
+                line,
+                ArpaReadError::SectionBoundaryMissing,
+            )),
+            (Step::Boundary, _) => {
+                self.section = self.section.saturating_add(1);
+                self.step = if self.counts.get(self.section).is_some() {
+                    Step::Header
+                } else if self.order() < self.file_order {
+                    // the sections above the capped order are not read
+                    Step::Done
+                } else {
+                    Step::End
+                };
+                Ok(None)
+            }
+            (Step::End, None) => Err(ArpaReadError::EndMarkerMissing),
+            (Step::End, Some(line)) => match line.trim() {
+                ARPA_END_MARKER => {
+                    self.step = Step::Done;
+                    Ok(None)
+                }
+                "" => Ok(None),
+                _ => Err(at_line(self.line, line, ArpaReadError::EndMarkerMissing)),
+            },
+        }
+    }
+
+    fn entries(remaining: usize) -> Step {
+        if remaining == 0 {
+            Step::Boundary
+        } else {
+            Step::Entries(remaining)
+        }
+    }
+
+    /// Parses an n-gram line of the current section, its backoff is dropped if the section is the
+    /// highest order one
+    fn parse_entry<'l, F>(&self, line: &'l str) -> Result<ArpaSliceEntry<'l, F>, ArpaReadError>
+    where
+        F: FromStr + Copy + Default + Into<f64>,
+    {
+        let order = self.section;
+        let (log_prob, ngram, backoff) = split_entry(line, order, order < self.file_order)
+            .map_err(|err| at_line(self.line, line, err))?;
+        let backoff = if order < self.order() {
+            backoff
+        } else {
+            F::default()
+        };
+        self.validate_log_prob(log_prob)?;
+        self.validate_backoff(backoff)?;
+        Ok(ArpaSliceEntry {
+            order,
+            ngram,
+            log_prob,
+            backoff,
+        })
+    }
+
+    fn validate_log_prob<F: Into<f64>>(&self, log_prob: F) -> Result<(), ArpaReadError> {
+        if !self.strict {
+            return Ok(());
+        }
+        let value = log_prob.into();
+        if value.is_nan() {
+            Err(self.invalid_value(value, "log-probability is NaN"))
+        } else if value.is_infinite() && value > 0.0 {
+            Err(self.invalid_value(value, "log-probability is infinite"))
+        } else if value > 0.0 {
+            Err(self.invalid_value(value, "log-probability is positive"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_backoff<F: Into<f64>>(&self, backoff: F) -> Result<(), ArpaReadError> {
+        if !self.strict {
+            return Ok(());
+        }
+        let value = backoff.into();
+        if value.is_nan() {
+            Err(self.invalid_value(value, "backoff is NaN"))
+        } else if value.is_infinite() {
+            Err(self.invalid_value(value, "backoff is infinite"))
+        } else if value.abs() > STRICT_MAX_ABS_BACKOFF {
+            Err(self.invalid_value(value, "backoff is out of range"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn invalid_value(&self, value: f64, reason: &'static str) -> ArpaReadError {
+        ArpaReadError::InvalidValue {
+            line: self.line,
+            value,
+            reason,
+        }
+    }
+}
+
+/// Splits a line of the section of `order` into log-probability, n-gram and backoff
+///
+/// Lines of sections with backoff which have exactly `order` words have a backoff of zero.
+/// Malformed lines are reported as [ArpaReadError::BackOffSectionError] or
+/// [ArpaReadError::NoBackoffSectionError], depending on the kind of section.
+fn split_entry<F: FromStr + Default>(
+    line: &str,
+    order: NonZeroUsize,
+    has_backoff: bool,
+) -> Result<(F, &str, F), ArpaReadError> {
+    let malformed = if has_backoff {
+        ArpaReadError::BackOffSectionError
+    } else {
+        ArpaReadError::NoBackoffSectionError
+    };
+    let line = line.trim_matches(|c: char| c.is_ascii_whitespace());
+    let (log_prob, rest) = line
+        .split_once(|c: char| c.is_ascii_whitespace())
+        .unwrap_or((line, ""));
+    let log_prob = match log_prob.parse::<F>() {
+        Ok(log_prob) => log_prob,
+        Err(_) => return Err(malformed),
+    };
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    if !has_backoff || rest.split_ascii_whitespace().count() == order.get() {
+        return Ok((log_prob, rest, F::default()));
+    }
+    let (ngram, backoff) = rest
+        .rsplit_once(|c: char| c.is_ascii_whitespace())
+        .ok_or(ArpaReadError::BackOffSectionError)?;
+    let backoff = backoff
+        .parse::<F>()
+        .map_err(|_| ArpaReadError::BackOffSectionError)?;
+    Ok((
+        log_prob,
+        ngram.trim_end_matches(|c: char| c.is_ascii_whitespace()),
+        backoff,
+    ))
+}
@@ -0,0 +1,307 @@
+#[cfg(feature = "mmap")]
+use std::{fs::File, path::Path};
+use std::{marker::PhantomData, num::NonZeroUsize, str::FromStr, str::SplitAsciiWhitespace};
+
+use crate::headers::Counts;
+
+use super::{read_count_header, sections::Sections, ArpaProgress, ArpaReadError};
+
+/// A memory-mapped ARPA file
+///
+/// The n-grams of [MappedArpa::entries] borrow from the mapping, parsing does not allocate per
+/// line. Requires the `mmap` feature.
+/// ```
+/// # #[cfg(feature = "mmap")]
+/// # {
+/// use kenlm_rs::reader::arpa::MappedArpa;
+///
+/// let arpa = MappedArpa::open("test_data/arpa/lm_small.arpa").unwrap();
+/// let unigrams = arpa
+///     .entries()
+///     .unwrap()
+///     .map(Result::unwrap)
+///     .filter(|entry| entry.order.get() == 1)
+///     .map(|entry| entry.ngram)
+///     .collect::<Vec<&str>>();
+/// assert_eq!(unigrams[1], "<s>");
+/// # }
+/// ```
+#[cfg(feature = "mmap")]
+pub struct MappedArpa {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedArpa {
+    /// Maps the ARPA file at `path`
+    ///
+    /// The file must not be modified while it is mapped. Compressed files have to be decompressed
+    /// first, see [super::ArpaReader::open].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArpaReadError> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is only read. Like for KenLM's own mapped models, truncating or
+        // writing to the file while it is mapped is ruled out by the caller.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { map })
+    }
+
+    /// Parses the count header and returns an iterator over the n-gram entries
+    pub fn entries(&self) -> Result<ArpaSliceEntries<'_>, ArpaReadError> {
+        ArpaSliceEntries::new(&self.map)
+    }
+
+    /// The mapped bytes of the file
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+/// An n-gram entry borrowed from the ARPA file it was parsed from
+///
+/// Entries of the highest order have no backoff in the file, their backoff is zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArpaSliceEntry<'a, F = f32> {
+    /// Order of the section the entry was read from
+    pub order: NonZeroUsize,
+    /// The words of the n-gram, separated as in the file
+    pub ngram: &'a str,
+    /// Log10-probability of the n-gram
+    pub log_prob: F,
+    /// Log10-backoff of the n-gram, zero if the file has none for it
+    pub backoff: F,
+}
+
+impl<'a, F> ArpaSliceEntry<'a, F> {
+    pub fn words(&self) -> SplitAsciiWhitespace<'a> {
+        self.ngram.split_ascii_whitespace()
+    }
+}
+
+/// Iterator over the n-gram entries of an ARPA file held in memory
+///
+/// This is the zero-copy counterpart of [super::ArpaEntries]: lines are split off the byte slice
+/// in place instead of being read into a `String` each. Both run the same section state machine,
+/// so comments, the `\end\` marker, SRILM's quirks, strict validation, progress and capped orders
+/// are handled the same way. The iterator ends after the first error.
+pub struct ArpaSliceEntries<'a, F = f32> {
+    rest: &'a [u8],
+    sections: Sections,
+    done: bool,
+    precision: PhantomData<F>,
+}
+
+impl<'a> ArpaSliceEntries<'a> {
+    /// Parses the count header of the ARPA file in `data`
+    pub fn new(data: &'a [u8]) -> Result<Self, ArpaReadError> {
+        let mut rest = data;
+        let (counts, line) = read_count_header(&mut rest)?;
+        Ok(Self {
+            rest,
+            sections: Sections::new(counts, line),
+            done: false,
+            precision: PhantomData,
+        })
+    }
+}
+
+impl<'a, F> ArpaSliceEntries<'a, F>
+where
+    F: FromStr + Copy + Default + Into<f64>,
+{
+    /// Switches the float type probabilities and backoffs are parsed into
+    pub fn with_precision<G: FromStr>(self) -> ArpaSliceEntries<'a, G> {
+        let Self {
+            rest,
+            sections,
+            done,
+            ..
+        } = self;
+        ArpaSliceEntries {
+            rest,
+            sections,
+            done,
+            precision: PhantomData,
+        }
+    }
+
+    /// Toggles strict validation of probabilities and backoffs, see [super::ArpaReader::strict]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.sections.set_strict(strict);
+        self
+    }
+
+    /// Reads only the sections up to `max_order`, see [super::ArpaReader::with_max_order]
+    pub fn with_max_order(mut self, max_order: NonZeroUsize) -> Self {
+        self.sections.set_max_order(max_order);
+        self
+    }
+
+    /// Calls `callback` every `every` n-grams and after each section, see
+    /// [super::ArpaReader::with_progress]
+    pub fn with_progress(
+        mut self,
+        every: NonZeroUsize,
+        callback: impl FnMut(ArpaProgress) + Send + 'static,
+    ) -> Self {
+        self.sections.set_progress(every, Box::new(callback));
+        self
+    }
+
+    /// Returns the order of the model, lower than the file's if it was capped
+    pub fn order(&self) -> NonZeroUsize {
+        self.sections.order()
+    }
+
+    pub fn counts(&self) -> &Counts {
+        self.sections.counts()
+    }
+
+    fn next_entry(&mut self) -> Result<Option<ArpaSliceEntry<'a, F>>, ArpaReadError> {
+        while !self.sections.is_done() {
+            let line = self.next_line()?;
+            if let Some(entry) = self.sections.feed(line, false)? {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Splits the next line off the remaining bytes
+    fn next_line(&mut self) -> Result<Option<&'a str>, ArpaReadError> {
+        if self.rest.is_empty() {
+            return Ok(None);
+        }
+        let end = self
+            .rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .unwrap_or(self.rest.len());
+        let (line, rest) = self.rest.split_at(end);
+        self.rest = rest.get(1..).unwrap_or_default();
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        std::str::from_utf8(line).map(Some).map_err(|err| {
+            ArpaReadError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })
+    }
+}
+
+impl<'a, F> Iterator for ArpaSliceEntries<'a, F>
+where
+    F: FromStr + Copy + Default + Into<f64>,
+{
+    type Item = Result<ArpaSliceEntry<'a, F>, ArpaReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let entry = self.next_entry().transpose();
+        if !matches!(entry, Some(Ok(_))) {
+            self.done = true;
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::BufReader;
+    use std::num::NonZeroUsize;
+
+    use approx::assert_abs_diff_eq;
+
+    use super::ArpaSliceEntries;
+    use crate::reader::arpa::{ArpaReadError, ArpaReader};
+
+    #[test]
+    fn slice_entries_match_entries() {
+        let data = std::fs::read("test_data/arpa/lm_small.arpa").unwrap();
+        let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+        let entries = ArpaReader::new(BufReader::new(fd))
+            .unwrap()
+            .into_entries()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        let slice_entries = ArpaSliceEntries::new(&data)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), slice_entries.len());
+        for ((order, owned), borrowed) in entries.iter().zip(&slice_entries) {
+            assert_eq!(*order, borrowed.order);
+            assert_eq!(owned.ngram.0, borrowed.ngram);
+            assert_abs_diff_eq!(owned.prob_backoff.log_prob, borrowed.log_prob);
+            assert_abs_diff_eq!(owned.prob_backoff.backoff, borrowed.backoff);
+        }
+    }
+
+    #[test]
+    fn slice_entries_handle_srilm_and_comments() {
+        for (file_name, start_log_prob) in [
+            ("test_data/arpa/lm_srilm.arpa", -99.0),
+            ("test_data/arpa/lm_comments.arpa", -1.0),
+        ] {
+            let data = std::fs::read(file_name).unwrap();
+            let entries = ArpaSliceEntries::new(&data).unwrap();
+            let n_entries = entries
+                .counts()
+                .counts()
+                .iter()
+                .map(|count| count.cardinality)
+                .sum::<usize>();
+            let parsed = entries.map(Result::unwrap).collect::<Vec<_>>();
+            assert_eq!(parsed.len(), n_entries);
+            assert_eq!(parsed[0].backoff, 0.0);
+            assert_eq!(parsed[1].ngram, "<s>");
+            assert_abs_diff_eq!(parsed[1].log_prob, start_log_prob);
+            assert_abs_diff_eq!(parsed[1].backoff, -0.5);
+        }
+    }
+
+    #[test]
+    fn slice_entries_stop_after_error() {
+        let data = std::fs::read("test_data/arpa/lm_truncated.arpa").unwrap();
+        let mut entries = ArpaSliceEntries::new(&data).unwrap();
+        assert_eq!(entries.by_ref().take_while(Result::is_ok).count(), 6);
+        assert!(entries.next().is_none());
+
+        let data = std::fs::read("test_data/arpa/lm_no_end.arpa").unwrap();
+        let last = ArpaSliceEntries::new(&data).unwrap().last().unwrap();
+        assert!(matches!(last, Err(ArpaReadError::EndMarkerMissing)));
+    }
+
+    #[test]
+    fn slice_entries_are_validated_and_capped() {
+        let data = std::fs::read("test_data/arpa/lm_positive_prob.arpa").unwrap();
+        let err = ArpaSliceEntries::new(&data)
+            .unwrap()
+            .strict(true)
+            .find_map(Result::err)
+            .unwrap();
+        assert!(matches!(err, ArpaReadError::InvalidValue { line: 12, .. }));
+
+        let data = std::fs::read("test_data/arpa/lm_small.arpa").unwrap();
+        let entries = ArpaSliceEntries::new(&data)
+            .unwrap()
+            .with_max_order(NonZeroUsize::new(2).unwrap())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries.len(), 25);
+        assert!(entries[12..]
+            .iter()
+            .all(|entry| entry.order.get() == 2 && entry.backoff == 0.0));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapped_entries_match_slice_entries() {
+        let arpa = super::MappedArpa::open("test_data/arpa/lm_small.arpa").unwrap();
+        let data = std::fs::read("test_data/arpa/lm_small.arpa").unwrap();
+        assert_eq!(arpa.as_bytes(), data.as_slice());
+        assert!(arpa
+            .entries()
+            .unwrap()
+            .map(Result::unwrap)
+            .eq(ArpaSliceEntries::new(&data).unwrap().map(Result::unwrap)));
+    }
+}
@@ -88,10 +88,91 @@ fn test_header() {
             NGramCardinality::try_from_order_and_cardinality(2, 18349).unwrap()
         ])
         .unwrap(),
-        err.counts
+        *err.counts()
     )
 }
 
+#[test]
+fn test_write_dot() {
+    let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let sections = read_arpa(BufReader::new(fd)).unwrap();
+    let mut dot = vec![];
+    sections.write_dot(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph arpa {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("  \"\" -> \"i\" [label=\"i (-0.91229796)\"];\n"));
+    assert!(dot.contains("  \"i\" -> \"i have\" [label=\"have (-0.5346796)\"];\n"));
+    assert!(dot.contains("  \"i have\" -> \"i have a\" [label=\"a (-0.10225761)\"];\n"));
+    assert!(dot.contains("  \"i have\" [label=\"i have\\nbackoff: -0.30103\"];\n"));
+}
+
+#[test]
+#[allow(clippy::approx_constant)]
+fn test_reads_f64() {
+    let fd = std::fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let reader = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .with_precision::<f64>();
+    let ArpaFileSections {
+        backoffs,
+        no_backoff,
+        ..
+    } = reader.into_arpa_sections().unwrap();
+    assert_eq!(backoffs[0][3].prob_backoff.log_prob, -0.91229796f64);
+    assert_eq!(backoffs[0][3].prob_backoff.backoff, -0.30103f64);
+    assert_eq!(no_backoff[1].prob, -0.10757457f64);
+    assert_eq!(no_backoff[1].ngram, NGram("you remember i".to_string()));
+}
+
+#[test]
+fn test_strict_rejects_positive_log_prob() {
+    let fd = fs::File::open("test_data/arpa/lm_positive_prob.arpa").unwrap();
+    let err = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .strict(true)
+        .into_arpa_sections();
+    match err {
+        Ok(_) => panic!("returned Ok when it should have been `Err(InvalidValue)`"),
+        Err(err) => assert!(matches!(
+            err,
+            ArpaReadError::InvalidValue { line: 12, value, .. } if value > 0.
+        )),
+    }
+}
+
+#[test]
+fn test_strict_rejects_nan_backoff() {
+    let fd = fs::File::open("test_data/arpa/lm_nan_backoff.arpa").unwrap();
+    let err = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .with_precision::<f64>()
+        .strict(true)
+        .into_arpa_sections();
+    match err {
+        Ok(_) => panic!("returned Ok when it should have been `Err(InvalidValue)`"),
+        Err(err) => assert!(matches!(
+            err,
+            ArpaReadError::InvalidValue { line: 8, value, .. } if value.is_nan()
+        )),
+    }
+}
+
+#[test]
+fn test_lenient_accepts_invalid_values() {
+    for file in ["lm_positive_prob.arpa", "lm_nan_backoff.arpa"] {
+        let fd = fs::File::open(format!("test_data/arpa/{}", file)).unwrap();
+        assert!(read_arpa(BufReader::new(fd)).is_ok());
+    }
+}
+
+#[test]
+fn test_strict_accepts_valid_file() {
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let reader = ArpaReader::new(BufReader::new(fd)).unwrap().strict(true);
+    assert!(reader.into_arpa_sections().is_ok());
+}
+
 macro_rules! prob_backoff_ngram {
     (
         $(
@@ -181,3 +262,263 @@ pub fn get_unigrams() -> Vec<ProbBackoffNgram> {
     -1.0761548,	"you", -0.30103;
     -1.0761548,	"remember", -0.30103)
 }
+
+#[test]
+fn test_entries_match_sections() {
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let sections = read_arpa(BufReader::new(fd)).unwrap();
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let entries = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .into_entries()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let expected = sections
+        .backoffs
+        .iter()
+        .flatten()
+        .map(|entry| (entry.ngram.clone(), entry.prob_backoff.log_prob))
+        .chain(
+            sections
+                .no_backoff
+                .iter()
+                .map(|entry| (entry.ngram.clone(), entry.prob)),
+        )
+        .collect::<Vec<_>>();
+    assert_eq!(entries.len(), expected.len());
+    for ((order, entry), (ngram, log_prob)) in entries.iter().zip(expected) {
+        assert_eq!(entry.ngram, ngram);
+        assert_eq!(entry.prob_backoff.log_prob, log_prob);
+        assert_eq!(order.get(), entry.ngram.0.split(' ').count());
+    }
+    assert_eq!(entries.last().unwrap().1.prob_backoff.backoff, 0.);
+}
+
+#[test]
+fn test_entries_stop_after_error() {
+    let fd = fs::File::open("test_data/arpa/lm_truncated.arpa").unwrap();
+    let mut entries = ArpaReader::new(BufReader::new(fd)).unwrap().into_entries();
+    assert_eq!(entries.by_ref().take_while(Result::is_ok).count(), 6);
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn test_errors_carry_line_context() {
+    let fd = fs::File::open("test_data/arpa/lm_truncated.arpa").unwrap();
+    let err = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .into_arpa_sections()
+        .err()
+        .unwrap();
+    assert_eq!(err.line(), Some(14));
+    match err {
+        ArpaReadError::AtLine {
+            line,
+            content,
+            source,
+        } => {
+            assert_eq!(line, 14);
+            assert_eq!(content, "");
+            assert!(matches!(*source, ArpaReadError::NoBackoffSectionError));
+        }
+        err => panic!("expected line context, got {err:?}"),
+    }
+}
+
+#[test]
+fn test_malformed_lines_name_their_section() {
+    let data = "\\data\\\nngram 1=1\nngram 2=1\n\n\\1-grams:\nx\t<s>\t-0.5\n\n\\2-grams:\n-0.2\t<s> <s>\n\n\\end\\\n";
+    let err = read_arpa(data.as_bytes()).err().unwrap();
+    assert_eq!(err.line(), Some(6));
+    assert!(matches!(
+        err,
+        ArpaReadError::AtLine { source, .. } if matches!(*source, ArpaReadError::BackOffSectionError)
+    ));
+}
+
+#[test]
+fn test_error_content_is_truncated() {
+    let long_line = format!("-0.5 {} x", "ä".repeat(100));
+    let err = super::at_line(3, &long_line, ArpaReadError::BackOffSectionError);
+    match err {
+        ArpaReadError::AtLine { content, .. } => {
+            assert!(content.len() <= super::MAX_ERROR_CONTENT);
+            assert!(long_line.starts_with(&content));
+        }
+        err => panic!("expected line context, got {err:?}"),
+    }
+}
+
+#[test]
+fn test_reads_srilm_quirks() {
+    let fd = fs::File::open("test_data/arpa/lm_srilm.arpa").unwrap();
+    let ArpaFileSections {
+        backoffs,
+        no_backoff,
+        ..
+    } = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .strict(true)
+        .into_arpa_sections()
+        .unwrap();
+    check_probbackoff_for_order(
+        &backoffs[0],
+        prob_backoff_ngram!(-1.0, "<unk>", 0.0;
+            -99.0, "<s>", -0.5;
+            -1.0, "</s>", 0.0;
+            -0.5, "a", -0.25),
+    );
+    assert_eq!(backoffs[0].len(), 4);
+    assert_eq!(no_backoff.len(), 3);
+}
+
+#[test]
+fn test_skips_comments() {
+    let fd = fs::File::open("test_data/arpa/lm_comments.arpa").unwrap();
+    let sections = read_arpa(BufReader::new(fd)).unwrap();
+    assert_eq!(sections.backoffs[0].len(), 4);
+    assert_eq!(sections.backoffs[0][1].ngram, NGram("<s>".to_string()));
+    assert_eq!(sections.no_backoff.len(), 2);
+}
+
+#[test]
+fn test_end_marker_missing() {
+    let fd = fs::File::open("test_data/arpa/lm_no_end.arpa").unwrap();
+    assert!(matches!(
+        read_arpa(BufReader::new(fd)),
+        Err(ArpaReadError::EndMarkerMissing)
+    ));
+
+    let fd = fs::File::open("test_data/arpa/lm_no_end.arpa").unwrap();
+    let entries = ArpaReader::new(BufReader::new(fd)).unwrap().into_entries();
+    let last = entries.last().unwrap();
+    assert!(matches!(last, Err(ArpaReadError::EndMarkerMissing)));
+}
+
+#[test]
+fn test_reads_crlf_line_endings() {
+    let data = fs::read_to_string("test_data/arpa/lm_small.arpa").unwrap();
+    let crlf = data.replace('\n', "\r\n");
+    let ArpaFileSections {
+        backoffs,
+        no_backoff,
+        ..
+    } = read_arpa(crlf.as_bytes()).unwrap();
+    check_probbackoff_for_order(&backoffs[0], get_unigrams());
+    check_probbackoff_for_order(&backoffs[1], get_bigrams());
+    check_prob_for_order(&no_backoff, get_trigrams());
+}
+
+#[test]
+fn test_progress_reports_every_n_and_sections() {
+    use std::num::NonZeroUsize;
+    use std::sync::{Arc, Mutex};
+
+    let reports = Arc::new(Mutex::new(vec![]));
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let sink = reports.clone();
+    ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .with_progress(NonZeroUsize::new(10).unwrap(), move |progress| {
+            sink.lock().unwrap().push(progress)
+        })
+        .into_arpa_sections()
+        .unwrap();
+    let reports = reports.lock().unwrap();
+    let read = reports.iter().map(|p| p.read).collect::<Vec<_>>();
+    assert_eq!(read, vec![10, 12, 20, 25, 30, 37]);
+    assert_eq!(
+        reports.iter().filter(|p| p.section_done()).count(),
+        3,
+        "every section completes once"
+    );
+    let last = reports.last().unwrap();
+    assert_eq!(last.order.get(), 3);
+    assert_eq!(last.total, 37);
+    assert_abs_diff_eq!(last.fraction(), 1.0);
+}
+
+#[test]
+fn test_max_order_caps_sections() {
+    use std::num::NonZeroUsize;
+
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let ArpaFileSections {
+        counts,
+        backoffs,
+        no_backoff,
+    } = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .with_max_order(NonZeroUsize::new(1).unwrap())
+        .into_arpa_sections()
+        .unwrap();
+    assert_eq!(counts.order().get(), 1);
+    assert!(backoffs.is_empty());
+    let unigrams = get_unigrams()
+        .into_iter()
+        .map(|ngram| ProbNgram {
+            ngram: ngram.ngram,
+            prob: ngram.prob_backoff.log_prob,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(no_backoff.len(), unigrams.len());
+    check_prob_for_order(&no_backoff, unigrams);
+
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let entries = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .with_max_order(NonZeroUsize::new(2).unwrap())
+        .into_entries()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 25);
+    assert!(entries[12..]
+        .iter()
+        .all(|(order, ngram)| order.get() == 2 && ngram.prob_backoff.backoff == 0.0));
+}
+
+#[test]
+fn test_selected_sections() {
+    use std::num::NonZeroUsize;
+
+    let orders = [3, 1, 3].map(|order| NonZeroUsize::new(order).unwrap());
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let sections = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .into_selected_sections(orders)
+        .unwrap();
+    assert_eq!(
+        sections
+            .iter()
+            .map(|(order, ngrams)| (order.get(), ngrams.len()))
+            .collect::<Vec<_>>(),
+        vec![(1, 12), (3, 12)]
+    );
+    check_probbackoff_for_order(&sections[0].1, get_unigrams());
+    let trigrams = get_trigrams()
+        .into_iter()
+        .map(|ngram| ProbBackoffNgram {
+            ngram: ngram.ngram,
+            prob_backoff: ProbBackoff {
+                log_prob: ngram.prob,
+                backoff: 0.0,
+            },
+        })
+        .collect();
+    check_probbackoff_for_order(&sections[1].1, trigrams);
+
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let unigrams = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .into_unigrams()
+        .unwrap();
+    assert_eq!(unigrams.len(), 12);
+    check_probbackoff_for_order(&unigrams, get_unigrams());
+
+    let fd = fs::File::open("test_data/arpa/lm_small.arpa").unwrap();
+    let missing = ArpaReader::new(BufReader::new(fd))
+        .unwrap()
+        .into_selected_sections([NonZeroUsize::new(4).unwrap()]);
+    assert!(matches!(missing, Err(ArpaReadError::SectionMissing(order)) if order.get() == 4));
+}
@@ -0,0 +1,125 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::headers::{total_header_size, Counts, FixedParameters, Sanity};
+use crate::{ConfigBuilder, Error};
+
+/// Reads the vocab of a KenLM binary without loading the model
+///
+/// KenLM appends the vocab to binary files as null-terminated strings in the order of their
+/// indices, starting with `<unk>`. Like KenLM when loading, the strings are read at the offset
+/// following the headers and the model, whose size is computed from the counts and the
+/// [FixedParameters]. The position of a word in the returned vector is its index.
+/// ```
+/// use kenlm_rs::reader::binary::read_vocab;
+///
+/// let vocab = read_vocab("test_data/test.bin").unwrap();
+/// assert_eq!(vocab[0], "<unk>");
+/// ```
+pub fn read_vocab(file_name: &str) -> Result<Vec<String>, Error> {
+    let mut fd = File::open(file_name).map_err(|_| Error::FileNotFound(file_name.to_string()))?;
+    if Sanity::from_file(&mut fd)? != Sanity::REFERENCE {
+        return Err(Error::SanityMismatch);
+    }
+    let fixed_params = FixedParameters::from_file(&mut fd)?;
+    if !fixed_params.has_vocabulary() {
+        return Err(Error::ModelHasNoVocab);
+    }
+    let counts = Counts::from_kenlm_binary(&mut fd, &fixed_params)?;
+    let words = counts.counts()[0].cardinality;
+
+    let offset = vocab_offset(&mut fd, &fixed_params, &counts)?;
+    let mut strings = Vec::new();
+    fd.seek(SeekFrom::Start(offset))?;
+    fd.read_to_end(&mut strings)?;
+    if !strings.starts_with(b"<unk>\0") {
+        return Err(Error::VocabFormatError);
+    }
+    let vocab = strings
+        .split(|&b| b == 0)
+        .take(words)
+        .map(|word| String::from_utf8(word.to_vec()).map_err(|_| Error::VocabFormatError))
+        .collect::<Result<Vec<_>, _>>()?;
+    if vocab.len() < words {
+        return Err(Error::VocabFormatError);
+    }
+    Ok(vocab)
+}
+
+/// Offset of the vocab strings, the size of the headers plus the size of the model
+///
+/// Quantized and array-compressed tries store their bits at the start of the quantization
+/// tables and the first middle order, they are read from there the same way KenLM does in
+/// `TrieSearch::UpdateConfigFromBinary`.
+fn vocab_offset(
+    fd: &mut File,
+    fixed_params: &FixedParameters,
+    counts: &Counts,
+) -> Result<u64, Error> {
+    let order = counts.order().get();
+    if order < 2 {
+        return Err(Error::UnsupportedOrder(order));
+    }
+    let model_type = fixed_params.model_type()?;
+    let cardinalities = counts
+        .counts()
+        .iter()
+        .map(|count| count.cardinality as u64)
+        .collect::<Vec<_>>();
+    let header = total_header_size(order) as u64;
+    let mut config = crate::cxx::Config::default();
+    config.apply(&ConfigBuilder::new().probing_multiplier(fixed_params.probing_multiplier));
+
+    if model_type.is_quantized() {
+        let (vocab, _, _) = config.estimate_sizes(&cardinalities, model_type)?;
+        // version, probability bits, backoff bits
+        let bits = read_at(fd, header + vocab, 3)?;
+        config.set_quantization_bits(bits[1], bits[2]);
+    }
+    if model_type.is_array_compressed() && order > 2 {
+        let (vocab, quantization, orders) = config.estimate_sizes(&cardinalities, model_type)?;
+        // version, chopped bits
+        let bits = read_at(fd, header + vocab + quantization + orders[0], 2)?;
+        config.set_pointer_bhiksha_bits(bits[1]);
+    }
+    Ok(header + config.estimate_size(&cardinalities, model_type)?)
+}
+
+fn read_at(fd: &mut File, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0; len];
+    fd.seek(SeekFrom::Start(offset))?;
+    fd.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_vocab;
+    use crate::{Error, Model};
+
+    #[test]
+    fn reads_vocab_of_binaries() {
+        for file_name in [
+            "test_data/test.bin",
+            "test_data/carol.bin",
+            "test_data/carol_probing_bigram.bin",
+        ] {
+            let vocab = read_vocab(file_name).unwrap();
+            let model = Model::new(file_name, true).expect("should exist");
+            assert_eq!(vocab, model.get_vocab().unwrap());
+            assert_eq!(vocab.len(), model.vocab_size());
+        }
+    }
+
+    #[test]
+    fn rejects_binaries_without_vocab() {
+        assert!(matches!(
+            read_vocab("test_data/test_no_vocab.bin"),
+            Err(Error::ModelHasNoVocab)
+        ));
+        assert!(matches!(
+            read_vocab("test_data/arpa/lm_small.arpa"),
+            Err(Error::SanityMismatch | Error::SanityFormatError)
+        ));
+    }
+}
@@ -1,22 +1,27 @@
 pub mod arpa;
+pub mod binary;
 
+/// Log-probability and backoff of an n-gram
+///
+/// `F` is the float type the values are parsed into, `f32` unless the reader was switched to
+/// another precision with [arpa::ArpaReader::with_precision].
 #[derive(Debug, Clone)]
-pub struct ProbBackoff {
-    pub log_prob: f32,
-    pub backoff: f32,
+pub struct ProbBackoff<F = f32> {
+    pub log_prob: F,
+    pub backoff: F,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NGram(String); // TODO: this sensible?
 
 #[derive(Debug, Clone)]
-pub struct ProbBackoffNgram {
+pub struct ProbBackoffNgram<F = f32> {
     pub ngram: NGram,
-    pub prob_backoff: ProbBackoff,
+    pub prob_backoff: ProbBackoff<F>,
 }
 
 #[derive(Debug, Clone)]
-pub struct ProbNgram {
+pub struct ProbNgram<F = f32> {
     pub ngram: NGram,
-    pub prob: f32,
+    pub prob: F,
 }
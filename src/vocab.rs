@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use crate::{Error, WordIdx};
+
+/// Hashes `word` like KenLM's vocabularies do
+///
+/// Mirrors `lm::ngram::detail::HashForVocab` in src/cxx/lm/vocab.cc, which is MurmurHash64A
+/// in src/cxx/util/murmur_hash.cc with seed 0. KenLM looks up words by this hash, the hash is
+/// not the index of the word itself.
+pub fn hash_word(word: &str) -> u64 {
+    const M: u64 = 0xc6a4a7935bd1e995;
+    const R: u32 = 47;
+
+    let bytes = word.as_bytes();
+    let mut h = (bytes.len() as u64).wrapping_mul(M);
+
+    let mut blocks = bytes.chunks_exact(8);
+    for block in &mut blocks {
+        // chunks_exact guarantees 8 bytes
+        let mut k = u64::from_le_bytes(block.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        for (i, &b) in tail.iter().enumerate() {
+            h ^= u64::from(b) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+/// Word to index lookup computing KenLM compatible indices without calling into C++
+///
+/// Like KenLM's vocabularies, words are looked up by [hash_word] and words which are not part
+/// of the vocab map to `<unk>`, which has index 0.
+/// ```
+/// use kenlm_rs::vocab::VocabIndex;
+/// use kenlm_rs::Model;
+///
+/// let index = VocabIndex::from_binary("test_data/test.bin").unwrap();
+/// let model = Model::new("test_data/test.bin", false).unwrap();
+/// assert_eq!(index.index("have"), model.get_word_idx("have"));
+/// assert_eq!(index.index("toast"), model.not_found_idx());
+/// ```
+#[derive(Debug, Clone)]
+pub struct VocabIndex {
+    lookup: HashMap<u64, WordIdx>,
+}
+
+impl VocabIndex {
+    /// Builds the index from words in the order of their indices, i.e. `<unk>` first
+    pub fn from_words(words: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let lookup = words
+            .into_iter()
+            .enumerate()
+            .map(|(idx, word)| (hash_word(word.as_ref()), WordIdx::from_u32(idx as u32)))
+            .collect();
+        VocabIndex { lookup }
+    }
+
+    /// Builds the index from the vocab of a KenLM binary, see [crate::reader::binary::read_vocab]
+    pub fn from_binary(file_name: &str) -> Result<Self, Error> {
+        Ok(Self::from_words(crate::reader::binary::read_vocab(
+            file_name,
+        )?))
+    }
+
+    /// Index of `word`, None if `word` is not part of the vocab
+    pub fn get(&self, word: &str) -> Option<WordIdx> {
+        self.lookup.get(&hash_word(word)).copied()
+    }
+
+    /// Index of `word`, the index of `<unk>` if `word` is not part of the vocab
+    pub fn index(&self, word: &str) -> WordIdx {
+        self.get(word).unwrap_or_else(|| WordIdx::from_u32(0))
+    }
+
+    /// Number of words in the vocab
+    pub fn len(&self) -> usize {
+        self.lookup.len()
+    }
+
+    /// Whether the vocab is empty
+    pub fn is_empty(&self) -> bool {
+        self.lookup.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hash_word, VocabIndex};
+    use crate::Model;
+
+    #[test]
+    fn hashes_like_kenlm() {
+        // computed with util::MurmurHash64A(word, len, 0)
+        assert_eq!(hash_word(""), 0);
+        assert_eq!(hash_word("<unk>"), 16902545233577457963);
+        assert_eq!(hash_word("i"), 15817570140490810055);
+        assert_eq!(hash_word("have"), 11928693643882048719);
+        assert_eq!(hash_word("achieve"), 17075310971227025651);
+        assert_eq!(hash_word("a good deal"), 8825423078153763755);
+        assert_eq!(hash_word("äpfel"), 16638909228421123718);
+    }
+
+    #[test]
+    fn indices_match_kenlm() {
+        for file_name in ["test_data/test.bin", "test_data/carol_probing_bigram.bin"] {
+            let index = VocabIndex::from_binary(file_name).unwrap();
+            let model = Model::new(file_name, true).expect("should exist");
+            assert_eq!(index.len(), model.vocab_size());
+            for word in model.get_vocab().unwrap() {
+                assert_eq!(index.index(word), model.get_word_idx(word));
+            }
+            assert_eq!(index.get("toast"), None);
+            assert_eq!(index.index("toast"), model.not_found_idx());
+        }
+    }
+}